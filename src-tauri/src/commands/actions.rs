@@ -0,0 +1,346 @@
+//! Action items: Markdown checklist entries tracked as structured records.
+//!
+//! The checklist in a note (`- [ ] Send the deck @sam due:2026-03-01`) is the
+//! source of truth. Every save parses it into an `<id>.actions.json` sidecar
+//! that remembers when each item was created and completed, and marking an
+//! item done from the register ticks its box in the Markdown.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::catalog::CatalogState;
+use super::library::LibraryState;
+use super::note_id::NoteId;
+use super::notes::{self, Note, WriteNoteError};
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+const SUFFIX: &str = ".actions.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Open,
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionItem {
+    /// Derived from the item's text, so it survives edits elsewhere in the note.
+    pub item_id: String,
+    pub text: String,
+    pub owner: Option<String>,
+    /// Due date as `YYYY-MM-DD`.
+    pub due: Option<String>,
+    pub status: ActionStatus,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+    /// Zero-based line of the checklist entry in the note body.
+    pub line: usize,
+}
+
+/// An action item as listed across the library, with the note it belongs to.
+#[derive(Serialize, Debug)]
+pub struct NoteActionItem {
+    #[serde(flatten)]
+    pub item: ActionItem,
+    pub note_id: NoteId,
+    pub note_title: String,
+}
+
+/// Filter for `list_action_items`. Items without a due date never match a
+/// date bound.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ActionFilter {
+    pub owner: Option<String>,
+    /// Earliest due date, inclusive, as `YYYY-MM-DD`.
+    pub due_from: Option<String>,
+    /// Latest due date, inclusive, as `YYYY-MM-DD`.
+    pub due_to: Option<String>,
+    pub include_done: bool,
+}
+
+impl ActionFilter {
+    fn matches(&self, item: &ActionItem) -> bool {
+        if !self.include_done && item.status == ActionStatus::Done {
+            return false;
+        }
+        if let Some(owner) = &self.owner {
+            let owner = owner.trim_start_matches('@');
+            if !item
+                .owner
+                .as_deref()
+                .is_some_and(|o| o.eq_ignore_ascii_case(owner))
+            {
+                return false;
+            }
+        }
+        // ISO dates compare correctly as strings.
+        let due = item.due.as_deref().unwrap_or_default();
+        let before_from = |from: &str| due.is_empty() || due < from;
+        let after_to = |to: &str| due.is_empty() || due > to;
+        if self.due_from.as_deref().is_some_and(before_from)
+            || self.due_to.as_deref().is_some_and(after_to)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+pub(crate) fn sidecar_path(dir: &Path, id: &NoteId) -> PathBuf {
+    dir.join(format!("{id}{SUFFIX}"))
+}
+
+/// A checklist entry as written in the note.
+struct Checkbox<'a> {
+    line: usize,
+    done: bool,
+    text: &'a str,
+}
+
+/// Split `- [ ] text` into its state and text.
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let rest = line.trim_start();
+    let rest = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| rest.strip_prefix(bullet))?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    Some((done, rest[3..].trim()))
+}
+
+fn checkboxes(content: &str) -> Vec<Checkbox<'_>> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let (done, text) = checkbox(text)?;
+            Some(Checkbox { line, done, text })
+        })
+        .collect()
+}
+
+/// Pull `@owner` and `due:YYYY-MM-DD` out of an entry, returning
+/// `(text, owner, due)`.
+fn split_entry(entry: &str) -> (String, Option<String>, Option<String>) {
+    let mut owner = None;
+    let mut due = None;
+    let mut words = Vec::new();
+    for word in entry.split_whitespace() {
+        let handle = word
+            .strip_prefix('@')
+            .map(|h| h.trim_end_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|h| !h.is_empty());
+        let date = word
+            .strip_prefix("due:")
+            .filter(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok());
+        match (handle, date) {
+            (Some(handle), _) if owner.is_none() => owner = Some(handle.to_string()),
+            (_, Some(date)) if due.is_none() => due = Some(date.to_string()),
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), owner, due)
+}
+
+fn item_id(text: &str, occurrence: usize) -> String {
+    let digest = Sha256::digest(format!("{}\n{occurrence}", text.to_lowercase()));
+    digest.iter().take(6).map(|b| format!("{b:02x}")).collect()
+}
+
+fn read_sidecar(path: &Path) -> Vec<ActionItem> {
+    fs::read(path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// The action items in `content`, carrying over timestamps from `stored`.
+fn parse_items(content: &str, stored: &[ActionItem]) -> Vec<ActionItem> {
+    let stored: HashMap<&str, &ActionItem> =
+        stored.iter().map(|i| (i.item_id.as_str(), i)).collect();
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    checkboxes(content)
+        .into_iter()
+        .filter_map(|checkbox| {
+            let (text, owner, due) = split_entry(checkbox.text);
+            if text.is_empty() {
+                return None;
+            }
+            let occurrence = seen.entry(text.to_lowercase()).or_default();
+            let item_id = item_id(&text, *occurrence);
+            *occurrence += 1;
+
+            let previous = stored.get(item_id.as_str());
+            let completed_at = checkbox.done.then(|| {
+                previous
+                    .and_then(|p| p.completed_at.clone())
+                    .unwrap_or_else(|| now.clone())
+            });
+            Some(ActionItem {
+                item_id,
+                text,
+                owner,
+                due,
+                status: if checkbox.done {
+                    ActionStatus::Done
+                } else {
+                    ActionStatus::Open
+                },
+                created_at: previous
+                    .map(|p| p.created_at.clone())
+                    .unwrap_or_else(|| now.clone()),
+                completed_at,
+                line: checkbox.line,
+            })
+        })
+        .collect()
+}
+
+/// Action items in `content`, without stored timestamps.
+pub(crate) fn items_in(content: &str) -> Vec<ActionItem> {
+    parse_items(content, &[])
+}
+
+/// Action items of a note as it is on disk.
+pub(crate) fn note_items(dir: &Path, note: &Note) -> Vec<ActionItem> {
+    parse_items(&note.content, &read_sidecar(&sidecar_path(dir, &note.id)))
+}
+
+/// The sidecar to commit alongside a save of note `id` with `content`, or
+/// `None` if the note has never had action items.
+pub(crate) fn sidecar_for(
+    dir: &Path,
+    id: &NoteId,
+    content: &str,
+) -> Result<Option<(PathBuf, Vec<u8>)>, String> {
+    let path = sidecar_path(dir, id);
+    let items = parse_items(content, &read_sidecar(&path));
+    if items.is_empty() && !path.exists() {
+        return Ok(None);
+    }
+    let json = serde_json::to_vec_pretty(&items).map_err(|e| e.to_string())?;
+    Ok(Some((path, json)))
+}
+
+/// An open checklist entry for `item`, as it would be typed.
+pub(crate) fn checklist_line(item: &ActionItem) -> String {
+    let mut line = format!("- [ ] {}", item.text);
+    if let Some(owner) = &item.owner {
+        line.push_str(&format!(" @{owner}"));
+    }
+    if let Some(due) = &item.due {
+        line.push_str(&format!(" due:{due}"));
+    }
+    line
+}
+
+/// `content` with the checkbox on `line` set to `done`.
+fn set_checkbox(content: &str, line: usize, done: bool) -> Option<String> {
+    let mut lines: Vec<String> = content.split_inclusive('\n').map(str::to_string).collect();
+    let target = lines.get_mut(line)?;
+    let at = if done {
+        target.find("[ ]")
+    } else {
+        target.find("[x]").or_else(|| target.find("[X]"))
+    }?;
+    target.replace_range(at..at + 3, if done { "[x]" } else { "[ ]" });
+    Some(lines.concat())
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Action items across the library, by due date (undated last), then age.
+/// Only open items unless `filter.include_done` is set.
+#[tauri::command]
+pub fn list_action_items(
+    library: tauri::State<LibraryState>,
+    filter: Option<ActionFilter>,
+) -> Result<Vec<NoteActionItem>, String> {
+    let dir = library.notes_dir()?;
+    let filter = filter.unwrap_or_default();
+    let mut items = Vec::new();
+
+    for id in notes::note_ids(&dir) {
+        let Ok(note) = notes::load_note(&dir, &id) else {
+            continue;
+        };
+        for item in note_items(&dir, &note) {
+            if filter.matches(&item) {
+                items.push(NoteActionItem {
+                    item,
+                    note_id: id.clone(),
+                    note_title: note.meta.title.clone(),
+                });
+            }
+        }
+    }
+
+    items.sort_by(|a, b| {
+        let due = |i: &NoteActionItem| (i.item.due.is_none(), i.item.due.clone());
+        due(a)
+            .cmp(&due(b))
+            .then_with(|| a.item.created_at.cmp(&b.item.created_at))
+    });
+    Ok(items)
+}
+
+/// Mark an action item done (or open again) by ticking its box in the note.
+#[tauri::command]
+pub fn set_action_item_done(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    note_id: NoteId,
+    item_id: String,
+    done: bool,
+) -> Result<ActionItem, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let note = notes::load_note(&dir, &note_id)?;
+    let item = note_items(&dir, &note)
+        .into_iter()
+        .find(|i| i.item_id == item_id)
+        .ok_or_else(|| format!("No action item {item_id} in note {note_id}"))?;
+    if (item.status == ActionStatus::Done) == done {
+        return Ok(item);
+    }
+
+    let content = set_checkbox(&note.content, item.line, done)
+        .ok_or_else(|| format!("Action item {item_id} is no longer a checkbox"))?;
+    let mut meta = note.meta;
+    meta.updated_at = chrono::Utc::now().to_rfc3339();
+    notes::save_note(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        note_id.clone(),
+        content,
+        meta,
+        Some(note.revision),
+    )
+    .map_err(|e| match e {
+        WriteNoteError::Conflict { .. } => {
+            format!("Note {note_id} changed while updating the action item; try again")
+        }
+        WriteNoteError::Failed { message } => message,
+    })?;
+
+    let note = notes::load_note(&dir, &note_id)?;
+    note_items(&dir, &note)
+        .into_iter()
+        .find(|i| i.item_id == item_id)
+        .ok_or_else(|| format!("No action item {item_id} in note {note_id}"))
+}
@@ -1,5 +1,13 @@
 use serde::{Deserialize, Serialize};
 
+use super::decisions;
+use super::encryption;
+use super::history::{self, SnapshotReason};
+use super::library::LibraryState;
+use super::note_id::NoteId;
+use super::notes;
+use super::watcher::WatcherState;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EnhanceRequest {
     pub content: String,
@@ -7,6 +15,11 @@ pub struct EnhanceRequest {
     pub provider: String, // "local" | "openai" | "anthropic"
     pub api_key: Option<String>,
     pub model: Option<String>,
+    /// The note being enhanced; its current text is snapshotted first so the
+    /// rewrite can be undone from history, and extracted decisions are
+    /// recorded in its decision log.
+    #[serde(default)]
+    pub note_id: Option<NoteId>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -17,11 +30,20 @@ pub struct EnhanceResponse {
 /// Routes AI requests to the appropriate provider.
 /// Local: calls llama-server sidecar on localhost:8080
 /// Cloud: calls provider API directly with user's key
+/// Refuses encrypted notes while the library is locked.
 #[tauri::command]
-pub async fn enhance_note(request: EnhanceRequest) -> Result<EnhanceResponse, String> {
+pub async fn enhance_note(
+    library: tauri::State<'_, LibraryState>,
+    watcher: tauri::State<'_, WatcherState>,
+    request: EnhanceRequest,
+) -> Result<EnhanceResponse, String> {
+    if let Some(id) = &request.note_id {
+        encryption::check_readable(&library.notes_dir()?, id)?;
+        snapshot_before_rewrite(&library, id, &request.content)?;
+    }
     let prompt = build_prompt(&request.content, &request.mode);
 
-    match request.provider.as_str() {
+    let response = match request.provider.as_str() {
         "local" => call_local_llama(&prompt, request.model.as_deref()).await,
         "openai" => {
             let key = request.api_key.ok_or("OpenAI API key required")?;
@@ -32,7 +54,30 @@ pub async fn enhance_note(request: EnhanceRequest) -> Result<EnhanceResponse, St
             call_anthropic(&prompt, &key, request.model.as_deref()).await
         }
         _ => Err(format!("Unknown provider: {}", request.provider)),
+    }?;
+
+    if let (Some(id), "decisions") = (&request.note_id, request.mode.as_str()) {
+        let dir = library.notes_dir()?;
+        if let Err(e) = decisions::record_extracted(&dir, &watcher, id, &response.result) {
+            eprintln!("Failed to record decisions for note {id}: {e}");
+        }
+    }
+    Ok(response)
+}
+
+/// Snapshot note `id` as the user sees it: the saved note, with `content` in
+/// place of the body when the latest edits have not been saved yet.
+fn snapshot_before_rewrite(library: &LibraryState, id: &NoteId, content: &str) -> Result<(), String> {
+    let lib = library.active()?;
+    let Ok(mut note) = notes::load_note(&lib.notes_dir()?, id) else {
+        return Ok(());
+    };
+    if note.content != content {
+        note.content = content.to_string();
+        // Unsaved text has no on-disk revision; derive a stand-in from it.
+        note.revision = notes::revision_of(content.as_bytes(), note.revision.as_bytes());
     }
+    history::snapshot(&lib, &note, SnapshotReason::BeforeAi).map(|_| ())
 }
 
 fn build_prompt(content: &str, mode: &str) -> String {
@@ -44,10 +89,10 @@ fn build_prompt(content: &str, mode: &str) -> String {
             "Summarize the following meeting notes in 3-5 concise bullet points. Output only the bullets in Markdown.\n\n{content}"
         ),
         "action_items" => format!(
-            "Extract all action items from the following meeting notes. For each, note the owner if mentioned and any deadline. Output as a Markdown checklist.\n\n{content}"
+            "Extract all action items from the following meeting notes. Output only a Markdown checklist with one item per line in the form `- [ ] <task> @<owner> due:<YYYY-MM-DD>`, leaving out the owner or due date when the notes do not mention them.\n\n{content}"
         ),
         "decisions" => format!(
-            "Extract all decisions made in the following meeting notes. Output as a Markdown list.\n\n{content}"
+            "Extract all decisions made in the following meeting notes. Output only a Markdown list with one decision per line in the form `- <decision> — <rationale>`, leaving out the rationale when the notes do not give one.\n\n{content}"
         ),
         _ => format!("Process the following meeting notes:\n\n{content}"),
     }
@@ -0,0 +1,288 @@
+//! Crash-safe file writes.
+//!
+//! Every file is written to a hidden temp file next to its destination,
+//! fsynced and then renamed over the destination, so a crash leaves either the
+//! old or the new contents but never a truncated file. Groups of files that
+//! must change together (a note and its meta sidecar) are committed through a
+//! small journal in the library root: once the journal is on disk the write is
+//! considered done, and `recover` rolls it forward if the renames were cut
+//! short.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+
+const TMP_SUFFIX: &str = ".tmp";
+const JOURNAL_PREFIX: &str = ".brief-";
+const JOURNAL_SUFFIX: &str = ".commit";
+
+/// Serialises multi-file commits against readers in this process, so
+/// `read_note` never sees a new note body next to an old meta sidecar.
+static NOTE_IO: RwLock<()> = RwLock::new(());
+static TXN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Hold while reading files that are written with `write_files`.
+pub fn read_guard() -> RwLockReadGuard<'static, ()> {
+    NOTE_IO.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Journal {
+    /// `(temp, destination)` pairs, relative to the library root.
+    renames: Vec<(PathBuf, PathBuf)>,
+}
+
+fn txn_id() -> String {
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let n = TXN_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{nanos}-{n}", std::process::id())
+}
+
+fn temp_path(dest: &Path, txn: &str) -> io::Result<PathBuf> {
+    let name = dest
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    Ok(dest.with_file_name(format!(".{}.{txn}{TMP_SUFFIX}", name.to_string_lossy())))
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Make a rename or unlink inside `dir` durable.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = dir;
+    }
+    Ok(())
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
+
+/// Atomically replace a single file.
+pub fn write_file(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = temp_path(path, &txn_id())?;
+    if let Err(e) = write_synced(&tmp, bytes) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, path)?;
+    sync_dir(parent(path))
+}
+
+/// Atomically replace several files under `root`, and delete those in
+/// `remove`, as one unit: after a crash either all of them have their new
+/// contents (possibly after `recover`) or none do. A deletion is journalled as
+/// a rename to a temp file, which `recover` then clears away.
+pub fn write_files(
+    root: &Path,
+    files: &[(PathBuf, Vec<u8>)],
+    remove: &[PathBuf],
+) -> io::Result<()> {
+    let txn = txn_id();
+    let mut renames = Vec::with_capacity(files.len());
+
+    let staged: io::Result<()> = files.iter().try_for_each(|(dest, bytes)| {
+        let tmp = temp_path(dest, &txn)?;
+        write_synced(&tmp, bytes)?;
+        renames.push((tmp, dest.clone()));
+        Ok(())
+    });
+    if let Err(e) = staged {
+        for (tmp, _) in &renames {
+            let _ = fs::remove_file(tmp);
+        }
+        return Err(e);
+    }
+
+    let mut removals = Vec::with_capacity(remove.len());
+    for path in remove.iter().filter(|path| path.exists()) {
+        removals.push((path.clone(), temp_path(path, &txn)?));
+    }
+
+    let relative = |p: &Path| p.strip_prefix(root).unwrap_or(p).to_path_buf();
+    let journal = Journal {
+        renames: renames
+            .iter()
+            .chain(&removals)
+            .map(|(tmp, dest)| (relative(tmp), relative(dest)))
+            .collect(),
+    };
+    let journal_path = root.join(format!("{JOURNAL_PREFIX}{txn}{JOURNAL_SUFFIX}"));
+    let journal_json = serde_json::to_vec(&journal).map_err(io::Error::other)?;
+
+    let _guard = NOTE_IO.write().unwrap_or_else(|e| e.into_inner());
+    write_synced(&journal_path, &journal_json)?;
+    sync_dir(root)?;
+
+    // Commit point: from here on `recover` will finish the job after a crash.
+    for (tmp, dest) in renames.iter().chain(&removals) {
+        fs::rename(tmp, dest)?;
+    }
+    for (_, dest) in &renames {
+        sync_dir(parent(dest))?;
+    }
+    for (_, tmp) in &removals {
+        fs::remove_file(tmp)?;
+        sync_dir(parent(tmp))?;
+    }
+    fs::remove_file(&journal_path)?;
+    Ok(())
+}
+
+/// Move files or directories, creating destination parents as needed. Each
+/// rename is atomic; readers in this process see all of them or none.
+pub fn move_files(moves: &[(PathBuf, PathBuf)]) -> io::Result<()> {
+    let _guard = NOTE_IO.write().unwrap_or_else(|e| e.into_inner());
+    for (from, to) in moves {
+        fs::create_dir_all(parent(to))?;
+        fs::rename(from, to)?;
+    }
+    for (from, to) in moves {
+        sync_dir(parent(from))?;
+        sync_dir(parent(to))?;
+    }
+    Ok(())
+}
+
+/// Finish interrupted commits and delete orphaned temp files under `root`.
+/// Run once per library before anything reads from it.
+pub fn recover(root: &Path) -> io::Result<()> {
+    let _guard = NOTE_IO.write().unwrap_or_else(|e| e.into_inner());
+
+    for entry in fs::read_dir(root)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !(name.starts_with(JOURNAL_PREFIX) && name.ends_with(JOURNAL_SUFFIX)) {
+            continue;
+        }
+        let path = entry.path();
+        match fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<Journal>(&raw).ok())
+        {
+            Some(journal) => {
+                for (tmp, dest) in journal.renames {
+                    let tmp = root.join(tmp);
+                    if tmp.exists() {
+                        fs::rename(&tmp, root.join(dest))?;
+                    }
+                }
+            }
+            // A torn journal means the crash happened before the commit point.
+            None => eprintln!("Discarding incomplete write journal {name}"),
+        }
+        fs::remove_file(&path)?;
+    }
+
+    remove_temp_files(root)?;
+    sync_dir(root)
+}
+
+/// Whether `name` is a temp file from `temp_path`: `.<name>.<txn>.tmp`, with
+/// `<txn>` as made by `txn_id`. Temp files of sync tools and editors (such as
+/// Syncthing's `.syncthing.<name>.tmp`) do not match.
+fn is_own_temp(name: &str) -> bool {
+    let Some(stem) = name
+        .strip_prefix('.')
+        .and_then(|rest| rest.strip_suffix(TMP_SUFFIX))
+    else {
+        return false;
+    };
+    let Some((file, txn)) = stem.rsplit_once('.') else {
+        return false;
+    };
+    let parts: Vec<&str> = txn.split('-').collect();
+    !file.is_empty()
+        && parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn remove_temp_files(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !name.starts_with('.') {
+                remove_temp_files(&entry.path())?;
+            }
+        } else if is_own_temp(&name) {
+            eprintln!("Removing orphaned temp file {}", entry.path().display());
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_our_temp_names_match() {
+        let tmp = temp_path(Path::new("notes/2026-03-01-kickoff.md"), &txn_id()).unwrap();
+        assert!(is_own_temp(&tmp.file_name().unwrap().to_string_lossy()));
+        assert!(is_own_temp(".a.md.1-2-3.tmp"));
+        assert!(!is_own_temp(".syncthing.2026-03-01-kickoff.md.tmp"));
+        assert!(!is_own_temp(".a.md.tmp"));
+        assert!(!is_own_temp("a.md.1-2-3.tmp"));
+        assert!(!is_own_temp("..1-2-3.tmp"));
+        assert!(!is_own_temp(".a.md.1-2-x.tmp"));
+    }
+
+    #[test]
+    fn recovery_finishes_commits_and_keeps_foreign_temp_files() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let folder = root.join("Projects");
+        fs::create_dir(&folder).unwrap();
+        let note = folder.join("2026-03-01-kickoff.md");
+        let meta = folder.join("2026-03-01-kickoff.meta.json");
+        fs::write(&note, "old body").unwrap();
+        fs::write(&meta, "{}").unwrap();
+
+        // A commit cut short after its journal was written: the body was
+        // renamed into place, the meta sidecar was not.
+        let txn = txn_id();
+        let meta_tmp = temp_path(&meta, &txn).unwrap();
+        fs::write(&meta_tmp, r#"{"title":"Kickoff"}"#).unwrap();
+        fs::write(&note, "new body").unwrap();
+        let journal = Journal {
+            renames: vec![(
+                meta_tmp.strip_prefix(root).unwrap().to_path_buf(),
+                meta.strip_prefix(root).unwrap().to_path_buf(),
+            )],
+        };
+        let journal_path = root.join(format!("{JOURNAL_PREFIX}{txn}{JOURNAL_SUFFIX}"));
+        fs::write(&journal_path, serde_json::to_vec(&journal).unwrap()).unwrap();
+
+        // A `write_file` interrupted before its rename, and a sync in flight.
+        let orphan = temp_path(&note, &txn_id()).unwrap();
+        fs::write(&orphan, "half").unwrap();
+        let syncthing = folder.join(".syncthing.2026-03-01-kickoff.md.tmp");
+        fs::write(&syncthing, "incoming").unwrap();
+
+        recover(root).unwrap();
+        assert_eq!(fs::read_to_string(&note).unwrap(), "new body");
+        assert_eq!(fs::read_to_string(&meta).unwrap(), r#"{"title":"Kickoff"}"#);
+        assert!(!journal_path.exists());
+        assert!(!meta_tmp.exists());
+        assert!(!orphan.exists());
+        assert_eq!(fs::read_to_string(&syncthing).unwrap(), "incoming");
+    }
+}
@@ -0,0 +1,293 @@
+//! Files attached to a note, such as whiteboard photos and pasted screenshots.
+//! They live in `<id>.assets/` next to the note's Markdown file and are linked
+//! from its body by relative paths, e.g. `2026-02-18-kickoff.assets/board.png`.
+//! The directory is one of the note's `note_paths`, so it moves, and goes to
+//! the trash, with the note. An encrypted note's attachments are sealed under
+//! their own names; `read_linked` opens them.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::catalog::CatalogState;
+use super::encryption;
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes;
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+pub(crate) const ASSETS_SUFFIX: &str = ".assets";
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"];
+
+#[derive(Serialize, Debug)]
+pub struct Attachment {
+    pub name: String,
+    pub size: u64,
+    /// Path relative to the note's Markdown file, for links in its body.
+    pub link: String,
+    /// An image or link to the attachment, ready to insert into the body.
+    pub markdown: String,
+}
+
+pub(crate) fn assets_dir(dir: &Path, id: &NoteId) -> PathBuf {
+    dir.join(format!("{id}{ASSETS_SUFFIX}"))
+}
+
+/// How links in the body of note `id` start when they point at its assets.
+fn link_prefix(id: &NoteId) -> String {
+    format!("{}{ASSETS_SUFFIX}/", id.name())
+}
+
+fn extension(name: &str) -> String {
+    name.rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default()
+}
+
+pub(crate) fn is_image(name: &str) -> bool {
+    IMAGE_EXTENSIONS.contains(&extension(name).as_str())
+}
+
+pub(crate) fn mime_type(name: &str) -> &'static str {
+    match extension(name).as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+fn attachment(id: &NoteId, name: &str, size: u64) -> Attachment {
+    let link = format!("{}{name}", link_prefix(id));
+    let markdown = match is_image(name) {
+        true => format!("![{name}]({link})"),
+        false => format!("[{name}]({link})"),
+    };
+    Attachment {
+        name: name.to_string(),
+        size,
+        link,
+        markdown,
+    }
+}
+
+/// `name` as a file name that needs no escaping in a Markdown link.
+fn clean_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let cleaned: String = base
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') => c,
+            _ => '-',
+        })
+        .collect();
+    match cleaned.trim_matches(['.', '-']) {
+        "" => "attachment".to_string(),
+        cleaned => cleaned.to_string(),
+    }
+}
+
+/// `name`, or `name-2`, `name-3`... before the extension, whichever is free.
+fn unique_name(assets: &Path, name: &str) -> String {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while assets.join(&candidate).exists() {
+        candidate = format!("{stem}-{n}{ext}");
+        n += 1;
+    }
+    candidate
+}
+
+/// File extension for pasted data, from its first bytes.
+pub(crate) fn sniff_extension(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => "png",
+        [0xFF, 0xD8, 0xFF, ..] => "jpg",
+        [b'G', b'I', b'F', b'8', ..] => "gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "webp",
+        [b'%', b'P', b'D', b'F', ..] => "pdf",
+        _ => "bin",
+    }
+}
+
+/// Width and height in pixels of a PNG, GIF or JPEG image.
+pub(crate) fn image_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as u32);
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] if bytes.len() >= 24 => {
+            let be32 =
+                |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+            Some((be32(16), be32(20)))
+        }
+        [b'G', b'I', b'F', b'8', _, _, w0, w1, h0, h1, ..] => Some((
+            u16::from_le_bytes([*w0, *w1]).into(),
+            u16::from_le_bytes([*h0, *h1]).into(),
+        )),
+        [0xFF, 0xD8, ..] => {
+            let mut i = 2;
+            while *bytes.get(i)? == 0xFF {
+                let marker = *bytes.get(i + 1)?;
+                // Start-of-frame markers carry the size; C4, C8 and CC do not.
+                if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                    return Some((be16(i + 7)?, be16(i + 5)?));
+                }
+                i += 2 + be16(i + 2)? as usize;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// The name and bytes of the attachment `url`, a link in the body of note
+/// `id`, points at, opened if sealed; `None` for links anywhere else.
+pub(crate) fn read_linked(dir: &Path, id: &NoteId, url: &str) -> Option<(String, Vec<u8>)> {
+    let name = url.trim().strip_prefix(&link_prefix(id))?;
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return None;
+    }
+    let bytes = fs::read(assets_dir(dir, id).join(name)).ok()?;
+    let bytes = encryption::open_file(dir, id, bytes).ok()?;
+    Some((name.to_string(), bytes))
+}
+
+/// Point links to the attachments of a note renamed from `from` to `to` at
+/// its new assets directory. Call with `write_lock` held.
+pub(crate) fn relink(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    from: &NoteId,
+    to: &NoteId,
+) {
+    let Ok(dir) = lib.notes_dir() else {
+        return;
+    };
+    if from.name() == to.name() || !assets_dir(&dir, to).is_dir() {
+        return;
+    }
+    let (old, new) = (link_prefix(from), link_prefix(to));
+    let edited = notes::edit_note_locked(lib, search, catalog, watcher, to, |note| {
+        if !note.content.contains(&old) {
+            return false;
+        }
+        note.content = note.content.replace(&old, &new);
+        note.meta.updated_at = chrono::Utc::now().to_rfc3339();
+        true
+    });
+    if let Err(e) = edited {
+        eprintln!("Failed to update attachment links in {to}: {e}");
+    }
+}
+
+fn save(dir: &Path, id: &NoteId, name: &str, bytes: &[u8]) -> Result<Attachment, String> {
+    if !dir.join(format!("{id}.md")).exists() {
+        return Err(format!("Note not found: {id}"));
+    }
+    let assets = assets_dir(dir, id);
+    fs::create_dir_all(&assets).map_err(|e| e.to_string())?;
+    let name = unique_name(&assets, &clean_name(name));
+    let stored = match encryption::is_encrypted(dir, id) {
+        true => encryption::seal_file(dir, id, bytes)?,
+        false => bytes.to_vec(),
+    };
+    atomic::write_file(&assets.join(&name), &stored).map_err(|e| e.to_string())?;
+    Ok(attachment(id, &name, bytes.len() as u64))
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// The attachments of note `id`, by name.
+#[tauri::command]
+pub fn list_attachments(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+) -> Result<Vec<Attachment>, String> {
+    let dir = library.notes_dir()?;
+    let Ok(entries) = fs::read_dir(assets_dir(&dir, &id)) else {
+        return Ok(vec![]);
+    };
+    let mut attachments: Vec<Attachment> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let size = entry.metadata().ok()?.len();
+            (!name.starts_with('.')).then(|| attachment(&id, &name, size))
+        })
+        .collect();
+    attachments.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(attachments)
+}
+
+/// Attach `bytes`, e.g. an image pasted from the clipboard, to note `id`.
+/// Without a `name` one is made up from the time and the kind of data. A
+/// name already in use gets a number appended.
+#[tauri::command]
+pub fn add_attachment(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    name: Option<String>,
+    bytes: Vec<u8>,
+) -> Result<Attachment, String> {
+    let dir = library.notes_dir()?;
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        format!(
+            "pasted-{}.{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            sniff_extension(&bytes)
+        )
+    });
+    let _lock = notes::write_lock();
+    save(&dir, &id, &name, &bytes)
+}
+
+/// Copy the file at `path` into note `id`'s attachments.
+#[tauri::command]
+pub fn add_attachment_from_path(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    path: String,
+) -> Result<Attachment, String> {
+    let dir = library.notes_dir()?;
+    let source = Path::new(&path);
+    let bytes = fs::read(source).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let _lock = notes::write_lock();
+    save(&dir, &id, &name, &bytes)
+}
+
+/// Delete one attachment. Links to it in the note body are left for the user
+/// to remove.
+#[tauri::command]
+pub fn delete_attachment(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    name: String,
+) -> Result<(), String> {
+    let dir = library.notes_dir()?;
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("Invalid attachment name: {name}"));
+    }
+    let _lock = notes::write_lock();
+    let assets = assets_dir(&dir, &id);
+    fs::remove_file(assets.join(&name)).map_err(|_| format!("Attachment not found: {name}"))?;
+    // Leave no empty directory behind.
+    let _ = fs::remove_dir(&assets);
+    Ok(())
+}
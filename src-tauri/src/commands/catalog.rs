@@ -0,0 +1,600 @@
+//! Metadata cache behind `list_notes`: one row per note with its title, dates,
+//! tags, participants and preview, in an SQLite database in the library's
+//! cache dir. Rows are refreshed whenever a note's files have a different
+//! mtime than when it was cached, so the database can be deleted at any time
+//! and is rebuilt on next use. The `[[...]]` targets of each note are cached
+//! too, for resolving wiki-links. Encrypted notes only get a placeholder row,
+//! so nothing of theirs is cached in plaintext.
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use super::encryption;
+use super::library::Library;
+use super::links;
+use super::note_id::{FolderPath, NoteId};
+use super::notes::{self, Note, NoteSummary};
+
+const DB_FILE: &str = "catalog.sqlite";
+/// Bump when the tables change; older databases are dropped and rebuilt.
+const SCHEMA_VERSION: i32 = 4;
+const PREVIEW_CHARS: usize = 120;
+
+const SCHEMA: &str = "
+    CREATE TABLE notes (
+        id TEXT PRIMARY KEY,
+        folder TEXT NOT NULL,
+        title TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        date TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        preview TEXT NOT NULL,
+        series TEXT,
+        occurrence TEXT,
+        encrypted INTEGER NOT NULL,
+        mtime INTEGER NOT NULL
+    );
+    CREATE TABLE note_tags (
+        id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL COLLATE NOCASE
+    );
+    CREATE TABLE note_participants (
+        id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+        name TEXT NOT NULL COLLATE NOCASE
+    );
+    CREATE TABLE note_links (
+        id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+        target TEXT NOT NULL
+    );
+    CREATE INDEX note_tags_tag ON note_tags(tag);
+    CREATE INDEX note_participants_name ON note_participants(name);
+    CREATE INDEX notes_updated_at ON notes(updated_at);
+    CREATE INDEX notes_date ON notes(date);
+    CREATE INDEX notes_series ON notes(series, occurrence);
+";
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSort {
+    /// Most recently updated first.
+    #[default]
+    Updated,
+    /// Most recently created first.
+    Created,
+    /// Alphabetical by title.
+    Title,
+}
+
+/// Filters, order and page for `list_notes`. Every filter is optional.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct NoteQuery {
+    /// Only notes in this folder or its subfolders.
+    pub folder: Option<FolderPath>,
+    pub tag: Option<String>,
+    pub participant: Option<String>,
+    /// Only occurrences of this meeting series.
+    pub series: Option<String>,
+    /// Earliest meeting day, inclusive, as `YYYY-MM-DD`.
+    pub date_from: Option<String>,
+    /// Latest meeting day, inclusive, as `YYYY-MM-DD`.
+    pub date_to: Option<String>,
+    pub sort: NoteSort,
+    /// Reverse the order `sort` gives.
+    pub reverse: bool,
+    pub offset: usize,
+    /// Page size; all remaining notes if `None`.
+    pub limit: Option<usize>,
+}
+
+/// One page of `list_notes`, with the number of notes matching in total.
+#[derive(Serialize, Debug)]
+pub struct NotePage {
+    pub notes: Vec<NoteSummary>,
+    pub total: usize,
+}
+
+/// A tag or participant and the number of notes it appears in.
+#[derive(Serialize, Debug)]
+pub struct UsageCount {
+    pub name: String,
+    pub count: usize,
+}
+
+/// A note that is an occurrence of a meeting series.
+#[derive(Serialize, Clone, Debug)]
+pub struct Occurrence {
+    pub series_id: String,
+    pub note_id: NoteId,
+    pub title: String,
+    /// Day of the occurrence, as `YYYY-MM-DD`.
+    pub occurrence: String,
+}
+
+/// Every note's title and the raw `[[...]]` targets in its body.
+pub struct LinkIndex {
+    /// Most recently updated first.
+    pub notes: Vec<(NoteId, String)>,
+    /// Source note and link target, as written.
+    pub links: Vec<(NoteId, String)>,
+}
+
+// ── Catalog ──────────────────────────────────────────────────────────────────
+
+struct Catalog {
+    notes_dir: PathBuf,
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl Catalog {
+    /// Open the catalog of `library`, recreating it if it is unreadable or
+    /// from another schema version.
+    fn open(library: &Library) -> Result<Self, String> {
+        let notes_dir = library.notes_dir()?;
+        let cache_dir = library.cache_dir()?;
+        fs::create_dir_all(&cache_dir).map_err(|e| e.to_string())?;
+        let path = cache_dir.join(DB_FILE);
+
+        let conn = match open_db(&path) {
+            Ok(conn) => conn,
+            Err(e) => {
+                eprintln!("Recreating note catalog: {e}");
+                let _ = fs::remove_file(&path);
+                open_db(&path)?
+            }
+        };
+        Ok(Catalog {
+            notes_dir,
+            path,
+            conn,
+        })
+    }
+
+    /// Re-read notes whose files changed since they were cached and drop rows
+    /// for notes that no longer exist.
+    fn reconcile(&mut self) -> Result<(), String> {
+        let cached: HashMap<String, i64> = {
+            let mut stmt = self
+                .conn
+                .prepare_cached("SELECT id, mtime FROM notes")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+        };
+        let on_disk: HashSet<NoteId> = notes::note_ids(&self.notes_dir).into_iter().collect();
+
+        let stale: Vec<(NoteId, i64)> = on_disk
+            .iter()
+            .map(|id| (id.clone(), note_mtime(&self.notes_dir, id)))
+            .filter(|(id, mtime)| cached.get(id.as_str()) != Some(mtime))
+            .collect();
+        let gone: Vec<&String> = cached
+            .keys()
+            .filter(|id| !on_disk.contains(id.as_str()))
+            .collect();
+        if stale.is_empty() && gone.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        for id in gone {
+            delete_row(&tx, id)?;
+        }
+        for (id, mtime) in stale {
+            if encryption::is_encrypted(&self.notes_dir, &id) {
+                upsert_row(&tx, &encryption::placeholder(&id, mtime), mtime)?;
+                continue;
+            }
+            match notes::load_note(&self.notes_dir, &id) {
+                Ok(note) => upsert_row(&tx, &note, mtime)?,
+                Err(e) => eprintln!("Skipping unreadable note {id}: {e}"),
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Distinct values of `column` in `table`, ignoring case, by the number
+    /// of notes they appear in.
+    fn usage(&self, table: &str, column: &str) -> Result<Vec<UsageCount>, String> {
+        let sql = format!(
+            "SELECT {column}, COUNT(DISTINCT id) AS notes FROM {table}
+             GROUP BY {column} ORDER BY notes DESC, {column}"
+        );
+        let mut stmt = self.conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(UsageCount {
+                    name: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    fn link_index(&self) -> Result<LinkIndex, String> {
+        Ok(LinkIndex {
+            notes: self.id_pairs("SELECT id, title FROM notes ORDER BY updated_at DESC, id")?,
+            links: self.id_pairs("SELECT id, target FROM note_links")?,
+        })
+    }
+
+    /// Occurrences of `series_id`, or of every series, by series and date.
+    fn occurrences(&self, series_id: Option<&str>) -> Result<Vec<Occurrence>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT series, id, title, occurrence FROM notes
+                 WHERE series IS NOT NULL AND (?1 IS NULL OR series = ?1)
+                 ORDER BY series, occurrence, id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([series_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        let mut occurrences = Vec::new();
+        for row in rows {
+            let (series_id, id, title, occurrence) = row.map_err(|e| e.to_string())?;
+            if let Ok(note_id) = NoteId::parse(id) {
+                occurrences.push(Occurrence {
+                    series_id,
+                    note_id,
+                    title,
+                    occurrence,
+                });
+            }
+        }
+        Ok(occurrences)
+    }
+
+    /// Rows of a two-column `sql` query whose first column is a note id.
+    fn id_pairs(&self, sql: &str) -> Result<Vec<(NoteId, String)>, String> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        let mut pairs = Vec::new();
+        for row in rows {
+            let (id, value) = row.map_err(|e| e.to_string())?;
+            if let Ok(id) = NoteId::parse(id) {
+                pairs.push((id, value));
+            }
+        }
+        Ok(pairs)
+    }
+
+    fn query(&self, query: &NoteQuery) -> Result<NotePage, String> {
+        let mut clauses = Vec::new();
+        let mut args: Vec<SqlValue> = Vec::new();
+
+        if let Some(folder) = &query.folder {
+            clauses.push("(n.folder = ? OR substr(n.folder, 1, ?) = ?)");
+            args.push(folder.to_string().into());
+            args.push((folder.len() as i64 + 1).into());
+            args.push(format!("{folder}/").into());
+        }
+        if let Some(tag) = non_empty(&query.tag) {
+            clauses.push("EXISTS (SELECT 1 FROM note_tags t WHERE t.id = n.id AND t.tag = ?)");
+            args.push(tag.trim_start_matches('#').to_string().into());
+        }
+        if let Some(name) = non_empty(&query.participant) {
+            clauses.push(
+                "EXISTS (SELECT 1 FROM note_participants p WHERE p.id = n.id AND p.name = ?)",
+            );
+            args.push(name.trim_start_matches('@').to_string().into());
+        }
+        if let Some(series) = non_empty(&query.series) {
+            clauses.push("n.series = ?");
+            args.push(series.to_string().into());
+        }
+        if let Some(from) = non_empty(&query.date_from) {
+            clauses.push("n.date >= ?");
+            args.push(parse_day(from)?.into());
+        }
+        if let Some(to) = non_empty(&query.date_to) {
+            clauses.push("n.date <= ?");
+            args.push(parse_day(to)?.into());
+        }
+        let filter = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+
+        let total: i64 = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM notes n {filter}"),
+                params_from_iter(&args),
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let (column, newest_first) = match query.sort {
+            NoteSort::Updated => ("n.updated_at", true),
+            NoteSort::Created => ("n.created_at", true),
+            NoteSort::Title => ("n.title COLLATE NOCASE", false),
+        };
+        let direction = if newest_first != query.reverse {
+            "DESC"
+        } else {
+            "ASC"
+        };
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        args.push(limit.into());
+        args.push((query.offset as i64).into());
+
+        let sql = format!(
+            "SELECT id, folder, title, created_at, updated_at, tags, preview, encrypted FROM notes n
+             {filter} ORDER BY {column} {direction}, n.id {direction} LIMIT ? OFFSET ?"
+        );
+        let mut stmt = self.conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params_from_iter(&args), |row| {
+                let id: String = row.get(0)?;
+                let tags: String = row.get(5)?;
+                Ok((
+                    id,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    tags,
+                    row.get(6)?,
+                    row.get(7)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let mut notes = Vec::new();
+        for row in rows {
+            let (id, folder, title, created_at, updated_at, tags, preview, encrypted) =
+                row.map_err(|e| e.to_string())?;
+            // Rows come from our own writes, but the file is not ours alone.
+            let Ok(id) = NoteId::parse(id) else {
+                continue;
+            };
+            notes.push(NoteSummary {
+                id,
+                folder,
+                title,
+                created_at,
+                updated_at,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+                preview,
+                encrypted,
+            });
+        }
+        Ok(NotePage {
+            notes,
+            total: total as usize,
+        })
+    }
+}
+
+fn open_db(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+        .map_err(|e| e.to_string())?;
+    let version: i32 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if version != SCHEMA_VERSION {
+        conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS note_links;
+             DROP TABLE IF EXISTS note_tags;
+             DROP TABLE IF EXISTS note_participants;
+             DROP TABLE IF EXISTS notes;
+             {SCHEMA}
+             PRAGMA user_version = {SCHEMA_VERSION};"
+        ))
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(conn)
+}
+
+/// The start of a note body, as listed under its title.
+pub(crate) fn preview(content: &str) -> String {
+    content.chars().take(PREVIEW_CHARS).collect()
+}
+
+fn upsert_row(tx: &Transaction, note: &Note, mtime: i64) -> Result<(), String> {
+    let meta = &note.meta;
+    let tags = serde_json::to_string(&meta.tags).map_err(|e| e.to_string())?;
+    let preview = preview(&note.content);
+    let day = notes::meeting_day(meta).format("%Y-%m-%d").to_string();
+    let folder = note.id.folder().map(String::from).unwrap_or_default();
+
+    delete_row(tx, &note.id)?;
+    tx.execute(
+        "INSERT INTO notes (id, folder, title, created_at, updated_at, date, tags, preview,
+                            series, occurrence, encrypted, mtime)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            note.id.as_str(),
+            folder,
+            meta.title,
+            meta.created_at,
+            meta.updated_at,
+            day,
+            tags,
+            preview,
+            meta.series.as_ref().map(|s| &s.series_id),
+            meta.series.as_ref().map(|s| &s.occurrence),
+            note.encrypted,
+            mtime
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    for tag in &meta.tags {
+        tx.execute(
+            "INSERT INTO note_tags (id, tag) VALUES (?1, ?2)",
+            params![note.id.as_str(), tag.trim_start_matches('#')],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for name in &meta.participants {
+        tx.execute(
+            "INSERT INTO note_participants (id, name) VALUES (?1, ?2)",
+            params![note.id.as_str(), name.trim_start_matches('@')],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for target in links::targets(&note.content) {
+        tx.execute(
+            "INSERT INTO note_links (id, target) VALUES (?1, ?2)",
+            params![note.id.as_str(), target],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn delete_row(tx: &Transaction, id: &str) -> Result<(), String> {
+    tx.execute("DELETE FROM notes WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Latest modification time of a note's Markdown file and meta sidecar, in
+/// milliseconds since the epoch.
+fn note_mtime(dir: &Path, id: &NoteId) -> i64 {
+    [
+        dir.join(format!("{}.md", id)),
+        dir.join(format!("{}.meta.json", id)),
+    ]
+    .iter()
+    .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+    .filter_map(|t| t.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_millis() as i64)
+    .max()
+    .unwrap_or(0)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn parse_day(day: &str) -> Result<String, String> {
+    chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .map_err(|_| format!("Invalid date {day:?}, expected YYYY-MM-DD"))
+}
+
+// ── Catalog state ────────────────────────────────────────────────────────────
+
+/// Lazily opened metadata cache shared by the note commands.
+pub struct CatalogState(Mutex<Option<Catalog>>);
+
+impl Default for CatalogState {
+    fn default() -> Self {
+        CatalogState(Mutex::new(None))
+    }
+}
+
+impl CatalogState {
+    /// Run `f` against the catalog of `library`, (re)opening it if the active
+    /// library changed or the database was deleted since the last call.
+    fn with_catalog<T>(
+        &self,
+        library: &Library,
+        f: impl FnOnce(&mut Catalog) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut guard = self.0.lock().map_err(|e| e.to_string())?;
+        let current = guard
+            .as_ref()
+            .is_some_and(|catalog| catalog.notes_dir == library.root() && catalog.path.exists());
+        if !current {
+            *guard = None;
+            *guard = Some(Catalog::open(library)?);
+        }
+        f(guard.as_mut().unwrap())
+    }
+
+    /// Bring the catalog up to date with the files and answer `query`.
+    pub fn list(&self, library: &Library, query: &NoteQuery) -> Result<NotePage, String> {
+        self.with_catalog(library, |catalog| {
+            catalog.reconcile()?;
+            catalog.query(query)
+        })
+    }
+
+    /// Tags by the number of notes using them.
+    pub fn tag_counts(&self, library: &Library) -> Result<Vec<UsageCount>, String> {
+        self.with_catalog(library, |catalog| {
+            catalog.reconcile()?;
+            catalog.usage("note_tags", "tag")
+        })
+    }
+
+    /// Participants by the number of notes they appear in.
+    pub fn participant_counts(&self, library: &Library) -> Result<Vec<UsageCount>, String> {
+        self.with_catalog(library, |catalog| {
+            catalog.reconcile()?;
+            catalog.usage("note_participants", "name")
+        })
+    }
+
+    /// Note titles and link targets, brought up to date with the files.
+    pub fn link_index(&self, library: &Library) -> Result<LinkIndex, String> {
+        self.with_catalog(library, |catalog| {
+            catalog.reconcile()?;
+            catalog.link_index()
+        })
+    }
+
+    /// Occurrences of `series_id`, or of every series, in order.
+    pub fn occurrences(
+        &self,
+        library: &Library,
+        series_id: Option<&str>,
+    ) -> Result<Vec<Occurrence>, String> {
+        self.with_catalog(library, |catalog| {
+            catalog.reconcile()?;
+            catalog.occurrences(series_id)
+        })
+    }
+
+    /// Add or replace a single note.
+    pub fn index_note(&self, library: &Library, note: &Note) -> Result<(), String> {
+        self.with_catalog(library, |catalog| {
+            let mtime = note_mtime(&catalog.notes_dir, &note.id);
+            let placeholder;
+            let note = if note.encrypted {
+                placeholder = encryption::placeholder(&note.id, mtime);
+                &placeholder
+            } else {
+                note
+            };
+            let tx = catalog.conn.transaction().map_err(|e| e.to_string())?;
+            upsert_row(&tx, note, mtime)?;
+            tx.commit().map_err(|e| e.to_string())
+        })
+    }
+
+    /// Remove a single note.
+    pub fn remove_note(&self, library: &Library, id: &str) -> Result<(), String> {
+        self.with_catalog(library, |catalog| {
+            let tx = catalog.conn.transaction().map_err(|e| e.to_string())?;
+            delete_row(&tx, id)?;
+            tx.commit().map_err(|e| e.to_string())
+        })
+    }
+}
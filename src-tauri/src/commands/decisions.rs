@@ -0,0 +1,472 @@
+//! Decision log: structured decisions per note in an `<id>.decisions.json`
+//! sidecar, aggregated into a register across the library. An encrypted
+//! note's sidecar is sealed, and left out of the register while locked.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::atomic;
+use super::encryption;
+use super::library::LibraryState;
+use super::note_id::NoteId;
+use super::notes::{self, Note};
+use super::watcher::WatcherState;
+
+const SUFFIX: &str = ".decisions.json";
+
+static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Points at a decision in some note.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DecisionRef {
+    pub note_id: NoteId,
+    pub decision_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Decision {
+    pub decision_id: String,
+    pub statement: String,
+    pub rationale: Option<String>,
+    /// Day the decision was made, as `YYYY-MM-DD`.
+    pub date: String,
+    pub participants: Vec<String>,
+    pub created_at: String,
+    /// The later decision that replaced this one.
+    pub superseded_by: Option<DecisionRef>,
+}
+
+/// Fields a caller supplies when recording or editing a decision. Missing
+/// `date` and `participants` are taken from the note.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct DecisionInput {
+    pub statement: String,
+    pub rationale: Option<String>,
+    pub date: Option<String>,
+    pub participants: Option<Vec<String>>,
+}
+
+/// A decision as listed in the register, with the note it was made in.
+#[derive(Serialize, Debug)]
+pub struct NoteDecision {
+    #[serde(flatten)]
+    pub decision: Decision,
+    pub note_id: NoteId,
+    pub note_title: String,
+}
+
+pub(crate) fn sidecar_path(dir: &Path, id: &NoteId) -> PathBuf {
+    dir.join(format!("{id}{SUFFIX}"))
+}
+
+/// The decisions of note `id`. Fails if they are sealed and the library is
+/// locked, or unreadable, so they are not mistaken for none and overwritten.
+fn read_decisions(dir: &Path, id: &NoteId) -> Result<Vec<Decision>, String> {
+    let raw = match fs::read(sidecar_path(dir, id)) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.to_string()),
+    };
+    let json = encryption::open_file(dir, id, raw)?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid {id}{SUFFIX}: {e}"))
+}
+
+/// `decisions` as stored for note `id`: sealed if the note is encrypted.
+fn encode_decisions(
+    dir: &Path,
+    id: &NoteId,
+    decisions: &[Decision],
+    encrypted: bool,
+) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec_pretty(decisions).map_err(|e| e.to_string())?;
+    if encrypted {
+        encryption::seal_file(dir, id, &json)
+    } else {
+        Ok(json)
+    }
+}
+
+fn write_decisions(
+    dir: &Path,
+    watcher: &WatcherState,
+    id: &NoteId,
+    decisions: &[Decision],
+) -> Result<(), String> {
+    let path = sidecar_path(dir, id);
+    let encrypted = encryption::is_encrypted(dir, id);
+    let contents = encode_decisions(dir, id, decisions, encrypted)?;
+    atomic::write_file(&path, &contents).map_err(|e| e.to_string())?;
+    watcher.record_write(id, &path);
+    Ok(())
+}
+
+/// The decision sidecar of note `id` re-encoded for a note that is now
+/// `encrypted` or not, if it has one. Call with `notes::write_lock` held.
+pub(crate) fn sidecar_for(
+    dir: &Path,
+    id: &NoteId,
+    encrypted: bool,
+) -> Result<Option<(PathBuf, Vec<u8>)>, String> {
+    let path = sidecar_path(dir, id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let decisions = read_decisions(dir, id)?;
+    let contents = encode_decisions(dir, id, &decisions, encrypted)?;
+    Ok(Some((path, contents)))
+}
+
+/// Apply `f` to the decisions of note `id` and save them. Holds
+/// `notes::write_lock`, so the sidecar cannot be sealed or moved meanwhile.
+fn update_decisions<T>(
+    dir: &Path,
+    watcher: &WatcherState,
+    id: &NoteId,
+    f: impl FnOnce(&mut Vec<Decision>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = notes::write_lock();
+    let mut decisions = read_decisions(dir, id)?;
+    let result = f(&mut decisions)?;
+    write_decisions(dir, watcher, id, &decisions)?;
+    Ok(result)
+}
+
+fn note_date(note: &Note) -> String {
+    notes::meeting_day(&note.meta)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn new_decision(note: &Note, input: DecisionInput) -> Result<Decision, String> {
+    let statement = input.statement.trim().to_string();
+    if statement.is_empty() {
+        return Err("A decision needs a statement".into());
+    }
+    let date = match input.date {
+        Some(date) => {
+            chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date {date:?}, expected YYYY-MM-DD"))?;
+            date
+        }
+        None => note_date(note),
+    };
+    let now = chrono::Utc::now();
+    Ok(Decision {
+        decision_id: format!(
+            "{:x}-{}",
+            now.timestamp_nanos_opt().unwrap_or_default(),
+            ID_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ),
+        statement,
+        rationale: input
+            .rationale
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty()),
+        date,
+        participants: input
+            .participants
+            .unwrap_or_else(|| note.meta.participants.clone()),
+        created_at: now.to_rfc3339(),
+        superseded_by: None,
+    })
+}
+
+/// Parse a Markdown list of `- <decision> — <rationale>` lines.
+fn parse_decision_list(markdown: &str) -> Vec<DecisionInput> {
+    markdown
+        .lines()
+        .filter_map(|line| {
+            let item = line.trim_start();
+            let item = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|bullet| item.strip_prefix(bullet))?;
+            let (statement, rationale) = match item.split_once(" — ") {
+                Some((statement, rationale)) => (statement, Some(rationale.to_string())),
+                None => (item, None),
+            };
+            Some(DecisionInput {
+                statement: statement.trim().to_string(),
+                rationale,
+                ..Default::default()
+            })
+        })
+        .filter(|input| !input.statement.is_empty())
+        .collect()
+}
+
+/// Record the decisions in an AI-generated list for note `id`, skipping
+/// statements the note already has. Returns how many were added.
+pub(crate) fn record_extracted(
+    dir: &Path,
+    watcher: &WatcherState,
+    id: &NoteId,
+    markdown: &str,
+) -> Result<usize, String> {
+    let note = notes::load_note(dir, id)?;
+    update_decisions(dir, watcher, id, |decisions| {
+        let mut added = 0;
+        for input in parse_decision_list(markdown) {
+            let known = decisions
+                .iter()
+                .any(|d| d.statement.eq_ignore_ascii_case(input.statement.trim()));
+            if !known {
+                decisions.push(new_decision(&note, input)?);
+                added += 1;
+            }
+        }
+        Ok(added)
+    })
+}
+
+/// Point `superseded_by` links at note `from` to note `to` after a rename.
+/// Call with `notes::write_lock` held.
+pub(crate) fn rename_references(dir: &Path, watcher: &WatcherState, from: &NoteId, to: &NoteId) {
+    for id in notes::note_ids(dir) {
+        let mut decisions = match read_decisions(dir, &id) {
+            Ok(decisions) => decisions,
+            Err(e) => {
+                eprintln!("Failed to update decision links in {id}: {e}");
+                continue;
+            }
+        };
+        let mut changed = false;
+        for link in decisions
+            .iter_mut()
+            .filter_map(|d| d.superseded_by.as_mut())
+        {
+            if &link.note_id == from {
+                link.note_id = to.clone();
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = write_decisions(dir, watcher, &id, &decisions) {
+                eprintln!("Failed to update decision links in {id}: {e}");
+            }
+        }
+    }
+}
+
+fn matches_query(entry: &NoteDecision, query: &str) -> bool {
+    let query = query.to_lowercase();
+    let d = &entry.decision;
+    [
+        Some(&d.statement),
+        d.rationale.as_ref(),
+        Some(&entry.note_title),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(&d.participants)
+    .any(|field| field.to_lowercase().contains(&query))
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Decision register across the library in chronological order. `query`
+/// matches statements, rationales, participants and note titles.
+#[tauri::command]
+pub fn list_decisions(
+    library: tauri::State<LibraryState>,
+    query: Option<String>,
+    include_superseded: Option<bool>,
+) -> Result<Vec<NoteDecision>, String> {
+    let dir = library.notes_dir()?;
+    let include_superseded = include_superseded.unwrap_or(true);
+    let query = query.filter(|q| !q.trim().is_empty());
+    let mut register = Vec::new();
+
+    for id in notes::note_ids(&dir) {
+        let Ok(decisions) = read_decisions(&dir, &id) else {
+            continue;
+        };
+        if decisions.is_empty() {
+            continue;
+        }
+        let title = notes::load_note(&dir, &id)
+            .map(|note| note.meta.title)
+            .unwrap_or_else(|_| id.to_string());
+        for decision in decisions {
+            if decision.superseded_by.is_some() && !include_superseded {
+                continue;
+            }
+            let entry = NoteDecision {
+                decision,
+                note_id: id.clone(),
+                note_title: title.clone(),
+            };
+            if query
+                .as_deref()
+                .is_none_or(|q| matches_query(&entry, q.trim()))
+            {
+                register.push(entry);
+            }
+        }
+    }
+
+    register.sort_by(|a, b| {
+        (&a.decision.date, &a.decision.created_at).cmp(&(&b.decision.date, &b.decision.created_at))
+    });
+    Ok(register)
+}
+
+#[tauri::command]
+pub fn add_decision(
+    library: tauri::State<LibraryState>,
+    watcher: tauri::State<WatcherState>,
+    note_id: NoteId,
+    decision: DecisionInput,
+) -> Result<Decision, String> {
+    let dir = library.notes_dir()?;
+    let note = notes::load_note(&dir, &note_id)?;
+    let decision = new_decision(&note, decision)?;
+    update_decisions(&dir, &watcher, &note_id, |decisions| {
+        decisions.push(decision.clone());
+        Ok(decision)
+    })
+}
+
+#[tauri::command]
+pub fn update_decision(
+    library: tauri::State<LibraryState>,
+    watcher: tauri::State<WatcherState>,
+    note_id: NoteId,
+    decision_id: String,
+    decision: DecisionInput,
+) -> Result<Decision, String> {
+    let dir = library.notes_dir()?;
+    let note = notes::load_note(&dir, &note_id)?;
+    let edited = new_decision(&note, decision)?;
+    update_decisions(&dir, &watcher, &note_id, |decisions| {
+        let existing = decisions
+            .iter_mut()
+            .find(|d| d.decision_id == decision_id)
+            .ok_or_else(|| format!("No decision {decision_id} in note {note_id}"))?;
+        existing.statement = edited.statement;
+        existing.rationale = edited.rationale;
+        existing.date = edited.date;
+        existing.participants = edited.participants;
+        Ok(existing.clone())
+    })
+}
+
+#[tauri::command]
+pub fn remove_decision(
+    library: tauri::State<LibraryState>,
+    watcher: tauri::State<WatcherState>,
+    note_id: NoteId,
+    decision_id: String,
+) -> Result<(), String> {
+    let dir = library.notes_dir()?;
+    update_decisions(&dir, &watcher, &note_id, |decisions| {
+        let before = decisions.len();
+        decisions.retain(|d| d.decision_id != decision_id);
+        if decisions.len() == before {
+            return Err(format!("No decision {decision_id} in note {note_id}"));
+        }
+        Ok(())
+    })
+}
+
+/// Mark a decision as replaced by a later one, or with `None` clear the mark.
+#[tauri::command]
+pub fn supersede_decision(
+    library: tauri::State<LibraryState>,
+    watcher: tauri::State<WatcherState>,
+    note_id: NoteId,
+    decision_id: String,
+    superseded_by: Option<DecisionRef>,
+) -> Result<Decision, String> {
+    let dir = library.notes_dir()?;
+    let later = match &superseded_by {
+        Some(link) => Some(
+            read_decisions(&dir, &link.note_id)?
+                .into_iter()
+                .find(|d| d.decision_id == link.decision_id)
+                .ok_or_else(|| {
+                    format!("No decision {} in note {}", link.decision_id, link.note_id)
+                })?,
+        ),
+        None => None,
+    };
+    update_decisions(&dir, &watcher, &note_id, |decisions| {
+        let decision = decisions
+            .iter_mut()
+            .find(|d| d.decision_id == decision_id)
+            .ok_or_else(|| format!("No decision {decision_id} in note {note_id}"))?;
+        if let Some(later) = &later {
+            if later.decision_id == decision.decision_id || later.date < decision.date {
+                return Err("A decision can only be superseded by a later one".into());
+            }
+        }
+        decision.superseded_by = superseded_by;
+        Ok(decision.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "2026-03-01-kickoff";
+
+    fn note(dir: &Path) -> NoteId {
+        let id = NoteId::parse(ID).unwrap();
+        fs::write(dir.join(format!("{id}.md")), "# Kickoff").unwrap();
+        id
+    }
+
+    #[test]
+    fn decision_lists_are_parsed() {
+        let inputs = parse_decision_list(
+            "Decisions:\n- Ship in May — the beta went well\n* Hire two engineers\n-\n+  \n",
+        );
+        let parsed: Vec<(&str, Option<&str>)> = inputs
+            .iter()
+            .map(|i| (i.statement.as_str(), i.rationale.as_deref()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("Ship in May", Some("the beta went well")),
+                ("Hire two engineers", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn extracted_decisions_are_recorded_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let id = note(tmp.path());
+        let watcher = WatcherState::default();
+        let list = "- Ship in May — the beta went well\n- Hire two engineers";
+        assert_eq!(record_extracted(tmp.path(), &watcher, &id, list), Ok(2));
+        assert_eq!(
+            record_extracted(tmp.path(), &watcher, &id, "- ship in may\n- Cut scope"),
+            Ok(1)
+        );
+        let decisions = read_decisions(tmp.path(), &id).unwrap();
+        let statements: Vec<&str> = decisions.iter().map(|d| d.statement.as_str()).collect();
+        assert_eq!(
+            statements,
+            ["Ship in May", "Hire two engineers", "Cut scope"]
+        );
+    }
+
+    #[test]
+    fn corrupt_sidecars_are_not_overwritten() {
+        let tmp = tempfile::tempdir().unwrap();
+        let id = note(tmp.path());
+        let path = sidecar_path(tmp.path(), &id);
+        let conflicted = "[{\"decision_id\": \"1\", <<<<<<< ours";
+        fs::write(&path, conflicted).unwrap();
+
+        assert!(read_decisions(tmp.path(), &id).is_err());
+        let watcher = WatcherState::default();
+        assert!(record_extracted(tmp.path(), &watcher, &id, "- Ship in May").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), conflicted);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use similar::{capture_diff_slices, Algorithm, ChangeTag, DiffOp, TextDiff};
+use std::ops::Range;
+
+/// Split text into lines, keeping line terminators so they round-trip.
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// A replacement of `base[range]` by `lines` made on one side of a merge.
+struct Edit<'a> {
+    range: Range<usize>,
+    lines: &'a [&'a str],
+}
+
+fn edits<'a>(base: &[&str], side: &'a [&'a str]) -> Vec<Edit<'a>> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .into_iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .map(|op| Edit {
+            range: op.old_range(),
+            lines: &side[op.new_range()],
+        })
+        .collect()
+}
+
+/// Whether two edits touch the same part of the base text.
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    if a.start == b.start {
+        return true;
+    }
+    let inside = |point: usize, r: &Range<usize>| r.start < point && point < r.end;
+    if a.is_empty() {
+        return inside(a.start, b);
+    }
+    if b.is_empty() {
+        return inside(b.start, a);
+    }
+    a.start < b.end && b.start < a.end
+}
+
+/// Three-way merge of line-based edits. Returns `None` if `ours` and `theirs`
+/// changed the same region of `base` in different ways.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    let base_lines = lines(base);
+    let our_lines = lines(ours);
+    let their_lines = lines(theirs);
+
+    let mut all: Vec<Edit> = edits(&base_lines, &our_lines);
+    let theirs_edits = edits(&base_lines, &their_lines);
+
+    for edit in theirs_edits {
+        match all.iter().find(|e| overlaps(&e.range, &edit.range)) {
+            // Both sides made the same change.
+            Some(e) if e.range == edit.range && e.lines == edit.lines => {}
+            Some(_) => return None,
+            None => all.push(edit),
+        }
+    }
+    all.sort_by_key(|e| (e.range.start, e.range.end));
+
+    let mut merged = String::with_capacity(ours.len().max(theirs.len()));
+    let mut pos = 0;
+    for edit in all {
+        merged.extend(base_lines[pos..edit.range.start].iter().copied());
+        merged.extend(edit.lines.iter().copied());
+        pos = edit.range.end;
+    }
+    merged.extend(base_lines[pos..].iter().copied());
+    Some(merged)
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineChange {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// One line of a unified line diff.
+#[derive(Serialize, Debug)]
+pub struct DiffLine {
+    pub change: LineChange,
+    pub text: String,
+}
+
+/// Line diff turning `old` into `new`.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            change: match change.tag() {
+                ChangeTag::Equal => LineChange::Equal,
+                ChangeTag::Insert => LineChange::Insert,
+                ChangeTag::Delete => LineChange::Delete,
+            },
+            text: change.value().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "# Kickoff\nGoals\nBudget\nHiring\nNext steps\n";
+
+    #[test]
+    fn edits_to_different_lines_merge() {
+        let ours = "# Kickoff\nGoals for Q1\nBudget\nHiring\nNext steps\n";
+        let theirs = "# Kickoff\nGoals\nBudget\nHiring two engineers\nNext steps\n";
+        assert_eq!(
+            merge3(BASE, ours, theirs).as_deref(),
+            Some("# Kickoff\nGoals for Q1\nBudget\nHiring two engineers\nNext steps\n")
+        );
+        // Insertions at either end, and nothing changed on one side.
+        let ours = "Agenda\n# Kickoff\nGoals\nBudget\nHiring\nNext steps\n";
+        let theirs = "# Kickoff\nGoals\nBudget\nHiring\nNext steps\n- ship\n";
+        assert_eq!(
+            merge3(BASE, ours, theirs).as_deref(),
+            Some("Agenda\n# Kickoff\nGoals\nBudget\nHiring\nNext steps\n- ship\n")
+        );
+        assert_eq!(merge3(BASE, BASE, theirs).as_deref(), Some(theirs));
+    }
+
+    #[test]
+    fn edits_to_the_same_lines_conflict() {
+        let ours = "# Kickoff\nGoals\nBudget: 10k\nHiring\nNext steps\n";
+        let theirs = "# Kickoff\nGoals\nBudget: 12k\nHiring\nNext steps\n";
+        assert_eq!(merge3(BASE, ours, theirs), None);
+        // Two different insertions at the same place.
+        let ours = "# Kickoff\nGoals\nRisks\nBudget\nHiring\nNext steps\n";
+        let theirs = "# Kickoff\nGoals\nTimeline\nBudget\nHiring\nNext steps\n";
+        assert_eq!(merge3(BASE, ours, theirs), None);
+    }
+
+    #[test]
+    fn deletes_merge_unless_the_other_side_edited_the_lines() {
+        let ours = "# Kickoff\nGoals\nHiring\nNext steps\n";
+        let theirs = "# Kickoff\nGoals\nBudget\nHiring\nNext steps: ship\n";
+        assert_eq!(
+            merge3(BASE, ours, theirs).as_deref(),
+            Some("# Kickoff\nGoals\nHiring\nNext steps: ship\n")
+        );
+        let theirs = "# Kickoff\nGoals\nBudget: 12k\nHiring\nNext steps\n";
+        assert_eq!(merge3(BASE, ours, theirs), None);
+    }
+
+    #[test]
+    fn identical_edits_on_both_sides_apply_once() {
+        let edited = "# Kickoff\nGoals\nBudget: 12k\nHiring\nNext steps\n";
+        assert_eq!(merge3(BASE, edited, edited).as_deref(), Some(edited));
+        let deleted = "# Kickoff\nGoals\nHiring\nNext steps\n";
+        assert_eq!(merge3(BASE, deleted, deleted).as_deref(), Some(deleted));
+    }
+}
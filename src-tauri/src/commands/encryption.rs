@@ -0,0 +1,798 @@
+//! Opt-in encryption at rest for sensitive notes, e.g. HR or legal meetings
+//! in a synced library.
+//!
+//! A library with a passphrase has a random note key in `.encryption.json`,
+//! sealed with a key derived from the passphrase by Argon2id, so changing the
+//! passphrase does not touch the notes. An encrypted note's `<id>.md` is a
+//! header line followed by its body and meta sealed with XChaCha20-Poly1305
+//! under the note key; it has no meta or action item sidecar and no history,
+//! and its decision sidecar, its attachments in `<id>.assets/` and the
+//! recordings and transcripts in `recordings/<id>/` are sealed too.
+//!
+//! The note key is held in memory from `unlock_notes` until `lock_notes`, or
+//! until it has gone unused for the library's `lock_after_minutes`. While
+//! locked, encrypted notes cannot be read or written and are listed under a
+//! placeholder title; they are never added to the search index or catalog.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::prelude::*;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use super::atomic;
+use super::attachments;
+use super::catalog::{self, CatalogState};
+use super::git;
+use super::history;
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes::{self, Note, NoteMeta, NoteSummary};
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+const KEY_FILE: &str = ".encryption.json";
+/// First line of an encrypted note's `<id>.md`.
+const NOTE_HEADER: &str = "brief-encrypted-note v1\n";
+/// Start of a file sealed in place, e.g. an encrypted note's decisions or
+/// attachments.
+const FILE_HEADER: &[u8] = b"brief-encrypted-file v1\n";
+/// Appended to the names of sealed recordings and transcripts.
+const SEALED_SUFFIX: &str = ".sealed";
+const NONCE_LEN: usize = 24;
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// Title an encrypted note is listed under while its library is locked.
+pub(crate) const PLACEHOLDER_TITLE: &str = "Encrypted note";
+
+/// Unlocked libraries by root directory.
+static SESSIONS: Mutex<BTreeMap<PathBuf, Session>> = Mutex::new(BTreeMap::new());
+
+struct Session {
+    cipher: XChaCha20Poly1305,
+    last_used: Instant,
+    /// Lock again after this long unused; never if `None`.
+    idle_limit: Option<Duration>,
+}
+
+/// The contents of `.encryption.json`.
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    /// Argon2id salt and cost parameters for deriving the passphrase key.
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// The note key, sealed with the passphrase key.
+    note_key: String,
+}
+
+/// What is sealed into an encrypted note's `<id>.md`.
+#[derive(Serialize, Deserialize)]
+struct SealedNote {
+    content: String,
+    meta: NoteMeta,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EncryptionStatus {
+    /// Whether the library has a passphrase.
+    pub enabled: bool,
+    pub unlocked: bool,
+}
+
+fn locked_error(id: &NoteId) -> String {
+    format!("Note {id} is encrypted and the library is locked")
+}
+
+fn read_key_file(dir: &Path) -> Result<Option<KeyFile>, String> {
+    match fs::read(dir.join(KEY_FILE)) {
+        Ok(raw) => serde_json::from_slice(&raw)
+            .map(Some)
+            .map_err(|e| format!("Invalid {KEY_FILE}: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The key `passphrase` derives with the salt and costs in `key_file`.
+fn passphrase_cipher(passphrase: &str, key_file: &KeyFile) -> Result<XChaCha20Poly1305, String> {
+    let salt = BASE64_STANDARD
+        .decode(&key_file.salt)
+        .map_err(|e| e.to_string())?;
+    let params = Params::new(
+        key_file.memory_kib,
+        key_file.iterations,
+        key_file.parallelism,
+        Some(32),
+    )
+    .map_err(|e| e.to_string())?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| e.to_string())?;
+    XChaCha20Poly1305::new_from_slice(key.as_ref()).map_err(|e| e.to_string())
+}
+
+/// The note key sealed in `key_file`, if `passphrase` is the right one.
+fn open_key_file(passphrase: &str, key_file: &KeyFile) -> Result<Zeroizing<Vec<u8>>, String> {
+    let sealed = BASE64_STANDARD
+        .decode(&key_file.note_key)
+        .map_err(|e| e.to_string())?;
+    open(&passphrase_cipher(passphrase, key_file)?, &sealed)
+        .map(Zeroizing::new)
+        .map_err(|_| "Wrong passphrase".to_string())
+}
+
+/// A key file sealing `note_key` with `passphrase`, under a fresh salt.
+fn new_key_file(passphrase: &str, note_key: &[u8]) -> Result<KeyFile, String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut key_file = KeyFile {
+        salt: BASE64_STANDARD.encode(salt),
+        memory_kib: Params::DEFAULT_M_COST,
+        iterations: Params::DEFAULT_T_COST,
+        parallelism: Params::DEFAULT_P_COST,
+        note_key: String::new(),
+    };
+    let sealed = seal(&passphrase_cipher(passphrase, &key_file)?, note_key)?;
+    key_file.note_key = BASE64_STANDARD.encode(sealed);
+    Ok(key_file)
+}
+
+/// `plaintext` encrypted under a random nonce, which is prepended.
+fn seal(cipher: &XChaCha20Poly1305, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Encryption failed".to_string())?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn open(cipher: &XChaCha20Poly1305, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Encrypted data is truncated".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Encrypted data is damaged or from another library".to_string())
+}
+
+/// The note key of the library at `dir`, if it is unlocked. With `touch` it
+/// counts as a use for the idle timeout; only sealing and opening notes and
+/// their files do, so status checks and listings let an idle library lock.
+fn current_cipher(dir: &Path, touch: bool) -> Option<XChaCha20Poly1305> {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let session = sessions.get_mut(dir)?;
+    if session
+        .idle_limit
+        .is_some_and(|limit| session.last_used.elapsed() > limit)
+    {
+        sessions.remove(dir);
+        return None;
+    }
+    if touch {
+        session.last_used = Instant::now();
+    }
+    Some(session.cipher.clone())
+}
+
+fn session_cipher(dir: &Path) -> Option<XChaCha20Poly1305> {
+    current_cipher(dir, true)
+}
+
+fn start_session(lib: &Library, note_key: &[u8]) -> Result<(), String> {
+    let cipher = XChaCha20Poly1305::new_from_slice(note_key).map_err(|e| e.to_string())?;
+    let idle_limit = lib
+        .settings
+        .lock_after_minutes
+        .map(|minutes| Duration::from_secs(u64::from(minutes) * 60));
+    let session = Session {
+        cipher,
+        last_used: Instant::now(),
+        idle_limit,
+    };
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(lib.root(), session);
+    Ok(())
+}
+
+fn is_unlocked(dir: &Path) -> bool {
+    current_cipher(dir, false).is_some()
+}
+
+/// Whether note `id` is stored encrypted. Needs no key.
+pub(crate) fn is_encrypted(dir: &Path, id: &NoteId) -> bool {
+    let mut header = [0u8; NOTE_HEADER.len()];
+    fs::File::open(dir.join(format!("{id}.md")))
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header == NOTE_HEADER.as_bytes())
+}
+
+/// Fail if note `id` is encrypted and cannot be read right now, for commands
+/// that must not touch locked notes.
+pub(crate) fn check_readable(dir: &Path, id: &NoteId) -> Result<(), String> {
+    if is_encrypted(dir, id) && !is_unlocked(dir) {
+        return Err(locked_error(id));
+    }
+    Ok(())
+}
+
+/// The sealed part of a note's Markdown file, if it is encrypted.
+pub(crate) fn sealed_body(raw: &str) -> Option<&str> {
+    raw.strip_prefix(NOTE_HEADER)
+}
+
+/// The `<id>.md` contents of note `id` encrypted.
+pub(crate) fn seal_note(
+    dir: &Path,
+    id: &NoteId,
+    content: &str,
+    meta: &NoteMeta,
+) -> Result<String, String> {
+    let cipher = session_cipher(dir).ok_or_else(|| locked_error(id))?;
+    let sealed = SealedNote {
+        content: content.to_string(),
+        meta: meta.clone(),
+    };
+    let json = Zeroizing::new(serde_json::to_vec(&sealed).map_err(|e| e.to_string())?);
+    let sealed = seal(&cipher, &json)?;
+    Ok(format!("{NOTE_HEADER}{}\n", BASE64_STANDARD.encode(sealed)))
+}
+
+/// The body and meta sealed in encrypted note `id`.
+pub(crate) fn open_note(dir: &Path, id: &NoteId, body: &str) -> Result<(String, NoteMeta), String> {
+    let cipher = session_cipher(dir).ok_or_else(|| locked_error(id))?;
+    open_note_with(&cipher, id, body)
+}
+
+fn open_note_with(
+    cipher: &XChaCha20Poly1305,
+    id: &NoteId,
+    body: &str,
+) -> Result<(String, NoteMeta), String> {
+    let sealed = BASE64_STANDARD
+        .decode(body.trim())
+        .map_err(|e| format!("Encrypted note {id} is damaged: {e}"))?;
+    let json = Zeroizing::new(open(cipher, &sealed).map_err(|e| format!("Note {id}: {e}"))?);
+    let note: SealedNote = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    Ok((note.content, note.meta))
+}
+
+/// `contents` of a file of encrypted note `id`, sealed to be stored under
+/// the same name.
+pub(crate) fn seal_file(dir: &Path, id: &NoteId, contents: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = session_cipher(dir).ok_or_else(|| locked_error(id))?;
+    Ok([FILE_HEADER, &seal(&cipher, contents)?].concat())
+}
+
+fn is_sealed_file(raw: &[u8]) -> bool {
+    raw.starts_with(FILE_HEADER)
+}
+
+/// The contents of file `raw` of note `id`, opened if it is sealed.
+pub(crate) fn open_file(dir: &Path, id: &NoteId, raw: Vec<u8>) -> Result<Vec<u8>, String> {
+    let Some(sealed) = raw.strip_prefix(FILE_HEADER) else {
+        return Ok(raw);
+    };
+    let cipher = session_cipher(dir).ok_or_else(|| locked_error(id))?;
+    open(&cipher, sealed).map_err(|e| format!("Note {id}: {e}"))
+}
+
+/// What the catalog knows about encrypted note `id`: a placeholder title, the
+/// day from its id and `mtime` (milliseconds) as last update.
+pub(crate) fn placeholder(id: &NoteId, mtime: i64) -> Note {
+    let updated = chrono::DateTime::from_timestamp_millis(mtime).unwrap_or_default();
+    let created = id
+        .name()
+        .get(..10)
+        .and_then(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .and_then(|day| {
+            day.and_time(chrono::NaiveTime::MIN)
+                .and_local_timezone(chrono::Local)
+                .earliest()
+        })
+        .map_or(updated.to_rfc3339(), |t| t.to_rfc3339());
+    Note {
+        id: id.clone(),
+        content: String::new(),
+        meta: NoteMeta {
+            title: PLACEHOLDER_TITLE.to_string(),
+            participants: vec![],
+            tags: vec![],
+            created_at: created,
+            updated_at: updated.to_rfc3339(),
+            series: None,
+            extra: BTreeMap::new(),
+        },
+        revision: String::new(),
+        encrypted: true,
+    }
+}
+
+/// Show the real title, tags and preview of the encrypted notes among
+/// `summaries` if the library is unlocked. Listing is not a use of the
+/// library, so it does not hold off the idle lock.
+pub(crate) fn reveal(dir: &Path, summaries: &mut [NoteSummary]) {
+    if !summaries.iter().any(|s| s.encrypted) {
+        return;
+    }
+    let Some(cipher) = current_cipher(dir, false) else {
+        return;
+    };
+    for summary in summaries.iter_mut().filter(|s| s.encrypted) {
+        let raw = {
+            let _guard = atomic::read_guard();
+            fs::read_to_string(dir.join(format!("{}.md", summary.id)))
+        };
+        let Some((content, meta)) = raw.ok().and_then(|raw| {
+            let body = sealed_body(&raw)?;
+            open_note_with(&cipher, &summary.id, body).ok()
+        }) else {
+            continue;
+        };
+        summary.title = meta.title;
+        summary.tags = meta.tags;
+        summary.preview = catalog::preview(&content);
+    }
+}
+
+/// Where the recording or transcript at `path` is kept once sealed.
+fn sealed_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(SEALED_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Seal, or with `encrypt` false open, the files in `recordings/<id>/`.
+fn convert_recordings(dir: &Path, id: &NoteId, encrypt: bool) -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(dir.join("recordings").join(id)) else {
+        return Ok(());
+    };
+    let cipher = session_cipher(dir).ok_or_else(|| locked_error(id))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let target = match (encrypt, name.strip_suffix(SEALED_SUFFIX)) {
+            (true, None) => sealed_path(&path),
+            (false, Some(plain)) => path.with_file_name(plain),
+            _ => continue,
+        };
+        if !path.is_file() {
+            continue;
+        }
+        let bytes = Zeroizing::new(fs::read(&path).map_err(|e| e.to_string())?);
+        let converted = if encrypt {
+            seal(&cipher, &bytes)?
+        } else {
+            open(&cipher, &bytes).map_err(|e| format!("{}: {e}", path.display()))?
+        };
+        atomic::write_file(&target, &converted).map_err(|e| e.to_string())?;
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Seal, or with `encrypt` false open, the attachments in `<id>.assets/`.
+fn convert_attachments(dir: &Path, id: &NoteId, encrypt: bool) -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(attachments::assets_dir(dir, id)) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let bytes = Zeroizing::new(fs::read(&path).map_err(|e| e.to_string())?);
+        if is_sealed_file(&bytes) == encrypt {
+            continue;
+        }
+        let converted = if encrypt {
+            seal_file(dir, id, &bytes)?
+        } else {
+            open_file(dir, id, bytes.to_vec()).map_err(|e| format!("{}: {e}", path.display()))?
+        };
+        atomic::write_file(&path, &converted).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// How a new recording or transcript is written: sealed in memory if it
+/// belongs to an encrypted note, so no plaintext copy reaches the disk.
+pub(crate) struct RecordingSeal(Option<XChaCha20Poly1305>);
+
+impl RecordingSeal {
+    /// For recordings not kept with any note.
+    pub(crate) fn none() -> Self {
+        RecordingSeal(None)
+    }
+
+    /// For recordings of note `id`. Fails if it is encrypted and the library
+    /// is locked.
+    pub(crate) fn for_note(dir: &Path, id: &NoteId) -> Result<Self, String> {
+        if !is_encrypted(dir, id) {
+            return Ok(RecordingSeal(None));
+        }
+        session_cipher(dir)
+            .map(|cipher| RecordingSeal(Some(cipher)))
+            .ok_or_else(|| locked_error(id))
+    }
+
+    /// Where the file meant for `path` is written.
+    pub(crate) fn stored_path(&self, path: &Path) -> PathBuf {
+        match self.0 {
+            Some(_) => sealed_path(path),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Write `bytes` to the `stored_path` for `path`.
+    pub(crate) fn write(&self, path: &Path, bytes: &[u8]) -> Result<(), String> {
+        let contents = match &self.0 {
+            Some(cipher) => seal(cipher, bytes)?,
+            None => bytes.to_vec(),
+        };
+        atomic::write_file(&self.stored_path(path), &contents).map_err(|e| e.to_string())
+    }
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn encryption_status(library: tauri::State<LibraryState>) -> Result<EncryptionStatus, String> {
+    let dir = library.notes_dir()?;
+    Ok(EncryptionStatus {
+        enabled: read_key_file(&dir)?.is_some(),
+        unlocked: is_unlocked(&dir),
+    })
+}
+
+/// Set the passphrase of the active library, or change it, which requires the
+/// `current` one. Leaves the library unlocked.
+#[tauri::command]
+pub fn set_encryption_passphrase(
+    library: tauri::State<LibraryState>,
+    passphrase: String,
+    current: Option<String>,
+) -> Result<(), String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "The passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+        ));
+    }
+    let _lock = notes::write_lock();
+    let note_key = match read_key_file(&dir)? {
+        Some(key_file) => {
+            let current = current.ok_or("The current passphrase is required")?;
+            open_key_file(&current, &key_file)?
+        }
+        None => Zeroizing::new(XChaCha20Poly1305::generate_key(&mut OsRng).to_vec()),
+    };
+    let key_file = new_key_file(&passphrase, &note_key)?;
+    let json = serde_json::to_vec_pretty(&key_file).map_err(|e| e.to_string())?;
+    atomic::write_file(&dir.join(KEY_FILE), &json).map_err(|e| e.to_string())?;
+    start_session(&lib, &note_key)
+}
+
+/// Unlock the encrypted notes of the active library.
+#[tauri::command]
+pub fn unlock_notes(library: tauri::State<LibraryState>, passphrase: String) -> Result<(), String> {
+    let lib = library.active()?;
+    let key_file = read_key_file(&lib.notes_dir()?)?.ok_or("No passphrase is set")?;
+    start_session(&lib, &open_key_file(&passphrase, &key_file)?)
+}
+
+/// Forget the note key of the active library until it is unlocked again.
+#[tauri::command]
+pub fn lock_notes(library: tauri::State<LibraryState>) -> Result<(), String> {
+    let root = library.active()?.root();
+    SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&root);
+    Ok(())
+}
+
+/// Encrypt note `id`, or decrypt it back into the library's layout. Both need
+/// the library unlocked. Encrypting seals the note's decisions and attachments
+/// and deletes its history and action item sidecar, which are kept in
+/// plaintext. In a git-synced library, earlier commits keep the note in
+/// plaintext, on the remote too; encrypting such a note fails unless
+/// `despite_git_history` acknowledges that. Returns the rewritten note.
+#[tauri::command]
+pub fn set_note_encrypted(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+    encrypted: bool,
+    despite_git_history: Option<bool>,
+) -> Result<Note, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    if read_key_file(&dir)?.is_none() {
+        return Err("Set a passphrase before encrypting notes".to_string());
+    }
+    if !is_unlocked(&dir) {
+        return Err("Unlock the library first".to_string());
+    }
+    if encrypted && despite_git_history != Some(true) && git::has_plaintext_history(&dir, &id)? {
+        return Err(format!(
+            "Earlier versions of note {id} are in the library's git history unencrypted, \
+             and stay readable there and on its remote; encrypting only protects later versions"
+        ));
+    }
+
+    let _lock = notes::write_lock();
+    let note = notes::load_note(&dir, &id)?;
+    if note.encrypted != encrypted {
+        notes::rewrite_note_locked(&lib, &search, &catalog, &watcher, &note, encrypted)?;
+    }
+    convert_recordings(&dir, &id, encrypted)?;
+    convert_attachments(&dir, &id, encrypted)?;
+    if encrypted {
+        history::clear(&dir, &id)?;
+    }
+    notes::load_note(&dir, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+    const ID: &str = "2026-03-01-hr-review";
+
+    fn library(root: &Path) -> Library {
+        let name = root.file_name().unwrap().to_string_lossy();
+        Library {
+            id: format!("test-{name}"),
+            name: "Test".to_string(),
+            path: root.to_string_lossy().into_owned(),
+            settings: Default::default(),
+        }
+    }
+
+    /// A library at `root` with a passphrase, unlocked.
+    fn unlocked_library(root: &Path) -> Library {
+        let lib = library(root);
+        let note_key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let key_file = new_key_file(PASSPHRASE, &note_key).unwrap();
+        fs::write(root.join(KEY_FILE), serde_json::to_vec(&key_file).unwrap()).unwrap();
+        start_session(&lib, &note_key).unwrap();
+        lib
+    }
+
+    fn unlock(lib: &Library, passphrase: &str) -> Result<(), String> {
+        let key_file = read_key_file(&lib.root())?.unwrap();
+        start_session(lib, &open_key_file(passphrase, &key_file)?)
+    }
+
+    fn lock(root: &Path) {
+        SESSIONS.lock().unwrap().remove(root);
+    }
+
+    /// Write note `ID` encrypted, as `set_note_encrypted` leaves it.
+    fn write_encrypted(root: &Path, title: &str, content: &str) -> NoteId {
+        let id = NoteId::parse(ID).unwrap();
+        let mut meta = placeholder(&id, 0).meta;
+        meta.title = title.to_string();
+        let sealed = seal_note(root, &id, content, &meta).unwrap();
+        fs::write(root.join(format!("{ID}.md")), sealed).unwrap();
+        id
+    }
+
+    #[test]
+    fn sealed_notes_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Performance review", "Salary: confidential");
+
+        let raw = fs::read_to_string(tmp.path().join(format!("{ID}.md"))).unwrap();
+        assert!(raw.starts_with(NOTE_HEADER));
+        assert!(!raw.contains("confidential") && !raw.contains("Performance"));
+        assert!(is_encrypted(tmp.path(), &id));
+
+        let note = notes::load_note(tmp.path(), &id).unwrap();
+        assert!(note.encrypted);
+        assert_eq!(note.content, "Salary: confidential");
+        assert_eq!(note.meta.title, "Performance review");
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lib = unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Review", "secret");
+        lock(tmp.path());
+
+        assert_eq!(
+            unlock(&lib, "not the passphrase"),
+            Err("Wrong passphrase".into())
+        );
+        assert!(!is_unlocked(tmp.path()));
+        assert!(notes::load_note(tmp.path(), &id).is_err());
+
+        unlock(&lib, PASSPHRASE).unwrap();
+        assert_eq!(notes::load_note(tmp.path(), &id).unwrap().content, "secret");
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let tmp = tempfile::tempdir().unwrap();
+        unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Review", "secret");
+
+        let path = tmp.path().join(format!("{ID}.md"));
+        let raw = fs::read_to_string(&path).unwrap();
+        let mut sealed = BASE64_STANDARD
+            .decode(sealed_body(&raw).unwrap().trim())
+            .unwrap();
+        *sealed.last_mut().unwrap() ^= 1;
+        fs::write(
+            &path,
+            format!("{NOTE_HEADER}{}\n", BASE64_STANDARD.encode(sealed)),
+        )
+        .unwrap();
+        let err = notes::load_note(tmp.path(), &id).unwrap_err();
+        assert!(err.contains("damaged"), "{err}");
+
+        let mut file = seal_file(tmp.path(), &id, b"board photo").unwrap();
+        assert_eq!(
+            open_file(tmp.path(), &id, file.clone()).unwrap(),
+            b"board photo"
+        );
+        file[FILE_HEADER.len() + NONCE_LEN] ^= 1;
+        assert!(open_file(tmp.path(), &id, file).is_err());
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn locked_notes_cannot_be_read_or_written() {
+        let tmp = tempfile::tempdir().unwrap();
+        unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Review", "secret");
+        let sealed_file = seal_file(tmp.path(), &id, b"decisions").unwrap();
+        lock(tmp.path());
+
+        let err = notes::load_note(tmp.path(), &id).unwrap_err();
+        assert!(err.contains("locked"), "{err}");
+        assert!(check_readable(tmp.path(), &id).is_err());
+        assert!(seal_note(tmp.path(), &id, "new", &placeholder(&id, 0).meta).is_err());
+        assert!(seal_file(tmp.path(), &id, b"new").is_err());
+        assert!(open_file(tmp.path(), &id, sealed_file).is_err());
+        assert!(RecordingSeal::for_note(tmp.path(), &id).is_err());
+        // Plaintext files of other notes are still readable.
+        assert_eq!(
+            open_file(tmp.path(), &id, b"plain".to_vec()).unwrap(),
+            b"plain"
+        );
+    }
+
+    #[test]
+    fn idle_libraries_lock_again() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut lib = unlocked_library(tmp.path());
+        let idle = |minutes: u64| {
+            let mut sessions = SESSIONS.lock().unwrap();
+            let session = sessions.get_mut(tmp.path()).unwrap();
+            session.last_used = Instant::now() - Duration::from_secs(minutes * 60);
+        };
+
+        idle(14);
+        assert!(is_unlocked(tmp.path()));
+        idle(16);
+        assert!(!is_unlocked(tmp.path()));
+
+        lib.settings.lock_after_minutes = None;
+        unlock(&lib, PASSPHRASE).unwrap();
+        idle(24 * 60);
+        assert!(is_unlocked(tmp.path()));
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn only_sealing_and_opening_count_as_use() {
+        let tmp = tempfile::tempdir().unwrap();
+        unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Performance review", "Salary: confidential");
+        let idle_minutes = || {
+            let sessions = SESSIONS.lock().unwrap();
+            sessions[tmp.path()].last_used.elapsed().as_secs() / 60
+        };
+        SESSIONS
+            .lock()
+            .unwrap()
+            .get_mut(tmp.path())
+            .unwrap()
+            .last_used = Instant::now() - Duration::from_secs(10 * 60);
+
+        // Status checks and listings leave the idle timer running.
+        assert!(is_unlocked(tmp.path()));
+        let mut summaries = vec![NoteSummary {
+            id: id.clone(),
+            folder: String::new(),
+            title: PLACEHOLDER_TITLE.to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            tags: vec![],
+            preview: String::new(),
+            encrypted: true,
+        }];
+        reveal(tmp.path(), &mut summaries);
+        assert_eq!(summaries[0].title, "Performance review");
+        assert_eq!(idle_minutes(), 10);
+
+        notes::load_note(tmp.path(), &id).unwrap();
+        assert_eq!(idle_minutes(), 0);
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn recordings_and_attachments_are_sealed() {
+        let tmp = tempfile::tempdir().unwrap();
+        unlocked_library(tmp.path());
+        let id = write_encrypted(tmp.path(), "Review", "secret");
+        let recordings = tmp.path().join("recordings").join(ID);
+        fs::create_dir_all(&recordings).unwrap();
+
+        let seal = RecordingSeal::for_note(tmp.path(), &id).unwrap();
+        let wav = recordings.join("20260301-100000.wav");
+        seal.write(&wav, b"RIFF audio").unwrap();
+        assert!(!wav.exists());
+        let sealed = fs::read(seal.stored_path(&wav)).unwrap();
+        assert!(!sealed.windows(5).any(|w| w == b"audio"));
+        convert_recordings(tmp.path(), &id, false).unwrap();
+        assert_eq!(fs::read(&wav).unwrap(), b"RIFF audio");
+
+        let assets = attachments::assets_dir(tmp.path(), &id);
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("board.png"), b"PNG pixels").unwrap();
+        convert_attachments(tmp.path(), &id, true).unwrap();
+        assert!(is_sealed_file(&fs::read(assets.join("board.png")).unwrap()));
+        convert_attachments(tmp.path(), &id, false).unwrap();
+        assert_eq!(fs::read(assets.join("board.png")).unwrap(), b"PNG pixels");
+        lock(tmp.path());
+    }
+
+    #[test]
+    fn locked_listing_shows_only_a_placeholder() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lib = unlocked_library(tmp.path());
+        write_encrypted(tmp.path(), "Performance review", "Salary: confidential");
+        let catalog = CatalogState::default();
+        let list = || {
+            let mut page = catalog.list(&lib, &Default::default()).unwrap();
+            reveal(tmp.path(), &mut page.notes);
+            page.notes
+        };
+
+        lock(tmp.path());
+        let locked = list();
+        assert_eq!(locked.len(), 1);
+        assert!(locked[0].encrypted);
+        assert_eq!(locked[0].title, PLACEHOLDER_TITLE);
+        assert!(locked[0].preview.is_empty() && locked[0].tags.is_empty());
+
+        unlock(&lib, PASSPHRASE).unwrap();
+        let unlocked = list();
+        assert_eq!(unlocked[0].title, "Performance review");
+        assert_eq!(unlocked[0].preview, "Salary: confidential");
+        lock(tmp.path());
+    }
+}
@@ -0,0 +1,762 @@
+//! Export notes for people who don't read Markdown: a standalone HTML page
+//! with embedded CSS, an HTML page laid out for printing to PDF, or a Word
+//! document. Each starts with the note's title, meeting date, participants
+//! and tags. Images attached to the note are embedded, and its attachments
+//! directory is copied next to the exported file so other links keep working.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::attachments::{self, ASSETS_SUFFIX};
+use super::catalog::{CatalogState, NoteQuery};
+use super::encryption;
+use super::library::LibraryState;
+use super::links;
+use super::note_id::NoteId;
+use super::notes::{self, Note};
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Html,
+    /// HTML with A4 page margins and print styles, for "Save as PDF".
+    PrintHtml,
+    Docx,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html | ExportFormat::PrintHtml => "html",
+            ExportFormat::Docx => "docx",
+        }
+    }
+}
+
+const CSS: &str = "
+body { margin: 0; background: #fff; color: #1d1d1f; font: 16px/1.6 -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif; }
+article { max-width: 46rem; margin: 3rem auto; padding: 0 1.5rem; }
+h1, h2, h3, h4 { line-height: 1.25; margin: 1.6em 0 0.5em; }
+header h1 { margin-top: 0; font-size: 2rem; }
+.meta { display: grid; grid-template-columns: max-content 1fr; gap: 0.25rem 1rem; margin: 0 0 2rem; padding-bottom: 1rem; border-bottom: 1px solid #ddd; color: #555; font-size: 0.9rem; }
+.meta dt { font-weight: 600; }
+.meta dd { margin: 0; }
+a { color: #1f5fad; }
+code, pre { font-family: SFMono-Regular, Consolas, 'Liberation Mono', monospace; font-size: 0.875em; background: #f4f4f4; border-radius: 4px; }
+code { padding: 0.1em 0.3em; }
+pre { padding: 0.75rem 1rem; overflow-x: auto; }
+pre code { padding: 0; background: none; }
+blockquote { margin: 1rem 0; padding: 0 1rem; border-left: 3px solid #ddd; color: #555; }
+table { border-collapse: collapse; margin: 1rem 0; }
+th, td { border: 1px solid #ddd; padding: 0.35rem 0.7rem; text-align: left; }
+th { background: #f7f7f7; }
+li:has(> input[type=checkbox]) { list-style: none; margin-left: -1.4em; }
+hr { border: 0; border-top: 1px solid #ddd; margin: 2rem 0; }
+";
+
+const PRINT_CSS: &str = "
+@page { size: A4; margin: 20mm 18mm; }
+body { font-size: 11pt; color: #000; }
+article { max-width: none; margin: 0; padding: 0; }
+a { color: inherit; text-decoration: none; }
+a[href^='http']::after { content: ' (' attr(href) ')'; font-size: 0.85em; color: #555; }
+h1, h2, h3, h4 { break-after: avoid; }
+pre, blockquote, table, tr, li { break-inside: avoid; }
+pre { white-space: pre-wrap; }
+";
+
+/// Wiki-links replaced by their label, or their target if they have none.
+fn plain_links(markdown: &str) -> String {
+    let mut out = markdown.to_string();
+    for span in links::link_spans(markdown).iter().rev() {
+        let (Some(open), Some(close)) = (
+            markdown[..span.range.start].rfind("[["),
+            markdown[span.range.end..].find("]]"),
+        ) else {
+            continue;
+        };
+        let close = span.range.end + close;
+        let label = markdown[span.range.end..close]
+            .split_once('|')
+            .map(|(_, label)| label.trim())
+            .filter(|label| !label.is_empty())
+            .unwrap_or(span.target);
+        out.replace_range(open..close + 2, label);
+    }
+    out
+}
+
+fn markdown_events(markdown: &str) -> impl Iterator<Item = Event<'_>> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    // Raw HTML in a note is shown as written rather than run in the export.
+    Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    })
+}
+
+/// Header fields shown above the note body, as label and value.
+fn header_fields(note: &Note) -> Vec<(&'static str, String)> {
+    let mut fields = vec![(
+        "Date",
+        notes::meeting_day(&note.meta)
+            .format("%A, %-d %B %Y")
+            .to_string(),
+    )];
+    if !note.meta.participants.is_empty() {
+        fields.push(("Participants", note.meta.participants.join(", ")));
+    }
+    if !note.meta.tags.is_empty() {
+        fields.push(("Tags", note.meta.tags.join(", ")));
+    }
+    fields
+}
+
+fn title_of(note: &Note) -> &str {
+    match note.meta.title.trim() {
+        "" => note.id.name(),
+        title => title,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Not allowed in XML documents.
+            c if c < ' ' && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ── HTML ─────────────────────────────────────────────────────────────────────
+
+fn render_html(dir: &Path, note: &Note, print: bool) -> String {
+    let title = escape(title_of(note));
+    let mut meta = String::new();
+    for (label, value) in header_fields(note) {
+        meta.push_str(&format!("<dt>{label}</dt><dd>{}</dd>\n", escape(&value)));
+    }
+    let content = plain_links(&note.content);
+    let events = markdown_events(&content).map(|event| match event {
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let dest_url = match attachments::read_linked(dir, &note.id, &dest_url) {
+                Some((name, bytes)) if attachments::is_image(&name) => format!(
+                    "data:{};base64,{}",
+                    attachments::mime_type(&name),
+                    BASE64_STANDARD.encode(bytes)
+                )
+                .into(),
+                _ => dest_url,
+            };
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            })
+        }
+        event => event,
+    });
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, events);
+    let css = if print {
+        format!("{CSS}{PRINT_CSS}")
+    } else {
+        CSS.to_string()
+    };
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<style>{css}</style>
+</head>
+<body>
+<article>
+<header>
+<h1>{title}</h1>
+<dl class=\"meta\">
+{meta}</dl>
+</header>
+{body}</article>
+</body>
+</html>
+"
+    )
+}
+
+// ── DOCX ─────────────────────────────────────────────────────────────────────
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Default Extension="png" ContentType="image/png"/><Default Extension="jpg" ContentType="image/jpeg"/><Default Extension="gif" ContentType="image/gif"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/></Types>"#;
+
+const RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#;
+
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:cs="Calibri"/><w:sz w:val="22"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults><w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style><w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:rPr><w:b/><w:sz w:val="40"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Meta"><w:name w:val="Meta"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="40"/></w:pPr><w:rPr><w:color w:val="555555"/><w:sz w:val="20"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="32"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="60"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:sz w:val="24"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Code"><w:name w:val="Code"/><w:basedOn w:val="Normal"/><w:pPr><w:shd w:val="clear" w:color="auto" w:fill="F4F4F4"/><w:spacing w:after="0" w:line="240" w:lineRule="auto"/></w:pPr><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="20"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:rPr><w:i/><w:color w:val="555555"/></w:rPr></w:style></w:styles>"#;
+
+const TABLE_PROPS: &str = r#"<w:tblPr><w:tblW w:w="0" w:type="auto"/><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:left w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:right w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/></w:tblBorders><w:tblCellMar><w:left w:w="100" w:type="dxa"/><w:right w:w="100" w:type="dxa"/></w:tblCellMar></w:tblPr>"#;
+
+/// Widest an embedded image may be, the text width of the page, in EMU.
+const MAX_IMAGE_WIDTH: f64 = 6_120_000.0;
+/// EMU per pixel at 96 dpi.
+const EMU_PER_PIXEL: f64 = 9525.0;
+
+/// WordprocessingML for a Markdown body. Paragraphs are opened lazily by the
+/// first run written into them, so tight list items and table cells, which
+/// have no paragraph events of their own, still get one.
+#[derive(Default)]
+struct DocxBody {
+    xml: String,
+    open: bool,
+    heading: Option<HeadingLevel>,
+    code_block: bool,
+    quotes: usize,
+    /// Open lists, with the next number for ordered ones.
+    lists: Vec<Option<u64>>,
+    /// Bullet, number or checkbox to start the next paragraph with.
+    marker: Option<String>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    code: bool,
+    link: usize,
+    /// Library root and note whose attachments images may link to.
+    assets: Option<(PathBuf, NoteId)>,
+    /// Embedded images, by file name in `word/media`.
+    media: Vec<(String, Vec<u8>)>,
+    /// Inside an embedded image, whose alt text is not shown.
+    in_image: bool,
+}
+
+impl DocxBody {
+    fn paragraph_props(&self) -> String {
+        let style = match self.heading {
+            Some(HeadingLevel::H1) => "Heading1",
+            Some(HeadingLevel::H2) => "Heading2",
+            Some(_) => "Heading3",
+            None if self.code_block => "Code",
+            None if self.quotes > 0 => "Quote",
+            None => "",
+        };
+        let mut props = String::new();
+        if !style.is_empty() {
+            props.push_str(&format!(r#"<w:pStyle w:val="{style}"/>"#));
+        }
+        let indent = self.lists.len() + self.quotes;
+        if indent > 0 {
+            props.push_str(&format!(r#"<w:ind w:left="{}"/>"#, indent * 360));
+        }
+        match props.is_empty() {
+            true => props,
+            false => format!("<w:pPr>{props}</w:pPr>"),
+        }
+    }
+
+    fn run_props(&self) -> String {
+        let mut props = String::new();
+        if self.code && !self.code_block {
+            props.push_str(r#"<w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/>"#);
+        }
+        if self.bold > 0 {
+            props.push_str("<w:b/>");
+        }
+        if self.italic > 0 {
+            props.push_str("<w:i/>");
+        }
+        if self.strike > 0 {
+            props.push_str("<w:strike/>");
+        }
+        if self.link > 0 {
+            props.push_str(r#"<w:color w:val="1F5FAD"/><w:u w:val="single"/>"#);
+        }
+        match props.is_empty() {
+            true => props,
+            false => format!("<w:rPr>{props}</w:rPr>"),
+        }
+    }
+
+    fn open_paragraph(&mut self) {
+        if self.open {
+            return;
+        }
+        self.xml.push_str("<w:p>");
+        self.xml.push_str(&self.paragraph_props());
+        self.open = true;
+        if let Some(marker) = self.marker.take() {
+            self.xml.push_str(&format!(
+                r#"<w:r><w:t xml:space="preserve">{marker}</w:t></w:r>"#
+            ));
+        }
+    }
+
+    fn close_paragraph(&mut self) {
+        if self.open {
+            self.xml.push_str("</w:p>");
+            self.open = false;
+        }
+    }
+
+    fn run(&mut self, text: &str) {
+        self.open_paragraph();
+        let props = self.run_props();
+        self.xml.push_str(&format!(
+            r#"<w:r>{props}<w:t xml:space="preserve">{}</w:t></w:r>"#,
+            escape(text)
+        ));
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_image {
+            return;
+        }
+        if !self.code_block {
+            return self.run(text);
+        }
+        // One paragraph per line, keeping blank lines.
+        let mut lines = text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            if lines.peek().is_none() {
+                if !line.is_empty() {
+                    self.run(line);
+                }
+                break;
+            }
+            self.open_paragraph();
+            if !line.is_empty() {
+                self.run(line);
+            }
+            self.close_paragraph();
+        }
+    }
+
+    /// Embed the attachment `url` links to if it is a picture Word can show;
+    /// otherwise the image's alt text stands in for it.
+    fn image(&mut self, url: &str) {
+        let Some((dir, id)) = &self.assets else {
+            return;
+        };
+        let Some((name, bytes)) = attachments::read_linked(dir, id, url) else {
+            return;
+        };
+        let Some((width, height)) = attachments::image_size(&bytes) else {
+            return;
+        };
+        if width == 0 || height == 0 {
+            return;
+        }
+        let n = self.media.len() + 1;
+        let scale = (MAX_IMAGE_WIDTH / (f64::from(width) * EMU_PER_PIXEL)).min(1.0);
+        let cx = (f64::from(width) * EMU_PER_PIXEL * scale) as u64;
+        let cy = (f64::from(height) * EMU_PER_PIXEL * scale) as u64;
+        let media_name = format!("image{n}.{}", attachments::sniff_extension(&bytes));
+        self.media.push((media_name, bytes));
+        self.open_paragraph();
+        self.xml.push_str(&format!(
+            r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{n}" name="{name}"/><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:nvPicPr><pic:cNvPr id="{n}" name="{name}"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="rIdImage{n}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#,
+            name = escape(&name)
+        ));
+        self.in_image = true;
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text),
+            Event::Code(text) => {
+                self.code = true;
+                self.run(&text);
+                self.code = false;
+            }
+            Event::SoftBreak => self.run(" "),
+            Event::HardBreak => {
+                self.open_paragraph();
+                self.xml.push_str("<w:r><w:br/></w:r>");
+            }
+            Event::Rule => {
+                self.close_paragraph();
+                self.xml.push_str(r#"<w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="1" w:color="BFBFBF"/></w:pBdr></w:pPr></w:p>"#);
+            }
+            Event::TaskListMarker(done) => {
+                self.marker = Some(if done { "☑ " } else { "☐ " }.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.close_paragraph(),
+            Tag::Heading { level, .. } => {
+                self.close_paragraph();
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote(_) => {
+                self.close_paragraph();
+                self.quotes += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.close_paragraph();
+                self.code_block = true;
+            }
+            Tag::List(start) => {
+                self.close_paragraph();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.close_paragraph();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                });
+            }
+            Tag::Table(columns) => {
+                self.close_paragraph();
+                self.xml.push_str("<w:tbl>");
+                self.xml.push_str(TABLE_PROPS);
+                self.xml.push_str("<w:tblGrid>");
+                for _ in &columns {
+                    self.xml.push_str(r#"<w:gridCol w:w="2400"/>"#);
+                }
+                self.xml.push_str("</w:tblGrid>");
+            }
+            Tag::TableHead => {
+                self.bold += 1;
+                self.xml.push_str("<w:tr>");
+            }
+            Tag::TableRow => self.xml.push_str("<w:tr>"),
+            Tag::TableCell => self.xml.push_str("<w:tc>"),
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link { .. } => self.link += 1,
+            Tag::Image { dest_url, .. } => self.image(&dest_url),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Item => self.close_paragraph(),
+            TagEnd::Heading(_) => {
+                self.close_paragraph();
+                self.heading = None;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.close_paragraph();
+                self.quotes -= 1;
+            }
+            TagEnd::CodeBlock => {
+                self.close_paragraph();
+                self.code_block = false;
+            }
+            TagEnd::List(_) => {
+                self.close_paragraph();
+                self.lists.pop();
+            }
+            TagEnd::Table => self.xml.push_str("</w:tbl>"),
+            TagEnd::TableHead => {
+                self.bold -= 1;
+                self.xml.push_str("</w:tr>");
+            }
+            TagEnd::TableRow => self.xml.push_str("</w:tr>"),
+            TagEnd::TableCell => {
+                // Word needs a paragraph in every cell, even an empty one.
+                self.open_paragraph();
+                self.close_paragraph();
+                self.xml.push_str("</w:tc>");
+            }
+            TagEnd::Emphasis => self.italic -= 1,
+            TagEnd::Strong => self.bold -= 1,
+            TagEnd::Strikethrough => self.strike -= 1,
+            TagEnd::Link => self.link -= 1,
+            TagEnd::Image => self.in_image = false,
+            _ => {}
+        }
+    }
+}
+
+fn styled_paragraph(style: &str, runs: &str) -> String {
+    format!(r#"<w:p><w:pPr><w:pStyle w:val="{style}"/></w:pPr>{runs}</w:p>"#)
+}
+
+fn render_docx(dir: &Path, note: &Note) -> Result<Vec<u8>, String> {
+    let title = escape(title_of(note));
+    let mut body = DocxBody {
+        assets: Some((dir.to_path_buf(), note.id.clone())),
+        ..DocxBody::default()
+    };
+    body.xml.push_str(&styled_paragraph(
+        "Title",
+        &format!("<w:r><w:t>{title}</w:t></w:r>"),
+    ));
+    for (label, value) in header_fields(note) {
+        body.xml.push_str(&styled_paragraph(
+            "Meta",
+            &format!(
+                r#"<w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve">{label}: </w:t></w:r><w:r><w:t>{}</w:t></w:r>"#,
+                escape(&value)
+            ),
+        ));
+    }
+    body.xml.push_str(r#"<w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="1" w:color="BFBFBF"/></w:pBdr></w:pPr></w:p>"#);
+    let content = plain_links(&note.content);
+    for event in markdown_events(&content) {
+        body.event(event);
+    }
+    body.close_paragraph();
+
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><w:body>{}<w:sectPr><w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1134" w:right="1134" w:bottom="1134" w:left="1134" w:header="709" w:footer="709" w:gutter="0"/></w:sectPr></w:body></w:document>"#,
+        body.xml
+    );
+    let core = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{title}</dc:title><dc:subject>{}</dc:subject><dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created></cp:coreProperties>"#,
+        escape(&note.meta.tags.join(", ")),
+        notes::meeting_day(&note.meta).format("%Y-%m-%dT00:00:00Z")
+    );
+
+    let mut document_rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+    );
+    for (i, (name, _)) in body.media.iter().enumerate() {
+        document_rels.push_str(&format!(
+            r#"<Relationship Id="rIdImage{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/{name}"/>"#,
+            i + 1
+        ));
+    }
+    document_rels.push_str("</Relationships>");
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut parts = vec![
+        (
+            "[Content_Types].xml".to_string(),
+            CONTENT_TYPES_XML.as_bytes(),
+        ),
+        ("_rels/.rels".to_string(), RELS_XML.as_bytes()),
+        ("docProps/core.xml".to_string(), core.as_bytes()),
+        (
+            "word/_rels/document.xml.rels".to_string(),
+            document_rels.as_bytes(),
+        ),
+        ("word/styles.xml".to_string(), STYLES_XML.as_bytes()),
+        ("word/document.xml".to_string(), document.as_bytes()),
+    ];
+    for (name, bytes) in &body.media {
+        parts.push((format!("word/media/{name}"), bytes));
+    }
+    for (name, bytes) in parts {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    let bytes = zip.finish().map_err(|e| e.to_string())?;
+    Ok(bytes.into_inner())
+}
+
+fn render(dir: &Path, note: &Note, format: ExportFormat) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Html => Ok(render_html(dir, note, false).into_bytes()),
+        ExportFormat::PrintHtml => Ok(render_html(dir, note, true).into_bytes()),
+        ExportFormat::Docx => render_docx(dir, note),
+    }
+}
+
+/// Write the export of `note` to `path`, with a copy of its attachments
+/// directory alongside.
+fn write_export(dir: &Path, note: &Note, format: ExportFormat, path: &Path) -> Result<(), String> {
+    let bytes = render(dir, note, format)?;
+    let parent = path.parent().unwrap_or(Path::new(""));
+    if !parent.as_os_str().is_empty() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    atomic::write_file(path, &bytes)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    let Ok(entries) = fs::read_dir(attachments::assets_dir(dir, &note.id)) else {
+        return Ok(());
+    };
+    let target = parent.join(format!("{}{ASSETS_SUFFIX}", note.id.name()));
+    fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+    for entry in entries.flatten() {
+        if entry.file_type().is_ok_and(|t| t.is_file()) {
+            // Sealed attachments of an encrypted note are exported opened.
+            let bytes = fs::read(entry.path())
+                .map_err(|e| e.to_string())
+                .and_then(|raw| encryption::open_file(dir, &note.id, raw))
+                .map_err(|e| format!("Failed to copy attachment: {e}"))?;
+            atomic::write_file(&target.join(entry.file_name()), &bytes)
+                .map_err(|e| format!("Failed to copy attachment: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Export a note, with its date, participants and tags, to `path`.
+#[tauri::command]
+pub fn export_note(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    format: ExportFormat,
+    path: String,
+) -> Result<(), String> {
+    let dir = library.notes_dir()?;
+    let note = notes::load_note(&dir, &id)?;
+    write_export(&dir, &note, format, Path::new(&path))
+}
+
+/// Export every note `query` matches (by folder, tag, participant or date
+/// range) into `dir`, mirroring their folders. Returns the files written.
+#[tauri::command]
+pub fn export_notes(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+    query: NoteQuery,
+    format: ExportFormat,
+    dir: String,
+) -> Result<Vec<String>, String> {
+    let lib = library.active()?;
+    let notes_dir = lib.notes_dir()?;
+    let page = catalog.list(&lib, &query)?;
+    let mut written = Vec::new();
+    for summary in page.notes {
+        let note = notes::load_note(&notes_dir, &summary.id)?;
+        let path = Path::new(&dir).join(format!("{}.{}", note.id, format.extension()));
+        write_export(&notes_dir, &note, format, &path)?;
+        written.push(path.display().to_string());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// The start of a 2×1 PNG: enough for `attachments::image_size`.
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x02\0\0\0\x01\x08\x06\0\0\0";
+
+    /// Note `2026-03-01-kickoff` with an image attached in `dir`.
+    fn note(dir: &Path, content: &str) -> Note {
+        let id = NoteId::parse("2026-03-01-kickoff").unwrap();
+        let assets = attachments::assets_dir(dir, &id);
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("chart.png"), PNG).unwrap();
+        let meta = serde_json::from_value(serde_json::json!({
+            "title": "Kickoff <Q3>",
+            "participants": ["Ana", "Sam"],
+            "tags": ["launch"],
+            "created_at": "2026-03-01T09:00:00Z",
+        }))
+        .unwrap();
+        Note {
+            id,
+            content: content.to_string(),
+            meta,
+            revision: String::new(),
+            encrypted: false,
+        }
+    }
+
+    #[test]
+    fn wiki_links_become_their_labels() {
+        assert_eq!(
+            plain_links("See [[2026-02-20-budget|the budget]] and [[Hiring plan]]."),
+            "See the budget and Hiring plan."
+        );
+        assert_eq!(
+            plain_links("Empty label: [[Roadmap|]]"),
+            "Empty label: Roadmap"
+        );
+        assert_eq!(plain_links("No links [here]"), "No links [here]");
+    }
+
+    #[test]
+    fn html_has_header_fields_and_embedded_images() {
+        let tmp = tempfile::tempdir().unwrap();
+        let note = note(
+            tmp.path(),
+            "Agreed on [[budget|the budget]].\n\n![chart](2026-03-01-kickoff.assets/chart.png)\n\n<script>alert(1)</script>\n",
+        );
+        let html = render_html(tmp.path(), &note, false);
+        assert!(html.contains("<title>Kickoff &lt;Q3&gt;</title>"));
+        assert!(html.contains("<dt>Date</dt><dd>Sunday, 1 March 2026</dd>"));
+        assert!(html.contains("<dt>Participants</dt><dd>Ana, Sam</dd>"));
+        assert!(html.contains("<dt>Tags</dt><dd>launch</dd>"));
+        assert!(html.contains("Agreed on the budget."));
+        let data_url = format!("data:image/png;base64,{}", BASE64_STANDARD.encode(PNG));
+        assert!(html.contains(&format!("src=\"{data_url}\"")));
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains(PRINT_CSS));
+        assert!(render_html(tmp.path(), &note, true).contains(PRINT_CSS));
+    }
+
+    #[test]
+    fn docx_is_a_zip_with_the_document() {
+        let tmp = tempfile::tempdir().unwrap();
+        let note = note(
+            tmp.path(),
+            "# Agenda\n\n- [[budget|The budget]] & hiring\n\n![chart](2026-03-01-kickoff.assets/chart.png)\n",
+        );
+        let bytes = render_docx(tmp.path(), &note).unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<&str> = zip.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "docProps/core.xml",
+                "word/_rels/document.xml.rels",
+                "word/document.xml",
+                "word/media/image1.png",
+                "word/styles.xml",
+            ]
+        );
+
+        let mut document = String::new();
+        zip.by_name("word/document.xml")
+            .unwrap()
+            .read_to_string(&mut document)
+            .unwrap();
+        assert!(document.starts_with("<?xml"));
+        assert!(document.ends_with("</w:document>"));
+        assert!(document.contains("Kickoff &lt;Q3&gt;"));
+        assert!(document.contains("Ana, Sam"));
+        assert!(document.contains("The budget &amp; hiring"));
+        assert!(!document.contains("[["));
+        assert!(document.contains(r#"r:embed="rIdImage1""#));
+    }
+}
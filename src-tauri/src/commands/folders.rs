@@ -0,0 +1,227 @@
+//! Folders of notes. A folder is a plain directory in the library; a note's
+//! folder is part of its id, so moving a folder moves every note in it to a
+//! new id along with its recordings and history.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::catalog::CatalogState;
+use super::library::{Library, LibraryState};
+use super::note_id::{FolderPath, NoteId};
+use super::notes;
+use super::search::SearchState;
+use super::trash::TrashEntry;
+use super::watcher::WatcherState;
+
+/// Notes in `folder` or any folder below it.
+fn notes_in(dir: &Path, folder: &FolderPath) -> Vec<NoteId> {
+    notes::note_ids(dir)
+        .into_iter()
+        .filter(|id| folder.contains(id))
+        .collect()
+}
+
+/// Whether a note or folder already goes by `path`.
+fn path_taken(dir: &Path, path: &FolderPath) -> Result<bool, String> {
+    Ok(notes::name_taken(dir, &NoteId::parse(path.as_str())?))
+}
+
+/// Move folder `from` to `to` with everything in it.
+fn relocate_folder(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    from: &FolderPath,
+    to: &FolderPath,
+) -> Result<(), String> {
+    let dir = lib.notes_dir()?;
+
+    let _lock = notes::write_lock();
+    if !dir.join(from).is_dir() {
+        return Err(format!("Folder not found: {from}"));
+    }
+    if from.contains(to) {
+        return Err(format!("Cannot move folder {from} into itself"));
+    }
+    if path_taken(&dir, to)? {
+        return Err(format!("A note or folder named {to} already exists"));
+    }
+
+    let renamed: Vec<(NoteId, NoteId)> = notes_in(&dir, from)
+        .into_iter()
+        .map(|id| {
+            let rest = &id[from.len()..];
+            let new_id = NoteId::parse(format!("{to}{rest}"))?;
+            Ok((id, new_id))
+        })
+        .collect::<Result<_, String>>()?;
+    let moves: Vec<(PathBuf, PathBuf)> = notes::folder_dirs(&dir, from)
+        .into_iter()
+        .zip(notes::folder_dirs(&dir, to))
+        .filter(|(from, _)| from.exists())
+        .collect();
+
+    for (id, _) in &renamed {
+        for path in notes::note_files(&dir, id) {
+            watcher.record_delete(id, &path);
+        }
+    }
+    atomic::move_files(&moves).map_err(|e| e.to_string())?;
+    for (_, new_id) in &renamed {
+        for path in notes::note_files(&dir, new_id) {
+            watcher.record_write(new_id, &path);
+        }
+    }
+    for (id, new_id) in &renamed {
+        notes::note_moved(lib, search, catalog, watcher, id, new_id);
+    }
+    Ok(())
+}
+
+/// The first file below `dir` that is neither hidden nor in `owned`.
+fn stray_file(dir: &Path, owned: &HashSet<PathBuf>) -> Option<PathBuf> {
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            // A note's attachment directory.
+            if owned.contains(&path) {
+                continue;
+            }
+            if hidden {
+                return Some(path);
+            }
+            if let Some(stray) = stray_file(&path, owned) {
+                return Some(stray);
+            }
+        } else if !hidden && !owned.contains(&path) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Remove `dir` and the directories below it if none of them hold any files.
+fn remove_empty_dirs(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries.flatten() {
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        empty &= is_dir && remove_empty_dirs(&entry.path());
+    }
+    empty && fs::remove_dir(dir).is_ok()
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Every folder in the library, including empty ones, parents first.
+#[tauri::command]
+pub fn list_folders(library: tauri::State<LibraryState>) -> Result<Vec<FolderPath>, String> {
+    let dir = library.notes_dir()?;
+    let mut folders = notes::folder_paths(&dir);
+    folders.sort();
+    Ok(folders)
+}
+
+/// Create a folder, and its parents if they are missing.
+#[tauri::command]
+pub fn create_folder(
+    library: tauri::State<LibraryState>,
+    path: FolderPath,
+) -> Result<FolderPath, String> {
+    let dir = library.notes_dir()?;
+    let _lock = notes::write_lock();
+    if path_taken(&dir, &path)? {
+        return Err(format!("A note or folder named {path} already exists"));
+    }
+    fs::create_dir_all(dir.join(&path)).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Rename a folder in place. Returns its new path.
+#[tauri::command]
+pub fn rename_folder(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    path: FolderPath,
+    name: String,
+) -> Result<FolderPath, String> {
+    let lib = library.active()?;
+    let new_path = FolderPath::in_folder(path.parent().as_ref(), name.trim())?;
+    if new_path != path {
+        relocate_folder(&lib, &search, &catalog, &watcher, &path, &new_path)?;
+    }
+    Ok(new_path)
+}
+
+/// Move a folder into `parent` (the top level if `None`). Returns its new path.
+#[tauri::command]
+pub fn move_folder(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    path: FolderPath,
+    parent: Option<FolderPath>,
+) -> Result<FolderPath, String> {
+    let lib = library.active()?;
+    let new_path = FolderPath::in_folder(parent.as_ref(), path.name())?;
+    if new_path != path {
+        relocate_folder(&lib, &search, &catalog, &watcher, &path, &new_path)?;
+    }
+    Ok(new_path)
+}
+
+/// Move every note in a folder to the trash and remove the folder. Refuses if
+/// the folder holds files that are not part of a note, so nothing is deleted
+/// that cannot be restored.
+#[tauri::command]
+pub fn delete_folder(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    path: FolderPath,
+) -> Result<Vec<TrashEntry>, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+
+    let _lock = notes::write_lock();
+    let folder_dir = dir.join(&path);
+    if !folder_dir.is_dir() {
+        return Err(format!("Folder not found: {path}"));
+    }
+    let ids = notes_in(&dir, &path);
+    let owned: HashSet<PathBuf> = ids
+        .iter()
+        .flat_map(|id| notes::note_files(&dir, id))
+        .collect();
+    if let Some(stray) = stray_file(&folder_dir, &owned) {
+        let stray = stray
+            .strip_prefix(&dir)
+            .unwrap_or(&stray)
+            .display()
+            .to_string();
+        return Err(format!(
+            "Folder {path} contains {stray}, which is not part of a note; move it out first"
+        ));
+    }
+
+    let mut trashed = Vec::with_capacity(ids.len());
+    for id in &ids {
+        trashed.push(notes::trash_note(&lib, &search, &catalog, &watcher, id)?);
+    }
+    // Only hidden files such as `.DS_Store` are left in the folder itself.
+    fs::remove_dir_all(&folder_dir).map_err(|e| e.to_string())?;
+    for leftover in notes::folder_dirs(&dir, &path).iter().skip(1) {
+        remove_empty_dirs(leftover);
+    }
+    Ok(trashed)
+}
@@ -0,0 +1,1022 @@
+//! Optional git history and sync for a library, through libgit2.
+//!
+//! Once `enable_git_sync` has made the library a repository, saves and
+//! deletions are committed after `COMMIT_DELAY` without further changes.
+//! `git_pull` and `git_push` exchange commits with the `origin` remote, which
+//! may be a hosted repository or a local bare one (a path or `file://` URL).
+//! Pulling never leaves conflict markers in notes: when both sides changed
+//! the same note nothing is touched, the conflicts are returned, and
+//! `resolve_git_conflicts` redoes the merge with one side picked per note.
+//! `.history/`, `.trash/`, `models/` and `recordings/` stay out of the
+//! repository. Encrypting a note does not reach back into the commits that
+//! already hold it in plaintext; `has_plaintext_history` tells if there are.
+
+use git2::{
+    build::CheckoutBuilder, Commit, Cred, CredentialType, Delta, FetchOptions, IndexAddOption,
+    IndexEntry, IndexTime, Oid, PushOptions, RemoteCallbacks, Repository, RepositoryInitOptions,
+    Signature, Sort, Tree, TreeWalkMode, TreeWalkResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::attachments::ASSETS_SUFFIX;
+use super::encryption;
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes;
+
+/// Quiet time after a save or deletion before it is committed.
+const COMMIT_DELAY: Duration = Duration::from_secs(30);
+const REMOTE: &str = "origin";
+const DEFAULT_BRANCH: &str = "main";
+const GITIGNORE: &str = "\
+# Kept per device; git has the history.
+.history/
+.trash/
+models/
+# Large, and not sealed until their note is encrypted.
+recordings/
+";
+
+/// Serialises commits, merges and pushes.
+static GIT_LOCK: Mutex<()> = Mutex::new(());
+
+fn git_lock() -> MutexGuard<'static, ()> {
+    GIT_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitCommit {
+    pub id: String,
+    pub message: String,
+    pub author: String,
+    pub time: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitStatus {
+    /// Whether the library is a git repository.
+    pub enabled: bool,
+    pub branch: Option<String>,
+    pub remote: Option<String>,
+    /// Commits not yet pushed, and fetched commits not yet pulled.
+    pub ahead: usize,
+    pub behind: usize,
+    /// Files changed since the last commit.
+    pub uncommitted: usize,
+    pub last_commit: Option<GitCommit>,
+}
+
+/// A note, or a file belonging to no note, changed on both sides of a pull.
+#[derive(Serialize, Debug)]
+pub struct GitConflict {
+    /// The note id, or the file's path; the key to pick a side by.
+    pub key: String,
+    pub note_id: Option<NoteId>,
+    /// The files changed on both sides, e.g. just `<id>.meta.json`.
+    pub paths: Vec<String>,
+    /// Each side's version of the first of `paths` that is text, preferring
+    /// the Markdown; `None` if that side deleted it, it is an attachment, or
+    /// it is encrypted and the library is locked.
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSide {
+    /// This device's version.
+    Ours,
+    /// The remote's version.
+    Theirs,
+}
+
+/// Outcome of `git_pull`, tagged by `status` for the frontend.
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PullResult {
+    UpToDate,
+    FastForward,
+    Merged,
+    /// Nothing was changed; pick a side for each with `resolve_git_conflicts`.
+    Conflicts {
+        conflicts: Vec<GitConflict>,
+    },
+}
+
+// ── Auto-commit ──────────────────────────────────────────────────────────────
+
+/// Auto-commits waiting for `COMMIT_DELAY` to pass, by library root.
+#[derive(Default)]
+pub struct GitState {
+    pending: Arc<Mutex<HashMap<PathBuf, Instant>>>,
+}
+
+impl GitState {
+    /// Commit `library` once it has gone `COMMIT_DELAY` without another
+    /// call, if it is a git repository.
+    pub fn schedule_commit(&self, library: &Library) {
+        let root = library.root();
+        if !root.join(".git").exists() {
+            return;
+        }
+        let due = Instant::now() + COMMIT_DELAY;
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        // A thread is already waiting if there is an entry; just push it back.
+        if pending.insert(root.clone(), due).is_some() {
+            return;
+        }
+        let pending = Arc::clone(&self.pending);
+        thread::spawn(move || loop {
+            let wait = {
+                let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(due) = pending.get(&root) else {
+                    return;
+                };
+                let wait = due.saturating_duration_since(Instant::now());
+                if wait.is_zero() {
+                    pending.remove(&root);
+                }
+                wait
+            };
+            if wait.is_zero() {
+                if let Err(e) = open(&root).and_then(|repo| commit_changes(&repo, None)) {
+                    eprintln!("Failed to commit library {}: {e}", root.display());
+                }
+                return;
+            }
+            thread::sleep(wait);
+        });
+    }
+}
+
+// ── Repository ───────────────────────────────────────────────────────────────
+
+fn open(root: &Path) -> Result<Repository, String> {
+    Repository::open(root).map_err(|_| "Git sync is not enabled for this library".to_string())
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    repo.signature()
+        .or_else(|_| Signature::now("Brief", "brief@localhost"))
+        .map_err(|e| e.to_string())
+}
+
+fn branch_name(repo: &Repository) -> String {
+    repo.head()
+        .ok()
+        .and_then(|head| head.shorthand().map(String::from))
+        .unwrap_or_else(|| DEFAULT_BRANCH.to_string())
+}
+
+fn head_commit(repo: &Repository) -> Option<Commit<'_>> {
+    repo.head().ok()?.peel_to_commit().ok()
+}
+
+/// The last fetched state of the remote branch, if any.
+fn remote_commit(repo: &Repository) -> Option<Commit<'_>> {
+    let name = format!("refs/remotes/{REMOTE}/{}", branch_name(repo));
+    repo.find_reference(&name).ok()?.peel_to_commit().ok()
+}
+
+fn commit_info(commit: &Commit) -> GitCommit {
+    let time = chrono::DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
+    GitCommit {
+        id: commit.id().to_string(),
+        message: commit.message().unwrap_or_default().trim().to_string(),
+        author: commit.author().name().unwrap_or_default().to_string(),
+        time: time.to_rfc3339(),
+    }
+}
+
+/// The note a file in the repository belongs to, if any: its Markdown file,
+/// a sidecar, an attachment or a recording.
+fn note_of(path: &str) -> Option<NoteId> {
+    let id = if let Some(rest) = path.strip_prefix("recordings/") {
+        rest.rsplit_once('/')?.0
+    } else if let Some((dir, _)) = path.split_once(&format!("{ASSETS_SUFFIX}/")) {
+        dir
+    } else {
+        path.strip_suffix(".meta.json")
+            .or_else(|| path.strip_suffix(".actions.json"))
+            .or_else(|| path.strip_suffix(".decisions.json"))
+            .or_else(|| path.strip_suffix(".md"))?
+    };
+    NoteId::parse(id).ok()
+}
+
+/// What `path` is grouped under in conflicts: its note id, or itself.
+fn conflict_key(path: &str) -> String {
+    note_of(path).map_or_else(|| path.to_string(), |id| id.to_string())
+}
+
+/// A summary of the changes between two trees, for a commit message.
+fn describe_changes(repo: &Repository, old: Option<&Tree>, new: &Tree) -> Result<String, String> {
+    let diff = repo
+        .diff_tree_to_tree(old, Some(new), None)
+        .map_err(|e| e.to_string())?;
+    let mut changes: BTreeMap<String, &str> = BTreeMap::new();
+    for delta in diff.deltas() {
+        let file = match delta.status() {
+            Delta::Deleted => delta.old_file(),
+            _ => delta.new_file(),
+        };
+        let Some(path) = file.path().and_then(|p| p.to_str()) else {
+            continue;
+        };
+        let verb = match (delta.status(), path.ends_with(".md")) {
+            (Delta::Added, true) => "Add",
+            (Delta::Deleted, true) => "Delete",
+            _ => "Update",
+        };
+        let entry = changes.entry(conflict_key(path)).or_insert(verb);
+        if *entry == "Update" {
+            *entry = verb;
+        }
+    }
+    let lines: Vec<String> = changes
+        .iter()
+        .map(|(key, verb)| format!("{verb} {key}"))
+        .collect();
+    let noun = if changes
+        .keys()
+        .all(|key| NoteId::parse(key.as_str()).is_ok())
+    {
+        "notes"
+    } else {
+        "files"
+    };
+    Ok(match lines.as_slice() {
+        [line] => line.clone(),
+        _ => format!("Update {} {noun}\n\n{}", lines.len(), lines.join("\n")),
+    })
+}
+
+/// Commit every change in the working tree, with `message` or a summary of
+/// the changes. Returns the commit, or `None` if nothing changed.
+fn commit_changes(repo: &Repository, message: Option<&str>) -> Result<Option<GitCommit>, String> {
+    let _git = git_lock();
+    let _lock = notes::write_lock();
+    commit_changes_locked(repo, message)
+}
+
+/// `commit_changes` with `git_lock` and `write_lock` held.
+fn commit_changes_locked(
+    repo: &Repository,
+    message: Option<&str>,
+) -> Result<Option<GitCommit>, String> {
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .map_err(|e| e.to_string())?;
+    index.update_all(["*"], None).map_err(|e| e.to_string())?;
+    index.write().map_err(|e| e.to_string())?;
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+
+    let parent = head_commit(repo);
+    let parent_tree = parent.as_ref().and_then(|p| p.tree().ok());
+    if parent_tree.as_ref().map(|t| t.id()) == Some(tree_id) {
+        return Ok(None);
+    }
+    let message = match message {
+        Some(message) => message.to_string(),
+        None => describe_changes(repo, parent_tree.as_ref(), &tree)?,
+    };
+    let signature = signature(repo)?;
+    let parents: Vec<&Commit> = parent.iter().collect();
+    let id = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )
+        .map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(id).map_err(|e| e.to_string())?;
+    Ok(Some(commit_info(&commit)))
+}
+
+/// Callbacks answering credential requests from the SSH agent or git's
+/// credential helpers, as the `git` command would.
+fn remote_callbacks<'a>() -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| {
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            let config = git2::Config::open_default()?;
+            return Cred::credential_helper(&config, url, username);
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+fn fetch(repo: &Repository) -> Result<(), String> {
+    let mut remote = repo
+        .find_remote(REMOTE)
+        .map_err(|_| "No remote is configured".to_string())?;
+    let branch = branch_name(repo);
+    let refspec = format!("+refs/heads/{branch}:refs/remotes/{REMOTE}/{branch}");
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks());
+    remote
+        .fetch(&[refspec], Some(&mut options), None)
+        .map_err(|e| format!("Failed to fetch from {REMOTE}: {}", e.message()))
+}
+
+/// Replace the working tree with `commit`, make it the branch head and let
+/// the notes' indexes catch up through the watcher.
+fn check_out(repo: &Repository, commit: &Commit, message: &str) -> Result<(), String> {
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+        .map_err(|e| e.to_string())?;
+    let branch = format!("refs/heads/{}", branch_name(repo));
+    repo.reference(&branch, commit.id(), true, message)
+        .map_err(|e| e.to_string())?;
+    repo.set_head(&branch).map_err(|e| e.to_string())
+}
+
+/// The contents of `path` in `tree`, as stored.
+fn file_bytes(repo: &Repository, tree: &Tree, path: &str) -> Option<Vec<u8>> {
+    let blob = tree
+        .get_path(Path::new(path))
+        .ok()?
+        .to_object(repo)
+        .ok()?
+        .peel_to_blob()
+        .ok()?;
+    Some(blob.content().to_vec())
+}
+
+/// The contents of `path` in `tree` as text, or `None` if they are binary.
+/// Encrypted notes and sealed sidecars are opened if the library at `root`
+/// is unlocked.
+fn file_text(repo: &Repository, root: &Path, tree: &Tree, path: &str) -> Option<String> {
+    let mut bytes = file_bytes(repo, tree, path)?;
+    if let Some(id) = note_of(path) {
+        bytes = encryption::open_file(root, &id, bytes).ok()?;
+    }
+    let text = String::from_utf8(bytes).ok()?;
+    match (encryption::sealed_body(&text), note_of(path)) {
+        (Some(sealed), Some(id)) => encryption::open_note(root, &id, sealed)
+            .ok()
+            .map(|(body, _)| body),
+        _ => Some(text),
+    }
+}
+
+/// Make `index` hold `side`'s version of every file grouped under `key`.
+fn take_side(
+    repo: &Repository,
+    index: &mut git2::Index,
+    side: &Tree,
+    key: &str,
+) -> Result<(), String> {
+    let stale: Vec<String> = index
+        .iter()
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+        .filter(|path| conflict_key(path) == key)
+        .collect();
+    for path in stale {
+        index
+            .remove_path(Path::new(&path))
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut entries = Vec::new();
+    side.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let path = format!("{dir}{}", entry.name().unwrap_or_default());
+        if entry.kind() == Some(git2::ObjectType::Blob) && conflict_key(&path) == key {
+            entries.push((path, entry.id(), entry.filemode()));
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(|e| e.to_string())?;
+    for (path, id, mode) in entries {
+        let size = repo
+            .find_blob(id)
+            .map(|blob| blob.size() as u32)
+            .unwrap_or_default();
+        let entry = IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: mode as u32,
+            uid: 0,
+            gid: 0,
+            file_size: size,
+            id,
+            // The path length, and stage 0: a resolved entry.
+            flags: path.len().min(0xFFF) as u16,
+            flags_extended: 0,
+            path: path.into_bytes(),
+        };
+        index.add(&entry).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Make `root` a repository, if it is not one yet, with `remote` as `origin`,
+/// and commit what is in it.
+fn init(root: &Path, remote: Option<&str>) -> Result<Repository, String> {
+    let repo = match Repository::open(root) {
+        Ok(repo) => repo,
+        Err(_) => {
+            let mut options = RepositoryInitOptions::new();
+            options.initial_head(DEFAULT_BRANCH);
+            Repository::init_opts(root, &options).map_err(|e| e.to_string())?
+        }
+    };
+    let gitignore = root.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, GITIGNORE).map_err(|e| e.to_string())?;
+    }
+    if let Some(url) = remote.map(str::trim).filter(|url| !url.is_empty()) {
+        set_remote(&repo, Some(url))?;
+    }
+    commit_changes(&repo, Some("Start syncing library"))?;
+    Ok(repo)
+}
+
+fn set_remote(repo: &Repository, url: Option<&str>) -> Result<(), String> {
+    let exists = repo.find_remote(REMOTE).is_ok();
+    match url {
+        Some(url) if exists => repo.remote_set_url(REMOTE, url),
+        Some(url) => repo.remote(REMOTE, url).map(|_| ()),
+        None if exists => repo.remote_delete(REMOTE),
+        None => Ok(()),
+    }
+    .map_err(|e| e.to_string())
+}
+
+fn push(repo: &Repository) -> Result<(), String> {
+    let mut remote = repo
+        .find_remote(REMOTE)
+        .map_err(|_| "No remote is configured".to_string())?;
+    let branch = branch_name(repo);
+    let refspec = format!("refs/heads/{branch}:refs/heads/{branch}");
+
+    let _git = git_lock();
+    let mut rejected = None;
+    let mut callbacks = remote_callbacks();
+    callbacks.push_update_reference(|_, status| {
+        rejected = status.map(String::from);
+        Ok(())
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    remote
+        .push(&[refspec], Some(&mut options))
+        .map_err(|e| match e.code() {
+            git2::ErrorCode::NotFastForward => {
+                format!("{REMOTE} has changes that are not here yet; pull first")
+            }
+            _ => format!("Failed to push to {REMOTE}: {}", e.message()),
+        })?;
+    drop(options);
+    match rejected {
+        Some(reason) => Err(format!("{REMOTE} rejected the push: {reason}")),
+        None => Ok(()),
+    }
+}
+
+/// Merge the fetched remote branch into the library at `root`, taking the
+/// side in `choices` for notes changed on both. Commits local changes first.
+fn merge_remote(
+    repo: &Repository,
+    root: &Path,
+    choices: &HashMap<String, ConflictSide>,
+) -> Result<PullResult, String> {
+    let _git = git_lock();
+    let _lock = notes::write_lock();
+    commit_changes_locked(repo, None)?;
+
+    let Some(theirs) = remote_commit(repo) else {
+        return Ok(PullResult::UpToDate);
+    };
+    let Some(ours) = head_commit(repo) else {
+        check_out(repo, &theirs, "pull: checkout")?;
+        return Ok(PullResult::FastForward);
+    };
+    let descends = |a: Oid, b: Oid| a == b || repo.graph_descendant_of(a, b).unwrap_or(false);
+    if descends(ours.id(), theirs.id()) {
+        return Ok(PullResult::UpToDate);
+    }
+    if descends(theirs.id(), ours.id()) {
+        check_out(repo, &theirs, "pull: fast-forward")?;
+        return Ok(PullResult::FastForward);
+    }
+
+    // Unrelated histories, e.g. two devices that each started a repository,
+    // merge as if from an empty library.
+    let ancestor = match repo.merge_base(ours.id(), theirs.id()) {
+        Ok(base) => repo.find_commit(base).and_then(|c| c.tree()),
+        Err(_) => repo
+            .treebuilder(None)
+            .and_then(|builder| builder.write())
+            .and_then(|id| repo.find_tree(id)),
+    }
+    .map_err(|e| e.to_string())?;
+    let (our_tree, their_tree) = (
+        ours.tree().map_err(|e| e.to_string())?,
+        theirs.tree().map_err(|e| e.to_string())?,
+    );
+    let mut index = repo
+        .merge_trees(&ancestor, &our_tree, &their_tree, None)
+        .map_err(|e| e.to_string())?;
+
+    if index.has_conflicts() {
+        // Conflicting paths by conflict key.
+        let mut keys: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for conflict in index.conflicts().map_err(|e| e.to_string())? {
+            let conflict = conflict.map_err(|e| e.to_string())?;
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(path) = entry.and_then(|e| String::from_utf8(e.path).ok()) {
+                keys.entry(conflict_key(&path)).or_default().push(path);
+            }
+        }
+        let conflicts: Vec<GitConflict> = keys
+            .iter()
+            .filter(|(key, _)| !choices.contains_key(*key))
+            .map(|(key, paths)| {
+                let note_id = NoteId::parse(key.as_str()).ok();
+                let markdown = paths.iter().find(|path| path.ends_with(".md"));
+                let (ours, theirs) = markdown
+                    .into_iter()
+                    .chain(paths)
+                    .map(|path| {
+                        (
+                            file_text(repo, root, &our_tree, path),
+                            file_text(repo, root, &their_tree, path),
+                        )
+                    })
+                    .find(|(ours, theirs)| ours.is_some() || theirs.is_some())
+                    .unwrap_or_default();
+                GitConflict {
+                    key: key.clone(),
+                    note_id,
+                    paths: paths.clone(),
+                    ours,
+                    theirs,
+                }
+            })
+            .collect();
+        if !conflicts.is_empty() {
+            return Ok(PullResult::Conflicts { conflicts });
+        }
+        for key in keys.keys() {
+            let side = match choices[key] {
+                ConflictSide::Ours => &our_tree,
+                ConflictSide::Theirs => &their_tree,
+            };
+            take_side(repo, &mut index, side, key)?;
+        }
+    }
+
+    let tree_id = index.write_tree_to(repo).map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+    let signature = signature(repo)?;
+    let message = format!("Merge changes from {REMOTE}");
+    let merged = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &[&ours, &theirs],
+        )
+        .map_err(|e| e.to_string())?;
+    let merged = repo.find_commit(merged).map_err(|e| e.to_string())?;
+    check_out(repo, &merged, "pull: merge")?;
+    Ok(PullResult::Merged)
+}
+
+/// Whether a commit in the library at `root` holds note `id` unencrypted,
+/// where encrypting it now cannot reach. `false` without git sync.
+pub(crate) fn has_plaintext_history(root: &Path, id: &NoteId) -> Result<bool, String> {
+    let Ok(repo) = Repository::open(root) else {
+        return Ok(false);
+    };
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.push_glob("refs/*").map_err(|e| e.to_string())?;
+    let markdown = format!("{id}.md");
+    for oid in walk {
+        let commit = repo
+            .find_commit(oid.map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        let tree = commit.tree().map_err(|e| e.to_string())?;
+        let plaintext = file_bytes(&repo, &tree, &markdown)
+            .is_some_and(|raw| encryption::sealed_body(&String::from_utf8_lossy(&raw)).is_none());
+        if plaintext || tree.get_path(Path::new(&format!("{id}.meta.json"))).is_ok() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn git_status(library: tauri::State<LibraryState>) -> Result<GitStatus, String> {
+    let root = library.active()?.root();
+    let Ok(repo) = Repository::open(&root) else {
+        return Ok(GitStatus {
+            enabled: false,
+            branch: None,
+            remote: None,
+            ahead: 0,
+            behind: 0,
+            uncommitted: 0,
+            last_commit: None,
+        });
+    };
+    let head = head_commit(&repo);
+    let (ahead, behind) = match (&head, remote_commit(&repo)) {
+        (Some(head), Some(remote)) => repo
+            .graph_ahead_behind(head.id(), remote.id())
+            .map_err(|e| e.to_string())?,
+        _ => (0, 0),
+    };
+    let uncommitted = repo.statuses(None).map_err(|e| e.to_string())?.len();
+    let remote = repo
+        .find_remote(REMOTE)
+        .ok()
+        .and_then(|remote| remote.url().map(String::from));
+    Ok(GitStatus {
+        enabled: true,
+        branch: Some(branch_name(&repo)),
+        remote,
+        ahead,
+        behind,
+        uncommitted,
+        last_commit: head.as_ref().map(commit_info),
+    })
+}
+
+/// Make the active library a git repository, if it is not one yet, and
+/// commit its notes. `remote` becomes `origin`; pull from it next to join
+/// a library another device already pushed.
+#[tauri::command]
+pub fn enable_git_sync(
+    library: tauri::State<LibraryState>,
+    remote: Option<String>,
+) -> Result<GitStatus, String> {
+    let root = library.active()?.notes_dir()?;
+    init(&root, remote.as_deref())?;
+    git_status(library)
+}
+
+/// Set the URL or path of the remote to sync with, or remove it.
+#[tauri::command]
+pub fn set_git_remote(
+    library: tauri::State<LibraryState>,
+    url: Option<String>,
+) -> Result<(), String> {
+    let repo = open(&library.active()?.root())?;
+    set_remote(
+        &repo,
+        url.as_deref().map(str::trim).filter(|url| !url.is_empty()),
+    )
+}
+
+/// Commit pending changes now rather than after `COMMIT_DELAY`. Returns the
+/// commit, or `None` if nothing changed.
+#[tauri::command]
+pub fn git_commit(
+    library: tauri::State<LibraryState>,
+    message: Option<String>,
+) -> Result<Option<GitCommit>, String> {
+    let repo = open(&library.active()?.root())?;
+    commit_changes(&repo, message.as_deref().filter(|m| !m.trim().is_empty()))
+}
+
+/// Fetch the remote and merge it into the library.
+#[tauri::command]
+pub fn git_pull(library: tauri::State<LibraryState>) -> Result<PullResult, String> {
+    let root = library.active()?.root();
+    let repo = open(&root)?;
+    fetch(&repo)?;
+    merge_remote(&repo, &root, &HashMap::new())
+}
+
+/// Finish a pull that returned conflicts, keeping for each conflict key the
+/// side in `choices`. Every conflict needs a choice; ones that appeared since
+/// are returned as conflicts again.
+#[tauri::command]
+pub fn resolve_git_conflicts(
+    library: tauri::State<LibraryState>,
+    choices: HashMap<String, ConflictSide>,
+) -> Result<PullResult, String> {
+    let root = library.active()?.root();
+    let repo = open(&root)?;
+    merge_remote(&repo, &root, &choices)
+}
+
+/// Commit pending changes and push them to the remote. Fails if the remote
+/// has commits that were not pulled yet.
+#[tauri::command]
+pub fn git_push(library: tauri::State<LibraryState>) -> Result<(), String> {
+    let repo = open(&library.active()?.root())?;
+    commit_changes(&repo, None)?;
+    push(&repo)
+}
+
+/// Commits that changed note `id`'s Markdown or meta file, newest first.
+#[tauri::command]
+pub fn note_git_log(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, String> {
+    let repo = open(&library.active()?.root())?;
+    if head_commit(&repo).is_none() {
+        return Ok(vec![]);
+    }
+    let paths = [format!("{id}.md"), format!("{id}.meta.json")];
+    let file_ids = |tree: Option<Tree>| -> Vec<Option<Oid>> {
+        paths
+            .iter()
+            .map(|path| {
+                tree.as_ref()?
+                    .get_path(Path::new(path))
+                    .ok()
+                    .map(|e| e.id())
+            })
+            .collect()
+    };
+
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+    walk.push_head().map_err(|e| e.to_string())?;
+    let mut commits = Vec::new();
+    for oid in walk {
+        let commit = repo
+            .find_commit(oid.map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        let current = file_ids(commit.tree().ok());
+        let previous = file_ids(commit.parent(0).ok().and_then(|p| p.tree().ok()));
+        if current != previous {
+            commits.push(commit_info(&commit));
+            if limit.is_some_and(|limit| commits.len() >= limit) {
+                break;
+            }
+        }
+    }
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "2026-01-05-planning";
+
+    /// A bare repository in `tmp` to sync through, as a `file://` URL.
+    fn bare_remote(tmp: &Path) -> String {
+        let path = tmp.join("remote.git");
+        Repository::init_bare(&path).unwrap();
+        format!("file://{}", path.display())
+    }
+
+    /// A library directory `name` in `tmp` holding `files`.
+    fn library(tmp: &Path, name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = tmp.join(name);
+        fs::create_dir_all(&root).unwrap();
+        for (path, contents) in files {
+            write(&root, path, contents);
+        }
+        root
+    }
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn read(root: &Path, path: &str) -> String {
+        fs::read_to_string(root.join(path)).unwrap()
+    }
+
+    fn pull(repo: &Repository, root: &Path) -> PullResult {
+        fetch(repo).unwrap();
+        merge_remote(repo, root, &HashMap::new()).unwrap()
+    }
+
+    /// Two libraries synced through a fresh remote, both holding `NOTE`.
+    fn synced_pair(tmp: &Path) -> ((Repository, PathBuf), (Repository, PathBuf)) {
+        let url = bare_remote(tmp);
+        let a = library(tmp, "a", &[(&format!("{NOTE}.md"), "Agenda\n")]);
+        let repo_a = init(&a, Some(&url)).unwrap();
+        push(&repo_a).unwrap();
+        let b = library(tmp, "b", &[]);
+        let repo_b = init(&b, Some(&url)).unwrap();
+        // Both start with a commit of their own `.gitignore`.
+        assert!(matches!(pull(&repo_b, &b), PullResult::Merged));
+        push(&repo_b).unwrap();
+        assert!(matches!(pull(&repo_a, &a), PullResult::FastForward));
+        ((repo_a, a), (repo_b, b))
+    }
+
+    fn is_clean(repo: &Repository) -> bool {
+        repo.statuses(None).unwrap().is_empty()
+    }
+
+    #[test]
+    fn libraries_sync_through_a_file_remote() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ((repo_a, a), (repo_b, b)) = synced_pair(tmp.path());
+        assert_eq!(read(&b, &format!("{NOTE}.md")), "Agenda\n");
+
+        write(&b, &format!("{NOTE}.md"), "Agenda\n- budget\n");
+        commit_changes(&repo_b, None).unwrap();
+        push(&repo_b).unwrap();
+        assert!(matches!(pull(&repo_a, &a), PullResult::FastForward));
+        assert_eq!(read(&a, &format!("{NOTE}.md")), "Agenda\n- budget\n");
+        assert!(matches!(pull(&repo_a, &a), PullResult::UpToDate));
+        assert!(is_clean(&repo_a));
+    }
+
+    #[test]
+    fn libraries_started_apart_merge() {
+        let tmp = tempfile::tempdir().unwrap();
+        let url = bare_remote(tmp.path());
+        let a = library(tmp.path(), "a", &[("2026-01-05-a.md", "from a")]);
+        push(&init(&a, Some(&url)).unwrap()).unwrap();
+        let b = library(tmp.path(), "b", &[("2026-01-06-b.md", "from b")]);
+        let repo_b = init(&b, Some(&url)).unwrap();
+
+        assert!(matches!(pull(&repo_b, &b), PullResult::Merged));
+        assert_eq!(read(&b, "2026-01-05-a.md"), "from a");
+        assert_eq!(read(&b, "2026-01-06-b.md"), "from b");
+        push(&repo_b).unwrap();
+    }
+
+    #[test]
+    fn edits_to_different_notes_merge_cleanly() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ((repo_a, a), (repo_b, b)) = synced_pair(tmp.path());
+        write(&a, &format!("{NOTE}.md"), "Agenda, edited on a\n");
+        commit_changes(&repo_a, None).unwrap();
+        push(&repo_a).unwrap();
+        write(&b, "2026-01-06-retro.md", "Retro\n");
+
+        assert!(matches!(pull(&repo_b, &b), PullResult::Merged));
+        assert_eq!(read(&b, &format!("{NOTE}.md")), "Agenda, edited on a\n");
+        assert_eq!(read(&b, "2026-01-06-retro.md"), "Retro\n");
+        assert!(is_clean(&repo_b));
+        push(&repo_b).unwrap();
+    }
+
+    #[test]
+    fn conflicts_are_resolved_by_picking_a_side() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ((repo_a, a), (repo_b, b)) = synced_pair(tmp.path());
+        let markdown = format!("{NOTE}.md");
+        write(&a, &markdown, "Agenda from a\n");
+        write(
+            &a,
+            &format!("{NOTE}.meta.json"),
+            "{\"title\": \"Planning\"}",
+        );
+        commit_changes(&repo_a, None).unwrap();
+        push(&repo_a).unwrap();
+        write(&b, &markdown, "Agenda from b\n");
+
+        let PullResult::Conflicts { conflicts } = pull(&repo_b, &b) else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].key, NOTE);
+        assert_eq!(conflicts[0].paths, std::slice::from_ref(&markdown));
+        assert_eq!(conflicts[0].ours.as_deref(), Some("Agenda from b\n"));
+        assert_eq!(conflicts[0].theirs.as_deref(), Some("Agenda from a\n"));
+        // Nothing is touched until a side is picked.
+        assert_eq!(read(&b, &markdown), "Agenda from b\n");
+
+        let choices = HashMap::from([(NOTE.to_string(), ConflictSide::Theirs)]);
+        assert!(matches!(
+            merge_remote(&repo_b, &b, &choices).unwrap(),
+            PullResult::Merged
+        ));
+        assert_eq!(read(&b, &markdown), "Agenda from a\n");
+        assert!(b.join(format!("{NOTE}.meta.json")).exists());
+        assert!(is_clean(&repo_b));
+        push(&repo_b).unwrap();
+
+        write(&a, &markdown, "Agenda from a, again\n");
+        commit_changes(&repo_a, None).unwrap();
+        write(&b, &markdown, "Agenda from b, again\n");
+        commit_changes(&repo_b, None).unwrap();
+        push(&repo_b).unwrap();
+        fetch(&repo_a).unwrap();
+        let choices = HashMap::from([(NOTE.to_string(), ConflictSide::Ours)]);
+        assert!(matches!(
+            merge_remote(&repo_a, &a, &choices).unwrap(),
+            PullResult::Merged
+        ));
+        assert_eq!(read(&a, &markdown), "Agenda from a, again\n");
+        assert!(is_clean(&repo_a));
+        push(&repo_a).unwrap();
+    }
+
+    #[test]
+    fn meta_conflicts_show_the_meta() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ((repo_a, a), (repo_b, b)) = synced_pair(tmp.path());
+        let meta = format!("{NOTE}.meta.json");
+        write(&a, &meta, "{\"title\": \"Planning\"}");
+        commit_changes(&repo_a, None).unwrap();
+        push(&repo_a).unwrap();
+        write(&b, &meta, "{\"title\": \"Q1 planning\"}");
+
+        let PullResult::Conflicts { conflicts } = pull(&repo_b, &b) else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(conflicts[0].paths, [meta]);
+        assert_eq!(
+            conflicts[0].ours.as_deref(),
+            Some("{\"title\": \"Q1 planning\"}")
+        );
+        assert_eq!(
+            conflicts[0].theirs.as_deref(),
+            Some("{\"title\": \"Planning\"}")
+        );
+    }
+
+    #[test]
+    fn pushing_behind_the_remote_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ((repo_a, a), (repo_b, b)) = synced_pair(tmp.path());
+        write(&a, "2026-01-06-a.md", "a");
+        commit_changes(&repo_a, None).unwrap();
+        push(&repo_a).unwrap();
+        write(&b, "2026-01-06-b.md", "b");
+        commit_changes(&repo_b, None).unwrap();
+
+        let err = push(&repo_b).unwrap_err();
+        assert!(err.contains("pull first"), "{err}");
+    }
+
+    #[test]
+    fn device_files_stay_out_of_the_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = library(
+            tmp.path(),
+            "a",
+            &[
+                (&format!("{NOTE}.md"), "Agenda"),
+                (&format!(".history/{NOTE}/1.md.gz"), "old"),
+                (&format!("recordings/{NOTE}/20260105-100000.wav"), "RIFF"),
+                ("models/ggml-base.en.bin", "model"),
+            ],
+        );
+        let repo = init(&root, None).unwrap();
+        let tree = head_commit(&repo).unwrap().tree().unwrap();
+        assert!(tree.get_path(Path::new(&format!("{NOTE}.md"))).is_ok());
+        for dir in [".history", "recordings", "models"] {
+            assert!(
+                tree.get_path(Path::new(dir)).is_err(),
+                "{dir} was committed"
+            );
+        }
+    }
+
+    #[test]
+    fn plaintext_history_is_detected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let id = NoteId::parse(NOTE).unwrap();
+        let other = NoteId::parse("2026-01-06-other").unwrap();
+        let root = library(tmp.path(), "a", &[]);
+        assert!(!has_plaintext_history(&root, &id).unwrap());
+
+        write(
+            &root,
+            &format!("{NOTE}.md"),
+            "brief-encrypted-note v1\nc2VhbGVk\n",
+        );
+        let repo = init(&root, None).unwrap();
+        assert!(!has_plaintext_history(&root, &id).unwrap());
+
+        write(&root, &format!("{other}.md"), "Salaries");
+        commit_changes(&repo, None).unwrap();
+        write(
+            &root,
+            &format!("{other}.md"),
+            "brief-encrypted-note v1\nc2VhbGVk\n",
+        );
+        commit_changes(&repo, None).unwrap();
+        assert!(has_plaintext_history(&root, &other).unwrap());
+    }
+}
@@ -0,0 +1,419 @@
+//! Per-note version history.
+//!
+//! Snapshots of a note live in `<library>/.history/<id>/`: a `versions.json`
+//! index plus gzip-compressed blobs named after the hash of their contents, so
+//! versions with identical contents share one blob. The directory travels with
+//! the note when it is trashed or renamed.
+
+use chrono::{DateTime, Duration, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::atomic;
+use super::catalog::CatalogState;
+use super::diff::{self, DiffLine};
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes::{self, Note, NoteMeta, WriteNoteError};
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+pub(crate) const HISTORY_DIR: &str = ".history";
+const INDEX: &str = "versions.json";
+const BLOB_SUFFIX: &str = ".json.gz";
+/// Autosave snapshots are taken at most this often per note.
+const SNAPSHOT_INTERVAL_MINUTES: i64 = 5;
+/// Hard cap per note, on top of the retention policy.
+const MAX_VERSIONS: usize = 500;
+
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    Autosave,
+    BeforeAi,
+    BeforeRestore,
+}
+
+/// One entry in a note's history.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Version {
+    pub version_id: String,
+    pub created_at: String,
+    pub reason: SnapshotReason,
+    /// Revision of the note this snapshot was taken from.
+    pub revision: String,
+    pub title: String,
+    /// Uncompressed size of the note body in bytes.
+    pub size: usize,
+    /// Content hash naming the compressed snapshot.
+    pub blob: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    content: String,
+    meta: NoteMeta,
+}
+
+/// Where the history of note `id` lives.
+pub(crate) fn history_dir(root: &Path, id: &NoteId) -> PathBuf {
+    root.join(HISTORY_DIR).join(id)
+}
+
+fn blob_path(dir: &Path, blob: &str) -> PathBuf {
+    dir.join(format!("{blob}{BLOB_SUFFIX}"))
+}
+
+/// Versions of a note, oldest first.
+fn read_index(dir: &Path) -> Vec<Version> {
+    fs::read(dir.join(INDEX))
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, versions: &[Version]) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(versions).map_err(|e| e.to_string())?;
+    atomic::write_file(&dir.join(INDEX), &json).map_err(|e| e.to_string())
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Record `note` in its history. Autosave snapshots are throttled; every
+/// snapshot is skipped if the note is unchanged since the last one, or
+/// encrypted, since history is kept in plaintext. Returns the new version, if
+/// one was taken.
+pub(crate) fn snapshot(
+    lib: &Library,
+    note: &Note,
+    reason: SnapshotReason,
+) -> Result<Option<Version>, String> {
+    if note.encrypted {
+        return Ok(None);
+    }
+    let dir = history_dir(&lib.notes_dir()?, &note.id);
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut versions = read_index(&dir);
+    let now = Utc::now();
+
+    if let Some(last) = versions.last() {
+        if last.revision == note.revision {
+            return Ok(None);
+        }
+        let recent = parse_time(&last.created_at)
+            .map(|t| now - t < Duration::minutes(SNAPSHOT_INTERVAL_MINUTES))
+            .unwrap_or(false);
+        if reason == SnapshotReason::Autosave && recent {
+            return Ok(None);
+        }
+    }
+
+    let snapshot = Snapshot {
+        content: note.content.clone(),
+        meta: note.meta.clone(),
+    };
+    let json = serde_json::to_vec(&snapshot).map_err(|e| e.to_string())?;
+    let blob: String = Sha256::digest(&json)
+        .iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect();
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = blob_path(&dir, &blob);
+    if !path.exists() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&json).map_err(|e| e.to_string())?;
+        let compressed = encoder.finish().map_err(|e| e.to_string())?;
+        atomic::write_file(&path, &compressed).map_err(|e| e.to_string())?;
+    }
+
+    let version = Version {
+        version_id: now.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+        created_at: now.to_rfc3339(),
+        reason,
+        revision: note.revision.clone(),
+        title: note.meta.title.clone(),
+        size: note.content.len(),
+        blob,
+    };
+    versions.push(version.clone());
+    prune(&mut versions, lib.settings.history_retention_days, now);
+    write_index(&dir, &versions)?;
+    remove_unused_blobs(&dir, &versions);
+    Ok(Some(version))
+}
+
+/// `snapshot`, logging failures: history is never a reason to refuse an edit.
+pub(crate) fn snapshot_or_log(lib: &Library, note: &Note, reason: SnapshotReason) {
+    if let Err(e) = snapshot(lib, note, reason) {
+        eprintln!("Failed to snapshot note {}: {e}", note.id);
+    }
+}
+
+/// Delete every version of note `id`.
+pub(crate) fn clear(root: &Path, id: &NoteId) -> Result<(), String> {
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    match fs::remove_dir_all(history_dir(root, id)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Keep every version from the last day and the newest version of each older
+/// day, dropping anything past `retention_days`.
+fn prune(versions: &mut Vec<Version>, retention_days: Option<u32>, now: DateTime<Utc>) {
+    let cutoff = retention_days.map(|days| now - Duration::days(days.into()));
+    let mut days_seen = HashSet::new();
+    let mut kept: Vec<Version> = versions
+        .drain(..)
+        .rev()
+        .filter(|version| {
+            let Some(created) = parse_time(&version.created_at) else {
+                return false;
+            };
+            if cutoff.is_some_and(|cutoff| created < cutoff) {
+                return false;
+            }
+            now - created < Duration::days(1) || days_seen.insert(created.date_naive())
+        })
+        .take(MAX_VERSIONS)
+        .collect();
+    kept.reverse();
+    *versions = kept;
+}
+
+fn remove_unused_blobs(dir: &Path, versions: &[Version]) {
+    let used: HashSet<&str> = versions.iter().map(|v| v.blob.as_str()).collect();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(blob) = name.strip_suffix(BLOB_SUFFIX) {
+            if !used.contains(blob) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+fn load_version(root: &Path, id: &NoteId, version_id: &str) -> Result<(Version, Snapshot), String> {
+    let dir = history_dir(root, id);
+    let version = read_index(&dir)
+        .into_iter()
+        .find(|v| v.version_id == version_id)
+        .ok_or_else(|| format!("Unknown version {version_id} of note {id}"))?;
+    if !version.blob.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Corrupt history index for note {id}"));
+    }
+    let file = fs::File::open(blob_path(&dir, &version.blob)).map_err(|e| e.to_string())?;
+    let mut json = Vec::new();
+    GzDecoder::new(file)
+        .read_to_end(&mut json)
+        .map_err(|e| e.to_string())?;
+    let snapshot = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    Ok((version, snapshot))
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Versions of a note, newest first.
+#[tauri::command]
+pub fn list_versions(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+) -> Result<Vec<Version>, String> {
+    let root = library.notes_dir()?;
+    let mut versions = read_index(&history_dir(&root, &id));
+    versions.reverse();
+    Ok(versions)
+}
+
+/// A past version of a note. Its `revision` is that of the note it was taken from.
+#[tauri::command]
+pub fn read_version(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    version_id: String,
+) -> Result<Note, String> {
+    let root = library.notes_dir()?;
+    let (version, snapshot) = load_version(&root, &id, &version_id)?;
+    Ok(Note {
+        id,
+        content: snapshot.content,
+        meta: snapshot.meta,
+        revision: version.revision,
+        encrypted: false,
+    })
+}
+
+/// Line diff of the note body between version `from` and version `to`, or
+/// the current note when `to` is `None`.
+#[tauri::command]
+pub fn diff_versions(
+    library: tauri::State<LibraryState>,
+    id: NoteId,
+    from: String,
+    to: Option<String>,
+) -> Result<Vec<DiffLine>, String> {
+    let root = library.notes_dir()?;
+    let (_, old) = load_version(&root, &id, &from)?;
+    let new = match to {
+        Some(to) => load_version(&root, &id, &to)?.1.content,
+        None => notes::load_note(&root, &id)?.content,
+    };
+    Ok(diff::line_diff(&old.content, &new))
+}
+
+/// Replace a note with one of its versions, snapshotting the current contents
+/// first. `base_revision` is checked exactly as in `write_note`.
+#[tauri::command]
+pub fn restore_version(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+    version_id: String,
+    base_revision: Option<String>,
+) -> Result<String, WriteNoteError> {
+    let lib = library.active()?;
+    restore(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        id,
+        &version_id,
+        base_revision,
+    )
+}
+
+/// The work behind `restore_version`. A restore refused as a conflict leaves
+/// the history as it was.
+fn restore(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: NoteId,
+    version_id: &str,
+    base_revision: Option<String>,
+) -> Result<String, WriteNoteError> {
+    let root = lib.notes_dir()?;
+    let (_, snapshot) = load_version(&root, &id, version_id)?;
+
+    let _lock = notes::write_lock();
+    if let Ok(current) = notes::load_note(&root, &id) {
+        if base_revision.as_deref() == Some(current.revision.as_str()) {
+            snapshot_or_log(lib, &current, SnapshotReason::BeforeRestore);
+        }
+    }
+    let mut meta = snapshot.meta;
+    meta.updated_at = Utc::now().to_rfc3339();
+    notes::save_note_locked(
+        lib,
+        search,
+        catalog,
+        watcher,
+        id,
+        snapshot.content,
+        meta,
+        base_revision,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicting_restores_leave_history_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lib = Library {
+            id: "test-history-restore".to_string(),
+            name: "Test".to_string(),
+            path: tmp.path().to_string_lossy().into_owned(),
+            settings: Default::default(),
+        };
+        let (search, catalog) = (SearchState::default(), CatalogState::default());
+        let watcher = WatcherState::default();
+        let id = NoteId::parse("2026-03-01-kickoff").unwrap();
+        let save = |content: &str, base: Option<String>| {
+            let mut meta = notes::load_note(tmp.path(), &id)
+                .map(|note| note.meta)
+                .unwrap_or_else(|_| serde_json::from_str("{}").unwrap());
+            meta.title = "Kickoff".to_string();
+            notes::save_note(
+                &lib,
+                &search,
+                &catalog,
+                &watcher,
+                id.clone(),
+                content.to_string(),
+                meta,
+                base,
+            )
+        };
+        let versions = || read_index(&history_dir(tmp.path(), &id));
+
+        save("First draft", None).unwrap();
+        let first = notes::load_note(tmp.path(), &id).unwrap();
+        let version = snapshot(&lib, &first, SnapshotReason::BeforeAi)
+            .unwrap()
+            .unwrap();
+        let current = save("Second draft", Some(first.revision.clone())).unwrap();
+        assert_eq!(versions().len(), 1);
+
+        let stale = restore(
+            &lib,
+            &search,
+            &catalog,
+            &watcher,
+            id.clone(),
+            &version.version_id,
+            Some(first.revision),
+        );
+        assert!(matches!(stale, Err(WriteNoteError::Conflict { .. })));
+        assert_eq!(versions().len(), 1);
+        assert_eq!(
+            notes::load_note(tmp.path(), &id).unwrap().content,
+            "Second draft"
+        );
+
+        restore(
+            &lib,
+            &search,
+            &catalog,
+            &watcher,
+            id.clone(),
+            &version.version_id,
+            Some(current),
+        )
+        .unwrap();
+        let reasons: Vec<SnapshotReason> = versions().into_iter().map(|v| v.reason).collect();
+        assert_eq!(
+            reasons,
+            [SnapshotReason::BeforeAi, SnapshotReason::BeforeRestore]
+        );
+        assert_eq!(
+            notes::load_note(tmp.path(), &id).unwrap().content,
+            "First draft"
+        );
+    }
+}
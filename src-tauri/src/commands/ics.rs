@@ -0,0 +1,1189 @@
+//! Import of meetings from iCalendar (`.ics`) files exported by Google
+//! Calendar, Outlook and the like. Events are listed for the user to pick
+//! from, then turned into notes with their attendees as participants and
+//! their description as the agenda. Occurrences of a recurring event share a
+//! meeting series.
+//!
+//! Times are converted to local time: those with a `TZID` through the
+//! file's `VTIMEZONE` of that name, or the IANA zone it names. Times in a
+//! zone neither describes are taken as local wall-clock time.
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+use super::catalog::CatalogState;
+use super::library::LibraryState;
+use super::note_id::FolderPath;
+use super::notes::{self, Note, NoteMeta};
+use super::search::SearchState;
+use super::series::SeriesLink;
+use super::watcher::WatcherState;
+
+/// `NoteMeta` key holding the `event_id` a note was imported from.
+const EVENT_KEY: &str = "calendar_event";
+/// Days listed when the caller gives no end date.
+const DEFAULT_WINDOW_DAYS: i64 = 30;
+/// Upper bound on occurrences generated per recurring event.
+const MAX_OCCURRENCES: usize = 5000;
+
+/// An event, or one occurrence of a recurring event, found in a file.
+#[derive(Serialize, Clone, Debug)]
+pub struct CalendarEvent {
+    /// The event's UID, plus the start for occurrences of recurring events.
+    /// Stable across imports of the same calendar.
+    pub event_id: String,
+    pub title: String,
+    /// Local start time, RFC 3339.
+    pub start: String,
+    pub end: Option<String>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub attendees: Vec<String>,
+    pub description: String,
+    /// Series all occurrences of a recurring event go into.
+    pub series_id: Option<String>,
+    /// Whether a note was already imported for this event.
+    pub imported: bool,
+}
+
+/// A `NAME;PARAM=value:VALUE` content line.
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct EventTime {
+    at: NaiveDateTime,
+    all_day: bool,
+}
+
+/// A `VEVENT` as written in the file.
+#[derive(Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    description: String,
+    location: Option<String>,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    duration: Option<Duration>,
+    attendees: Vec<String>,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+    recurrence_id: Option<NaiveDateTime>,
+    cancelled: bool,
+}
+
+/// A `STANDARD` or `DAYLIGHT` part of a `VTIMEZONE`: the offset from UTC, in
+/// seconds, taking effect at `start` and again whenever `rule` recurs.
+#[derive(Default)]
+struct Observance {
+    start: Option<NaiveDateTime>,
+    offset_from: Option<i32>,
+    offset_to: Option<i32>,
+    rule: Option<Rule>,
+}
+
+/// The `VTIMEZONE`s of a file, by `TZID`.
+type Zones = HashMap<String, Vec<Observance>>;
+
+// ── Parsing ──────────────────────────────────────────────────────────────────
+
+/// Content lines with folded continuation lines joined back up.
+fn unfold(raw: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split `s` on `sep`, ignoring separators inside double quotes.
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let mut head = split_unquoted(&line[..colon], ';').into_iter();
+    let name = head.next()?.trim().to_ascii_uppercase();
+    let params = head
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+/// Undo the escaping of iCalendar text values.
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let at = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&at)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// `+HHMM` or `+HHMMSS` in seconds.
+fn parse_offset(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (sign, digits) = match value.strip_prefix('+') {
+        Some(digits) => (1, digits),
+        None => (-1, value.strip_prefix('-')?),
+    };
+    if !matches!(digits.len(), 4 | 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let part = |at: usize| digits.get(at..at + 2).map_or(0, |d| d.parse().unwrap_or(0));
+    Some(sign * (part(0) * 3600 + part(2) * 60 + part(4)))
+}
+
+/// When `observance` last took effect at or before wall-clock time `at`.
+fn last_onset(observance: &Observance, at: NaiveDateTime) -> Option<NaiveDateTime> {
+    let start = observance.start.filter(|start| *start <= at)?;
+    let Some(rule) = &observance.rule else {
+        return Some(start);
+    };
+    let period = u32::try_from(at.year() - start.year()).ok()? / rule.interval;
+    (period.saturating_sub(1)..=period)
+        .flat_map(|n| period_days(rule, start.date(), n))
+        .map(|day| day.and_time(start.time()))
+        .filter(|onset| (start..=at).contains(onset))
+        .filter(|onset| rule.until.is_none_or(|until| *onset <= until))
+        .max()
+}
+
+/// The offset from UTC, in seconds, of zone `observances` at wall-clock time
+/// `at`: that of the last one to take effect, or before any did, the offset
+/// the earliest one switches from.
+fn zone_offset(observances: &[Observance], at: NaiveDateTime) -> Option<i32> {
+    let latest = observances
+        .iter()
+        .filter_map(|o| Some((last_onset(o, at)?, o.offset_to?)))
+        .max_by_key(|(onset, _)| *onset);
+    match latest {
+        Some((_, offset)) => Some(offset),
+        None => {
+            observances
+                .iter()
+                .filter(|o| o.start.is_some())
+                .min_by_key(|o| o.start)?
+                .offset_from
+        }
+    }
+}
+
+/// Wall-clock time `at` in zone `tzid` as local time; as it is if the zone
+/// is unknown.
+fn in_zone(at: NaiveDateTime, tzid: &str, zones: &Zones) -> NaiveDateTime {
+    let utc = match zones.get(tzid) {
+        Some(observances) => {
+            zone_offset(observances, at).map(|offset| at - Duration::seconds(i64::from(offset)))
+        }
+        None => tzid
+            .trim_start_matches('/')
+            .parse::<chrono_tz::Tz>()
+            .ok()
+            .and_then(|tz| tz.from_local_datetime(&at).earliest())
+            .map(|t| t.naive_utc()),
+    };
+    match utc {
+        Some(utc) => Utc
+            .from_utc_datetime(&utc)
+            .with_timezone(&Local)
+            .naive_local(),
+        None => at,
+    }
+}
+
+/// The time `value` of `property`, in local time.
+fn property_time(property: &Property, value: &str, zones: &Zones) -> Option<NaiveDateTime> {
+    let at = parse_time(value)?;
+    match property.param("TZID") {
+        Some(tzid) if value.contains('T') && !value.ends_with('Z') => {
+            Some(in_zone(at, tzid, zones))
+        }
+        _ => Some(at),
+    }
+}
+
+fn event_time(property: &Property, zones: &Zones) -> Option<EventTime> {
+    let value = property.value.trim();
+    Some(EventTime {
+        at: property_time(property, value, zones)?,
+        all_day: property.param("VALUE") == Some("DATE") || value.len() == 8,
+    })
+}
+
+/// An ISO 8601 duration such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// The attendee's name, or the local part of their address.
+fn attendee_name(property: &Property) -> Option<String> {
+    if matches!(property.param("CUTYPE"), Some("ROOM" | "RESOURCE")) {
+        return None;
+    }
+    let name = property
+        .param("CN")
+        .map(str::trim)
+        .filter(|cn| !cn.is_empty() && !cn.contains('@'));
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => {
+            let address = property.value.trim();
+            let address = address
+                .strip_prefix("mailto:")
+                .or_else(|| address.strip_prefix("MAILTO:"))
+                .unwrap_or(address);
+            address.split('@').next()?.to_string()
+        }
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+fn parse_zones(lines: &[String]) -> Zones {
+    let mut zones = Zones::new();
+    // The `TZID` and observances of the `VTIMEZONE` being read.
+    let mut zone: Option<(String, Vec<Observance>)> = None;
+    let mut current: Option<Observance> = None;
+
+    for line in lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        let value = property.value.trim();
+        let observance =
+            value.eq_ignore_ascii_case("STANDARD") || value.eq_ignore_ascii_case("DAYLIGHT");
+        match (property.name.as_str(), zone.as_mut(), current.as_mut()) {
+            ("BEGIN", None, _) if value.eq_ignore_ascii_case("VTIMEZONE") => {
+                zone = Some((String::new(), Vec::new()));
+            }
+            ("BEGIN", Some(_), None) if observance => current = Some(Observance::default()),
+            ("END", Some((_, observances)), Some(_)) if observance => {
+                observances.extend(current.take());
+            }
+            ("END", Some(_), None) if value.eq_ignore_ascii_case("VTIMEZONE") => {
+                if let Some((tzid, observances)) = zone.take() {
+                    zones.insert(tzid, observances);
+                }
+            }
+            ("TZID", Some((tzid, _)), None) => *tzid = value.to_string(),
+            ("DTSTART", Some(_), Some(o)) => o.start = parse_time(value),
+            ("TZOFFSETFROM", Some(_), Some(o)) => o.offset_from = parse_offset(value),
+            ("TZOFFSETTO", Some(_), Some(o)) => o.offset_to = parse_offset(value),
+            ("RRULE", Some(_), Some(o)) => o.rule = parse_rule(value),
+            _ => {}
+        }
+    }
+    zones
+}
+
+fn parse_events(raw: &str) -> Vec<VEvent> {
+    let lines = unfold(raw);
+    let zones = parse_zones(&lines);
+    let mut events = Vec::new();
+    let mut current: Option<VEvent> = None;
+    // Components nested in an event, such as `VALARM`.
+    let mut nested = 0;
+
+    for line in lines {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+        let value = property.value.trim();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(VEvent::default());
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(current.take());
+            }
+            (_, Some(_)) if nested > 0 => {}
+            (name, Some(event)) => match name {
+                "UID" => event.uid = value.to_string(),
+                "SUMMARY" => event.summary = unescape(value),
+                "DESCRIPTION" => event.description = unescape(&property.value),
+                "LOCATION" => {
+                    event.location = Some(unescape(value)).filter(|l| !l.trim().is_empty())
+                }
+                "DTSTART" => event.start = event_time(&property, &zones),
+                "DTEND" => event.end = event_time(&property, &zones),
+                "DURATION" => event.duration = parse_duration(value),
+                "RRULE" => event.rrule = Some(value.to_string()),
+                "EXDATE" => event.exdates.extend(
+                    value
+                        .split(',')
+                        .filter_map(|v| property_time(&property, v.trim(), &zones)),
+                ),
+                "RECURRENCE-ID" => event.recurrence_id = property_time(&property, value, &zones),
+                "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+                "ATTENDEE" | "ORGANIZER" => {
+                    if let Some(name) = attendee_name(&property) {
+                        if !event
+                            .attendees
+                            .iter()
+                            .any(|a| a.eq_ignore_ascii_case(&name))
+                        {
+                            event.attendees.push(name);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    events
+}
+
+// ── Recurrence ───────────────────────────────────────────────────────────────
+
+/// The parts of an `RRULE` this importer understands.
+struct Rule {
+    freq: String,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    /// Weekdays, each with an optional ordinal within the month (`2TU`, `-1FR`).
+    by_day: Vec<(Option<i32>, Weekday)>,
+    /// Months of yearly rules, such as the switch to daylight saving time.
+    by_month: Vec<u32>,
+}
+
+fn parse_rule(rrule: &str) -> Option<Rule> {
+    let parts: HashMap<String, &str> = rrule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), v))
+        .collect();
+    let weekday = |code: &str| match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    };
+    let by_day = parts
+        .get("BYDAY")
+        .map(|days| {
+            days.split(',')
+                .filter_map(|day| {
+                    let at = day.len().checked_sub(2)?;
+                    let ordinal = day[..at].parse().ok();
+                    Some((ordinal, weekday(&day[at..])?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(Rule {
+        freq: parts.get("FREQ")?.to_ascii_uppercase(),
+        interval: parts
+            .get("INTERVAL")
+            .and_then(|i| i.parse().ok())
+            .unwrap_or(1)
+            .max(1),
+        count: parts.get("COUNT").and_then(|c| c.parse().ok()),
+        until: parts.get("UNTIL").and_then(|u| parse_time(u)),
+        by_day,
+        by_month: parts
+            .get("BYMONTH")
+            .map(|months| months.split(',').filter_map(|m| m.parse().ok()).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// The `ordinal`th `weekday` of a month, counting from the end if negative.
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, ordinal: i32) -> Option<NaiveDate> {
+    if ordinal > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, ordinal as u8)
+    } else {
+        let last = (1..=5)
+            .rev()
+            .find_map(|n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n))?;
+        last.checked_sub_signed(Duration::weeks(i64::from(-ordinal - 1)))
+            .filter(|d| d.month() == month)
+    }
+}
+
+/// Candidate start days of period `n` of `rule`, counting from `start`.
+fn period_days(rule: &Rule, start: NaiveDate, n: u32) -> Vec<NaiveDate> {
+    let step = n * rule.interval;
+    match rule.freq.as_str() {
+        "DAILY" => start
+            .checked_add_signed(Duration::days(i64::from(step)))
+            .into_iter()
+            .collect(),
+        "WEEKLY" => {
+            let week = start - Duration::days(i64::from(start.weekday().num_days_from_monday()))
+                + Duration::weeks(i64::from(step));
+            let mut days: Vec<NaiveDate> = if rule.by_day.is_empty() {
+                vec![week + Duration::days(i64::from(start.weekday().num_days_from_monday()))]
+            } else {
+                rule.by_day
+                    .iter()
+                    .map(|(_, wd)| week + Duration::days(i64::from(wd.num_days_from_monday())))
+                    .collect()
+            };
+            days.sort();
+            days
+        }
+        "MONTHLY" => {
+            let months = start.month0() + step;
+            let year = start.year() + (months / 12) as i32;
+            let month = months % 12 + 1;
+            if rule.by_day.is_empty() {
+                NaiveDate::from_ymd_opt(year, month, start.day())
+                    .into_iter()
+                    .collect()
+            } else {
+                let mut days: Vec<NaiveDate> = rule
+                    .by_day
+                    .iter()
+                    .filter_map(|(ordinal, wd)| nth_weekday(year, month, *wd, ordinal.unwrap_or(1)))
+                    .collect();
+                days.sort();
+                days
+            }
+        }
+        "YEARLY" if !rule.by_month.is_empty() => {
+            let year = start.year() + step as i32;
+            let mut days: Vec<NaiveDate> = rule
+                .by_month
+                .iter()
+                .flat_map(|&month| match rule.by_day.as_slice() {
+                    [] => NaiveDate::from_ymd_opt(year, month, start.day())
+                        .into_iter()
+                        .collect(),
+                    by_day => by_day
+                        .iter()
+                        .filter_map(|(ordinal, wd)| {
+                            nth_weekday(year, month, *wd, ordinal.unwrap_or(1))
+                        })
+                        .collect::<Vec<_>>(),
+                })
+                .collect();
+            days.sort();
+            days
+        }
+        "YEARLY" => NaiveDate::from_ymd_opt(start.year() + step as i32, start.month(), start.day())
+            .into_iter()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The period of `rule` to start expanding at to find the starts from
+/// `from` on: one early, so that a period straddling `from` is not skipped.
+/// A rule with `COUNT` has to be counted from the first period.
+fn first_period(rule: &Rule, start: NaiveDate, from: NaiveDate) -> u32 {
+    if rule.count.is_some() || from <= start {
+        return 0;
+    }
+    let elapsed = match rule.freq.as_str() {
+        "DAILY" => (from - start).num_days(),
+        "WEEKLY" => (from - start).num_days() / 7,
+        "MONTHLY" => {
+            i64::from(from.year() - start.year()) * 12 + i64::from(from.month0())
+                - i64::from(start.month0())
+        }
+        "YEARLY" => i64::from(from.year() - start.year()),
+        _ => 0,
+    };
+    u32::try_from(elapsed / i64::from(rule.interval))
+        .unwrap_or(u32::MAX)
+        .saturating_sub(1)
+}
+
+/// Start times of `event` up to `to`, honouring `COUNT`, `UNTIL` and
+/// `EXDATE`; those of a rule without `COUNT` from around `from` on. A rule
+/// this importer cannot read yields just the first one.
+fn occurrences(
+    event: &VEvent,
+    start: EventTime,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let Some(rule) = event.rrule.as_deref().and_then(parse_rule) else {
+        return vec![start.at];
+    };
+    let first = first_period(&rule, start.at.date(), from.date());
+    let mut found = Vec::new();
+    let mut counted = 0;
+    for n in first.. {
+        for day in period_days(&rule, start.at.date(), n) {
+            let at = day.and_time(start.at.time());
+            if at < start.at {
+                continue;
+            }
+            if at > to
+                || rule.until.is_some_and(|until| at > until)
+                || rule.count.is_some_and(|count| counted >= count)
+                || found.len() >= MAX_OCCURRENCES
+            {
+                return found;
+            }
+            counted += 1;
+            if !event.exdates.contains(&at) {
+                found.push(at);
+            }
+        }
+        if (n - first) as usize > MAX_OCCURRENCES {
+            break;
+        }
+    }
+    found
+}
+
+// ── Events ───────────────────────────────────────────────────────────────────
+
+fn short_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .take(3)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn series_id_for(event: &VEvent) -> String {
+    let mut slug = notes::slugify(&event.summary);
+    slug.truncate(40);
+    let slug = slug.trim_end_matches('-');
+    let hash = short_hash(&event.uid);
+    if slug.is_empty() {
+        format!("event-{hash}")
+    } else {
+        format!("{slug}-{hash}")
+    }
+}
+
+fn local_rfc3339(at: NaiveDateTime) -> String {
+    Local
+        .from_local_datetime(&at)
+        .earliest()
+        .map(|t: DateTime<Local>| t.to_rfc3339())
+        .unwrap_or_else(|| at.and_utc().to_rfc3339())
+}
+
+/// Event ids of the notes already imported into the library in `dir`.
+fn imported_event_ids(dir: &std::path::Path) -> HashSet<String> {
+    notes::note_ids(dir)
+        .into_iter()
+        .filter_map(|id| notes::load_note(dir, &id).ok())
+        .filter_map(|note| {
+            note.meta
+                .extra
+                .get(EVENT_KEY)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// The event's UID, or for one without a hash of its summary and start, which
+/// an override without a UID only shares with its master if it kept both.
+fn event_uid(event: &VEvent) -> Option<String> {
+    if !event.uid.is_empty() {
+        return Some(event.uid.clone());
+    }
+    let start = event.start?;
+    Some(short_hash(&format!("{}{}", event.summary, start.at)))
+}
+
+/// The events in `raw` starting between `from` and `to`, recurring events
+/// expanded into their occurrences, by start time.
+fn calendar_events(raw: &str, from: NaiveDateTime, to: NaiveDateTime) -> Vec<CalendarEvent> {
+    let parsed = parse_events(raw);
+    // Occurrences moved or edited on their own, by UID and original start.
+    let overrides: HashMap<(String, NaiveDateTime), &VEvent> = parsed
+        .iter()
+        .filter_map(|e| Some(((event_uid(e)?, e.recurrence_id?), e)))
+        .collect();
+
+    let mut events = Vec::new();
+    for master in parsed.iter().filter(|e| e.recurrence_id.is_none()) {
+        let Some(start) = master.start else {
+            continue;
+        };
+        let recurring = master.rrule.is_some();
+        let Some(uid) = event_uid(master) else {
+            continue;
+        };
+        for at in occurrences(master, start, from, to) {
+            let (event, begins) = match overrides.get(&(uid.clone(), at)) {
+                Some(moved) => (*moved, moved.start.map_or(at, |s| s.at)),
+                None => (master, at),
+            };
+            if event.cancelled || begins < from || begins > to {
+                continue;
+            }
+            let length = match (event.start, event.end, event.duration) {
+                (Some(s), Some(e), _) => Some(e.at - s.at),
+                (_, _, Some(duration)) => Some(duration),
+                _ => None,
+            };
+            events.push(CalendarEvent {
+                event_id: if recurring {
+                    format!("{uid}/{}", at.format("%Y%m%dT%H%M%S"))
+                } else {
+                    uid.clone()
+                },
+                title: Some(event.summary.trim())
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or("Untitled meeting")
+                    .to_string(),
+                start: local_rfc3339(begins),
+                end: length.map(|length| local_rfc3339(begins + length)),
+                all_day: start.all_day,
+                location: event.location.clone(),
+                // Edited occurrences may list only what changed.
+                attendees: if event.attendees.is_empty() {
+                    master.attendees.clone()
+                } else {
+                    event.attendees.clone()
+                },
+                description: if event.description.trim().is_empty() {
+                    master.description.trim().to_string()
+                } else {
+                    event.description.trim().to_string()
+                },
+                series_id: recurring.then(|| series_id_for(master)),
+                imported: false,
+            });
+        }
+    }
+    events.sort_by(|a, b| (&a.start, &a.event_id).cmp(&(&b.start, &b.event_id)));
+    events
+}
+
+/// The `from`..`to` window as local times; from today for 30 days by default.
+fn window(
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+    let day = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date {value:?}, expected YYYY-MM-DD"))
+    };
+    let from = match from.as_deref() {
+        Some(value) => day(value)?,
+        None => Local::now().date_naive(),
+    };
+    let to = match to.as_deref() {
+        Some(value) => day(value)?,
+        None => from + Duration::days(DEFAULT_WINDOW_DAYS),
+    };
+    let start = from.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let end = to.and_hms_opt(23, 59, 59).unwrap_or_default();
+    Ok((start, end))
+}
+
+fn read_calendar(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Cannot read {path}: {e}"))
+}
+
+/// The Markdown a note imported from `event` starts with.
+fn note_body(event: &CalendarEvent) -> String {
+    let mut body = String::new();
+    let start = DateTime::parse_from_rfc3339(&event.start).ok();
+    let end = event
+        .end
+        .as_deref()
+        .and_then(|e| DateTime::parse_from_rfc3339(e).ok());
+    if let Some(start) = start {
+        let when = match (event.all_day, end) {
+            (true, _) => start.format("%a %-d %b %Y").to_string(),
+            (false, Some(end)) if end.date_naive() == start.date_naive() => {
+                format!(
+                    "{}–{}",
+                    start.format("%a %-d %b %Y, %H:%M"),
+                    end.format("%H:%M")
+                )
+            }
+            (false, _) => start.format("%a %-d %b %Y, %H:%M").to_string(),
+        };
+        body.push_str(&format!("**When:** {when}  \n"));
+    }
+    if let Some(location) = &event.location {
+        body.push_str(&format!("**Where:** {location}  \n"));
+    }
+    if !event.description.is_empty() {
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        body.push_str("## Agenda\n\n");
+        body.push_str(&event.description);
+        body.push('\n');
+    }
+    body
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Events in the `.ics` file at `path` starting between `from` and `to`
+/// (`YYYY-MM-DD`, inclusive; today and the 30 days after by default), with
+/// recurring events expanded into occurrences.
+#[tauri::command]
+pub fn list_ics_events(
+    library: tauri::State<LibraryState>,
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let dir = library.notes_dir()?;
+    let (from, to) = window(from, to)?;
+    let imported = imported_event_ids(&dir);
+    let mut events = calendar_events(&read_calendar(&path)?, from, to);
+    for event in &mut events {
+        event.imported = imported.contains(&event.event_id);
+    }
+    Ok(events)
+}
+
+/// Create a note for each of `event_ids`, as listed by `list_ics_events` with
+/// the same `path`, `from` and `to`, in `folder`. Events imported before are
+/// skipped. Returns the new notes.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn import_ics(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    path: String,
+    event_ids: Vec<String>,
+    from: Option<String>,
+    to: Option<String>,
+    folder: Option<FolderPath>,
+) -> Result<Vec<Note>, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let (from, to) = window(from, to)?;
+    let events = calendar_events(&read_calendar(&path)?, from, to);
+    let imported = imported_event_ids(&dir);
+
+    let mut picked = Vec::new();
+    for event_id in &event_ids {
+        let event = events
+            .iter()
+            .find(|e| &e.event_id == event_id)
+            .ok_or_else(|| format!("No event {event_id} in {path}"))?;
+        if !imported.contains(event_id) {
+            picked.push(event);
+        }
+    }
+
+    let mut notes = Vec::new();
+    for event in picked {
+        let occurrence = DateTime::parse_from_rfc3339(&event.start)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .map_err(|e| e.to_string())?;
+        let mut extra = BTreeMap::new();
+        extra.insert(EVENT_KEY.to_string(), event.event_id.clone().into());
+        extra.insert("starts_at".to_string(), event.start.clone().into());
+        if let Some(end) = &event.end {
+            extra.insert("ends_at".to_string(), end.clone().into());
+        }
+        let meta = NoteMeta {
+            title: event.title.clone(),
+            participants: event.attendees.clone(),
+            tags: vec![],
+            created_at: event.start.clone(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            series: event.series_id.clone().map(|series_id| SeriesLink {
+                series_id,
+                occurrence,
+            }),
+            extra,
+        };
+        notes.push(notes::add_note(
+            &lib,
+            &search,
+            &catalog,
+            &watcher,
+            folder.as_ref(),
+            note_body(event),
+            meta,
+        )?);
+    }
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// `events` in a calendar, with CRLF line ends as exporters write them.
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\nVERSION:2.0\n{events}END:VCALENDAR\n").replace('\n', "\r\n")
+    }
+
+    /// Local start times, without offset, of the events in `events` between
+    /// `from` and `to`.
+    fn starts(events: &str, from: &str, to: &str) -> Vec<String> {
+        calendar_events(&calendar(events), at(from), at(to))
+            .iter()
+            .map(|e| e.start[..16].to_string())
+            .collect()
+    }
+
+    fn weekly(rrule: &str) -> String {
+        format!(
+            "BEGIN:VEVENT\nUID:standup\nSUMMARY:Standup\nDTSTART:20260202T090000\n\
+             DURATION:PT15M\nRRULE:{rrule}\nEND:VEVENT\n"
+        )
+    }
+
+    /// UTC time `text` as local time, without offset.
+    fn local_of_utc(text: &str) -> String {
+        let at = Utc.from_utc_datetime(&at(text)).with_timezone(&Local);
+        at.naive_local().format("%Y-%m-%dT%H:%M").to_string()
+    }
+
+    #[test]
+    fn zoned_times_are_converted_to_local_time() {
+        // As Outlook writes it, under a Windows zone name.
+        let zone = "BEGIN:VTIMEZONE\nTZID:W. Europe Standard Time\n\
+                    BEGIN:STANDARD\nDTSTART:16010101T030000\nTZOFFSETFROM:+0200\n\
+                    TZOFFSETTO:+0100\nRRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10\nEND:STANDARD\n\
+                    BEGIN:DAYLIGHT\nDTSTART:16010101T020000\nTZOFFSETFROM:+0100\n\
+                    TZOFFSETTO:+0200\nRRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3\nEND:DAYLIGHT\n\
+                    END:VTIMEZONE\n";
+        let event = |uid: &str, start: &str| {
+            format!("BEGIN:VEVENT\nUID:{uid}\nSUMMARY:Sync\nDTSTART;{start}\nEND:VEVENT\n")
+        };
+        let events = [
+            zone.to_string(),
+            event("winter", "TZID=W. Europe Standard Time:20260115T100000"),
+            event("summer", "TZID=W. Europe Standard Time:20260715T100000"),
+            event("iana", "TZID=America/New_York:20260715T100000"),
+            event("unknown", "TZID=Somewhere Else:20260716T100000"),
+        ]
+        .concat();
+
+        let mut found = starts(&events, "2026-01-01 00:00", "2026-12-31 00:00");
+        found.sort();
+        let mut expected = vec![
+            local_of_utc("2026-01-15 09:00"),
+            local_of_utc("2026-07-15 08:00"),
+            local_of_utc("2026-07-15 14:00"),
+            "2026-07-16T10:00".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let lines = unfold("DESCRIPTION:Agenda: budget\r\n  and hiring\r\n\tplan\r\nSUMMARY:x\r\n");
+        assert_eq!(
+            lines,
+            ["DESCRIPTION:Agenda: budget and hiringplan", "SUMMARY:x"]
+        );
+    }
+
+    #[test]
+    fn parameters_and_quoted_values_are_parsed() {
+        let property = parse_property(
+            r#"attendee;CN="Doe, Jane: PM";ROLE=REQ-PARTICIPANT:mailto:jane@example.com"#,
+        )
+        .unwrap();
+        assert_eq!(property.name, "ATTENDEE");
+        assert_eq!(property.param("CN"), Some("Doe, Jane: PM"));
+        assert_eq!(property.param("ROLE"), Some("REQ-PARTICIPANT"));
+        assert_eq!(property.value, "mailto:jane@example.com");
+        assert_eq!(attendee_name(&property).as_deref(), Some("Doe, Jane: PM"));
+
+        let room =
+            parse_property("ATTENDEE;CUTYPE=ROOM;CN=Room 4:mailto:room4@example.com").unwrap();
+        assert_eq!(attendee_name(&room), None);
+        let bare = parse_property("ORGANIZER:mailto:ben@example.com").unwrap();
+        assert_eq!(attendee_name(&bare).as_deref(), Some("ben"));
+        assert_eq!(
+            unescape(r"Budget\, hiring\nand \\ more\;"),
+            "Budget, hiring\nand \\ more;"
+        );
+    }
+
+    #[test]
+    fn events_are_read_with_their_details() {
+        let raw = calendar(
+            "BEGIN:VEVENT\nUID:kickoff@example.com\nSUMMARY:Kickoff\n\
+             DTSTART;TZID=Europe/Paris:20260218T100000\nDTEND;TZID=Europe/Paris:20260218T113000\n\
+             LOCATION:Room 4\nDESCRIPTION:Goals\\nBudget\nATTENDEE;CN=Ana:mailto:ana@example.com\n\
+             BEGIN:VALARM\nDESCRIPTION:Reminder\nEND:VALARM\nEND:VEVENT\n",
+        );
+        let events = calendar_events(&raw, at("2026-02-01 00:00"), at("2026-03-01 00:00"));
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.event_id, "kickoff@example.com");
+        assert_eq!(event.title, "Kickoff");
+        assert_eq!(event.start[..16], local_of_utc("2026-02-18 09:00"));
+        assert_eq!(
+            event.end.as_deref().unwrap()[..16],
+            local_of_utc("2026-02-18 10:30")
+        );
+        assert_eq!(event.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.description, "Goals\nBudget");
+        assert_eq!(event.attendees, ["Ana"]);
+        assert!(!event.all_day && event.series_id.is_none());
+    }
+
+    #[test]
+    fn daily_rules_honour_count_and_until() {
+        let daily = |rule: &str| {
+            format!(
+                "BEGIN:VEVENT\nUID:daily\nSUMMARY:Sync\nDTSTART:20260202T090000\n\
+                 RRULE:{rule}\nEND:VEVENT\n"
+            )
+        };
+        assert_eq!(
+            starts(
+                &daily("FREQ=DAILY;COUNT=3"),
+                "2026-02-01 00:00",
+                "2026-03-01 00:00"
+            ),
+            ["2026-02-02T09:00", "2026-02-03T09:00", "2026-02-04T09:00"]
+        );
+        assert_eq!(
+            starts(
+                &daily("FREQ=DAILY;INTERVAL=2;UNTIL=20260208T090000"),
+                "2026-02-01 00:00",
+                "2026-03-01 00:00"
+            ),
+            [
+                "2026-02-02T09:00",
+                "2026-02-04T09:00",
+                "2026-02-06T09:00",
+                "2026-02-08T09:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_rules_follow_byday() {
+        assert_eq!(
+            starts(
+                &weekly("FREQ=WEEKLY;BYDAY=MO,TH"),
+                "2026-02-01 00:00",
+                "2026-02-13 00:00"
+            ),
+            [
+                "2026-02-02T09:00",
+                "2026-02-05T09:00",
+                "2026-02-09T09:00",
+                "2026-02-12T09:00"
+            ]
+        );
+        assert_eq!(
+            starts(
+                &weekly("FREQ=WEEKLY;INTERVAL=2"),
+                "2026-02-01 00:00",
+                "2026-03-01 00:00"
+            ),
+            ["2026-02-02T09:00", "2026-02-16T09:00"]
+        );
+    }
+
+    #[test]
+    fn monthly_rules_take_a_day_or_a_weekday() {
+        let monthly = |rule: &str| {
+            format!(
+                "BEGIN:VEVENT\nUID:review\nSUMMARY:Review\nDTSTART:20260115T140000\n\
+                 RRULE:{rule}\nEND:VEVENT\n"
+            )
+        };
+        assert_eq!(
+            starts(
+                &monthly("FREQ=MONTHLY;COUNT=3"),
+                "2026-01-01 00:00",
+                "2026-12-31 00:00"
+            ),
+            ["2026-01-15T14:00", "2026-02-15T14:00", "2026-03-15T14:00"]
+        );
+        // Second Tuesday and last Friday; January's second Tuesday is before
+        // the first start.
+        assert_eq!(
+            starts(
+                &monthly("FREQ=MONTHLY;BYDAY=2TU,-1FR"),
+                "2026-01-01 00:00",
+                "2026-02-28 00:00"
+            ),
+            ["2026-01-30T14:00", "2026-02-10T14:00", "2026-02-27T14:00"]
+        );
+    }
+
+    #[test]
+    fn exdates_and_overrides_change_single_occurrences() {
+        let events = format!(
+            "{}BEGIN:VEVENT\nUID:standup\nSUMMARY:Standup (moved)\n\
+             RECURRENCE-ID:20260204T090000\nDTSTART:20260204T113000\nEND:VEVENT\n\
+             BEGIN:VEVENT\nUID:standup\nRECURRENCE-ID:20260205T090000\n\
+             DTSTART:20260205T090000\nSTATUS:CANCELLED\nEND:VEVENT\n",
+            weekly("FREQ=DAILY;COUNT=5").replace("RRULE:", "EXDATE:20260203T090000\nRRULE:")
+        );
+        let found = calendar_events(
+            &calendar(&events),
+            at("2026-02-01 00:00"),
+            at("2026-03-01 00:00"),
+        );
+        let summary: Vec<(&str, &str)> = found
+            .iter()
+            .map(|e| (&e.start[..16], e.title.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("2026-02-02T09:00", "Standup"),
+                ("2026-02-04T11:30", "Standup (moved)"),
+                ("2026-02-06T09:00", "Standup"),
+            ]
+        );
+        // Occurrences keep the id of their original start.
+        assert_eq!(found[1].event_id, "standup/20260204T090000");
+        assert!(found.iter().all(|e| e.series_id == found[0].series_id));
+    }
+
+    #[test]
+    fn overrides_without_uid_stay_with_their_own_master() {
+        let events = "BEGIN:VEVENT\nSUMMARY:Standup\nDTSTART:20260202T090000\n\
+                      RRULE:FREQ=DAILY;COUNT=2\nEND:VEVENT\n\
+                      BEGIN:VEVENT\nSUMMARY:Retro\nDTSTART:20260202T090000\n\
+                      RRULE:FREQ=DAILY;COUNT=2\nEND:VEVENT\n\
+                      BEGIN:VEVENT\nSUMMARY:Standup\nRECURRENCE-ID:20260202T090000\n\
+                      DTSTART:20260202T090000\nSTATUS:CANCELLED\nEND:VEVENT\n";
+        let found = calendar_events(
+            &calendar(events),
+            at("2026-02-01 00:00"),
+            at("2026-03-01 00:00"),
+        );
+        let mut titles: Vec<(&str, &str)> = found
+            .iter()
+            .map(|e| (&e.start[..10], e.title.as_str()))
+            .collect();
+        titles.sort();
+        assert_eq!(
+            titles,
+            [
+                ("2026-02-02", "Retro"),
+                ("2026-02-03", "Retro"),
+                ("2026-02-03", "Standup")
+            ]
+        );
+    }
+
+    #[test]
+    fn old_series_still_reach_the_window() {
+        let events = "BEGIN:VEVENT\nUID:daily\nSUMMARY:Sync\nDTSTART:20050103T090000\n\
+                      EXDATE:20050104T090000,20050105T090000\nRRULE:FREQ=DAILY\nEND:VEVENT\n\
+                      BEGIN:VEVENT\nUID:weekly\nSUMMARY:Review\nDTSTART:20050103T140000\n\
+                      RRULE:FREQ=WEEKLY;BYDAY=MO\nEND:VEVENT\n";
+        assert_eq!(
+            starts(events, "2026-02-02 00:00", "2026-02-03 23:59"),
+            ["2026-02-02T09:00", "2026-02-02T14:00", "2026-02-03T09:00"]
+        );
+    }
+}
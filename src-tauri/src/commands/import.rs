@@ -0,0 +1,1040 @@
+//! Import of existing notes from other apps: plain Markdown folders, Obsidian
+//! vaults and Notion's Markdown export, either as a directory or a `.zip`.
+//! Each Markdown file becomes a note, with its title, date, participants and
+//! tags taken from frontmatter, Notion's property lines, the file name and
+//! `#hashtags`. Links between imported files are turned into wiki-links.
+//!
+//! Import runs as a dry run first, reporting what would be created and which
+//! files duplicate notes already in the library; nothing is written until it
+//! is run again with `apply`.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::catalog::CatalogState;
+use super::library::LibraryState;
+use super::links;
+use super::note_id::{FolderPath, NoteId};
+use super::notes::{self, NoteMeta};
+use super::people;
+use super::search::SearchState;
+use super::tags;
+use super::watcher::WatcherState;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKind {
+    /// A folder of `.md` files, e.g. exported from another notes app.
+    Markdown,
+    Obsidian,
+    Notion,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Would be created; the plan of a dry run.
+    New,
+    Imported,
+    /// Matches a note already in the library, or earlier in the same import.
+    Duplicate,
+    Skipped,
+}
+
+/// What happens, or would happen, to one file of the export.
+#[derive(Serialize, Debug)]
+pub struct ImportItem {
+    /// Path of the file within the export.
+    pub source: String,
+    pub status: ImportStatus,
+    /// The note created, or the one it duplicates.
+    pub note_id: Option<NoteId>,
+    pub title: String,
+    pub folder: Option<FolderPath>,
+    /// Meeting day, as `YYYY-MM-DD`.
+    pub date: String,
+    pub participants: Vec<String>,
+    pub tags: Vec<String>,
+    /// Why a file was skipped.
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportReport {
+    pub kind: ImportKind,
+    pub items: Vec<ImportItem>,
+}
+
+/// A file read from the export directory or zip.
+struct SourceFile {
+    /// `/`-separated path within the export.
+    path: String,
+    content: Result<String, String>,
+    modified: Option<DateTime<Utc>>,
+}
+
+/// A Markdown file turned into note content and metadata.
+struct Draft {
+    source: String,
+    folder: Option<FolderPath>,
+    content: String,
+    meta: NoteMeta,
+}
+
+/// Frontmatter keys and Notion properties holding the meeting date, tags and
+/// participants.
+const DATE_KEYS: &[&str] = &[
+    "date",
+    "created",
+    "created_at",
+    "created time",
+    "date created",
+    "meeting date",
+];
+const TAG_KEYS: &[&str] = &["tags", "tag", "labels"];
+const PARTICIPANT_KEYS: &[&str] = &["participants", "attendees", "people"];
+const UPDATED_KEYS: &[&str] = &["updated", "updated_at", "modified", "last edited time"];
+/// Keys `NoteMeta` reads itself; left-over fields by these names are renamed
+/// so they cannot clash with the note's own metadata.
+const RESERVED_KEYS: &[&str] = &[
+    "title",
+    "participants",
+    "tags",
+    "created_at",
+    "updated_at",
+    "series",
+];
+
+/// Bodies shorter than this are not compared when looking for duplicates.
+const MIN_MATCHED_BODY_CHARS: usize = 80;
+
+// ── Reading the export ───────────────────────────────────────────────────────
+
+/// Markdown files in directory `root`, skipping hidden entries. The second value is
+/// whether the directory has an Obsidian `.obsidian` config folder.
+fn read_dir_export(root: &Path) -> (Vec<SourceFile>, bool) {
+    fn walk(dir: &Path, prefix: &str, files: &mut Vec<SourceFile>, obsidian: &mut bool) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if name.starts_with('.') {
+                *obsidian |= prefix.is_empty() && is_dir && name == ".obsidian";
+                continue;
+            }
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            if is_dir {
+                walk(&entry.path(), &path, files, obsidian);
+            } else if is_markdown(&path) {
+                files.push(SourceFile {
+                    content: fs::read_to_string(entry.path()).map_err(|e| e.to_string()),
+                    modified: entry
+                        .metadata()
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .map(DateTime::<Utc>::from),
+                    path,
+                });
+            }
+        }
+    }
+    let mut files = Vec::new();
+    let mut obsidian = false;
+    walk(root, "", &mut files, &mut obsidian);
+    (files, obsidian)
+}
+
+fn zip_time(time: zip::DateTime) -> Option<DateTime<Utc>> {
+    let naive =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Like `read_dir_export`, for a `.zip` export.
+fn read_zip_export(path: &Path) -> Result<(Vec<SourceFile>, bool), String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut files = Vec::new();
+    let mut obsidian = false;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let segments: Vec<String> = name
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        obsidian |= segments.iter().any(|s| s == ".obsidian");
+        if entry.is_dir()
+            || !is_markdown(&name.to_string_lossy())
+            || segments
+                .iter()
+                .any(|s| s.starts_with('.') || s == "__MACOSX")
+        {
+            continue;
+        }
+        let mut content = String::new();
+        files.push(SourceFile {
+            path: segments.join("/"),
+            content: entry
+                .read_to_string(&mut content)
+                .map(|_| content)
+                .map_err(|e| e.to_string()),
+            modified: entry.last_modified().and_then(zip_time),
+        });
+    }
+    Ok((files, obsidian))
+}
+
+fn is_markdown(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".markdown")
+}
+
+/// `name` without the 32-digit hex id Notion appends to page and folder
+/// names, e.g. `Kickoff 0123456789abcdef0123456789abcdef`.
+fn strip_notion_id(name: &str) -> &str {
+    match name.rsplit_once(' ') {
+        Some((head, id)) if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) => head,
+        _ => name,
+    }
+}
+
+fn detect_kind(files: &[SourceFile], obsidian: bool) -> ImportKind {
+    if obsidian {
+        return ImportKind::Obsidian;
+    }
+    let stems: Vec<&str> = files.iter().map(|f| file_stem(&f.path)).collect();
+    let notion = stems.iter().filter(|s| strip_notion_id(s) != **s).count();
+    if !stems.is_empty() && notion * 2 >= stems.len() {
+        ImportKind::Notion
+    } else {
+        ImportKind::Markdown
+    }
+}
+
+fn file_stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+// ── Mapping files to notes ───────────────────────────────────────────────────
+
+/// A date or date and time as other apps write them, taken as local time.
+fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let local = |naive: NaiveDateTime| {
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+        "%B %d, %Y %I:%M %p",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return local(naive);
+        }
+    }
+    for format in ["%Y-%m-%d", "%B %d, %Y", "%d %B %Y", "%Y/%m/%d"] {
+        if let Ok(day) = NaiveDate::parse_from_str(text, format) {
+            return local(day.and_time(chrono::NaiveTime::MIN));
+        }
+    }
+    None
+}
+
+/// The first `YYYY-MM-DD`, `YYYY_MM_DD`, `YYYY.MM.DD` or `YYYYMMDD` date in
+/// a file name, and the name with it and the separators around it removed.
+fn date_in_name(name: &str) -> Option<(NaiveDate, String)> {
+    let bytes = name.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        range.end <= bytes.len() && bytes[range].iter().all(u8::is_ascii_digit)
+    };
+    for start in 0..bytes.len() {
+        if (start > 0 && bytes[start - 1].is_ascii_digit()) || !digits(start..start + 4) {
+            continue;
+        }
+        let sep = bytes.get(start + 4).copied();
+        let (len, text) = match sep {
+            Some(b'-' | b'_' | b'.')
+                if digits(start + 5..start + 7)
+                    && bytes.get(start + 7) == sep.as_ref()
+                    && digits(start + 8..start + 10) =>
+            {
+                let text = &name[start..start + 10];
+                (10, format!("{}-{}-{}", &text[..4], &text[5..7], &text[8..]))
+            }
+            _ if digits(start..start + 8) => {
+                let text = &name[start..start + 8];
+                (8, format!("{}-{}-{}", &text[..4], &text[4..6], &text[6..]))
+            }
+            _ => continue,
+        };
+        if bytes.get(start + len).is_some_and(u8::is_ascii_digit) {
+            continue;
+        }
+        let Ok(day) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") else {
+            continue;
+        };
+        let trim = |s: &str| {
+            s.trim_matches(|c: char| c.is_whitespace() || "-_.".contains(c))
+                .to_string()
+        };
+        let rest = [trim(&name[..start]), trim(&name[start + len..])]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        return Some((day, rest));
+    }
+    None
+}
+
+/// Names from a frontmatter list or a comma-separated string, without
+/// Obsidian `[[...]]` brackets, `#` or `@`.
+fn names(value: &serde_json::Value) -> Vec<String> {
+    let raw: Vec<String> = match value {
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        serde_json::Value::String(text) => text.split(',').map(str::to_string).collect(),
+        _ => vec![],
+    };
+    raw.iter()
+        .map(|name| {
+            let name = name.trim();
+            let name = name
+                .strip_prefix("[[")
+                .and_then(|n| n.strip_suffix("]]"))
+                .unwrap_or(name);
+            let name = name.split('|').next().unwrap_or(name);
+            name.trim_start_matches(['#', '@']).trim().to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Notion's `Key: Value` property lines after the page title, removed from
+/// `body`. Only a block made up entirely of such lines counts, and a single
+/// line only if it is a date, tag, participant or update property, so that a
+/// first paragraph like `Note: we agreed to ship Friday` stays.
+fn take_notion_properties(body: &mut String) -> Vec<(String, String)> {
+    let block_len = body.find("\n\n").unwrap_or(body.len());
+    let block = &body[..block_len];
+    let mut properties = Vec::new();
+    for line in block.lines() {
+        let Some((key, value)) = line.split_once(": ") else {
+            return vec![];
+        };
+        let key = key.trim();
+        if key.is_empty() || key.len() > 40 || key.starts_with(['#', '-', '*', '>']) {
+            return vec![];
+        }
+        properties.push((key.to_string(), value.trim().to_string()));
+    }
+    let known = |key: &str| {
+        let key = key.to_lowercase();
+        [DATE_KEYS, TAG_KEYS, PARTICIPANT_KEYS, UPDATED_KEYS]
+            .iter()
+            .any(|keys| keys.contains(&key.as_str()))
+    };
+    match properties.as_slice() {
+        [] => return vec![],
+        [(key, _)] if !known(key) => return vec![],
+        _ => {}
+    }
+    body.replace_range(..block_len, "");
+    properties
+}
+
+/// The first of `keys` in `fields`, ignoring case, removing all of them.
+fn take(
+    fields: &mut BTreeMap<String, serde_json::Value>,
+    keys: &[&str],
+) -> Option<serde_json::Value> {
+    let matching: Vec<String> = fields
+        .keys()
+        .filter(|k| keys.contains(&k.to_lowercase().as_str()))
+        .cloned()
+        .collect();
+    let mut found = None;
+    for key in keys {
+        for k in &matching {
+            if k.to_lowercase() == *key {
+                if let Some(value) = fields.remove(k) {
+                    found.get_or_insert(value);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// The folder a file in export directory `dir` goes into, below `target`.
+/// Names that are not valid folder names are cleaned up, or left out.
+fn folder_for(dir: &str, kind: ImportKind, target: Option<&FolderPath>) -> Option<FolderPath> {
+    let mut folder = target.cloned();
+    for segment in dir.split('/').filter(|s| !s.is_empty()) {
+        let segment = match kind {
+            ImportKind::Notion => strip_notion_id(segment),
+            _ => segment,
+        };
+        let cleaned: String = segment
+            .chars()
+            .map(|c| match c {
+                ':' | '*' | '?' | '"' | '<' | '>' | '|' | '\\' => '-',
+                c if c.is_control() => '-',
+                c => c,
+            })
+            .collect();
+        let cleaned = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+        if let Ok(path) = FolderPath::in_folder(folder.as_ref(), cleaned) {
+            folder = Some(path);
+        }
+    }
+    folder
+}
+
+fn draft(file: &SourceFile, content: &str, kind: ImportKind, target: Option<&FolderPath>) -> Draft {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let (yaml, body) = match notes::split_frontmatter(&content) {
+        Some((yaml, body)) => (Some(yaml), body),
+        None => (None, content.as_str()),
+    };
+    let mut fields: BTreeMap<String, serde_json::Value> = yaml
+        .and_then(|yaml| serde_yaml::from_str(yaml).ok())
+        .unwrap_or_default();
+    let mut body = body.trim_start().to_string();
+    let title_field = take(&mut fields, &["title"])
+        .and_then(|v| v.as_str().map(str::to_string))
+        .filter(|t| !t.trim().is_empty());
+
+    // A leading `# Heading` is the title, unless the frontmatter has one.
+    let mut heading = None;
+    if let Some(line) = body.lines().next().filter(|l| l.starts_with("# ")) {
+        if title_field.is_none() {
+            heading = Some(line[2..].trim().to_string());
+            body = body[line.len()..].trim_start().to_string();
+        }
+    }
+    if kind == ImportKind::Notion {
+        for (key, value) in take_notion_properties(&mut body) {
+            fields
+                .entry(key.to_lowercase())
+                .or_insert_with(|| value.into());
+        }
+        body = body.trim_start().to_string();
+    }
+
+    let date_field = take(&mut fields, DATE_KEYS).and_then(|v| v.as_str().and_then(parse_date));
+    let tags = take(&mut fields, TAG_KEYS)
+        .map(|v| names(&v))
+        .unwrap_or_default();
+    let participants = take(&mut fields, PARTICIPANT_KEYS)
+        .map(|v| names(&v))
+        .unwrap_or_default();
+    let updated = take(&mut fields, UPDATED_KEYS).and_then(|v| v.as_str().and_then(parse_date));
+
+    let stem = file_stem(&file.path);
+    let stem = match kind {
+        ImportKind::Notion => strip_notion_id(stem),
+        _ => stem,
+    };
+    let named = date_in_name(stem);
+    let title = title_field
+        .or(heading.filter(|h| !h.is_empty()))
+        .or_else(|| {
+            named
+                .as_ref()
+                .map(|(_, rest)| rest.clone())
+                .filter(|r| !r.is_empty())
+        })
+        .unwrap_or_else(|| stem.to_string());
+    let created = date_field
+        .or_else(|| {
+            named.and_then(|(day, _)| {
+                Local
+                    .from_local_datetime(&day.and_time(chrono::NaiveTime::MIN))
+                    .earliest()
+                    .map(|t| t.with_timezone(&Utc))
+            })
+        })
+        .or(file.modified)
+        .unwrap_or_else(Utc::now);
+    let updated = updated.or(file.modified).unwrap_or(created).max(created);
+    for key in RESERVED_KEYS {
+        if let Some(value) = fields.remove(*key) {
+            fields.insert(format!("imported_{key}"), value);
+        }
+    }
+
+    let dir = file.path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut body = body.trim_end().to_string();
+    body.push('\n');
+    Draft {
+        source: file.path.clone(),
+        folder: folder_for(dir, kind, target),
+        content: body,
+        meta: NoteMeta {
+            title: title.trim().to_string(),
+            participants,
+            tags,
+            created_at: created.to_rfc3339(),
+            updated_at: updated.to_rfc3339(),
+            series: None,
+            extra: fields,
+        },
+    }
+}
+
+// ── Links between imported files ─────────────────────────────────────────────
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `target` relative to directory `dir`, with `.` and `..` resolved.
+fn join_relative(dir: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// A wiki-link to `title`, showing `label` if it differs.
+fn wiki_link(title: &str, label: &str) -> String {
+    if label.trim().is_empty() || label.trim() == title {
+        format!("[[{title}]]")
+    } else {
+        format!("[[{title}|{}]]", label.replace(['[', ']', '|'], ""))
+    }
+}
+
+fn linkable(title: &str) -> bool {
+    !title.is_empty() && !title.contains(['|', '#', '[', ']', '\n'])
+}
+
+/// `[label](Other%20page.md)` links between imported files turned into
+/// wiki-links to the note titles, which `title_of` gives by source path.
+fn convert_markdown_links(
+    content: &str,
+    dir: &str,
+    title_of: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(mid) = rest.find("](") {
+        let Some(close) = rest[mid + 2..].find(')') else {
+            break;
+        };
+        let end = mid + 2 + close + 1;
+        let open = rest[..mid].rfind('[');
+        let target = rest[mid + 2..end - 1].trim();
+        let target = target
+            .strip_prefix('<')
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or(target);
+        let target = target.split('#').next().unwrap_or(target);
+        let title = match open {
+            Some(open)
+                if !rest[..open].ends_with('!')
+                    && !target.contains("://")
+                    && is_markdown(target) =>
+            {
+                title_of(&join_relative(dir, &percent_decode(target)))
+            }
+            _ => None,
+        };
+        match (open, title) {
+            (Some(open), Some(title)) => {
+                out.push_str(&rest[..open]);
+                out.push_str(&wiki_link(&title, &rest[open + 1..mid]));
+            }
+            _ => out.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Obsidian links name files, not titles: point `[[file name]]` at the title
+/// the file was imported with when the two differ.
+fn convert_wiki_links(content: &str, title_of: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = content.to_string();
+    for span in links::link_spans(content).iter().rev() {
+        let name = span.target.rsplit('/').next().unwrap_or(span.target);
+        let name = name.strip_suffix(".md").unwrap_or(name);
+        if let Some(title) = title_of(&name.to_lowercase()).filter(|t| t != span.target) {
+            out.replace_range(span.range.clone(), &title);
+        }
+    }
+    out
+}
+
+fn link_drafts(drafts: &mut [Draft], kind: ImportKind) {
+    let by_path: HashMap<String, String> = drafts
+        .iter()
+        .filter(|d| linkable(&d.meta.title))
+        .map(|d| (d.source.clone(), d.meta.title.clone()))
+        .collect();
+    let mut by_stem: HashMap<String, Option<String>> = HashMap::new();
+    for draft in drafts.iter().filter(|d| linkable(&d.meta.title)) {
+        by_stem
+            .entry(file_stem(&draft.source).to_lowercase())
+            .and_modify(|title| *title = None)
+            .or_insert_with(|| Some(draft.meta.title.clone()));
+    }
+    for draft in drafts.iter_mut() {
+        let dir = draft
+            .source
+            .rsplit_once('/')
+            .map_or("", |(dir, _)| dir)
+            .to_string();
+        let mut content =
+            convert_markdown_links(&draft.content, &dir, &|path| by_path.get(path).cloned());
+        if kind == ImportKind::Obsidian {
+            content = convert_wiki_links(&content, &|stem| by_stem.get(stem).cloned().flatten());
+        }
+        draft.content = content;
+    }
+}
+
+fn item(draft: &Draft, status: ImportStatus, note_id: Option<NoteId>) -> ImportItem {
+    ImportItem {
+        source: draft.source.clone(),
+        status,
+        note_id,
+        title: draft.meta.title.clone(),
+        folder: draft.folder.clone(),
+        date: notes::meeting_day(&draft.meta).to_string(),
+        participants: draft.meta.participants.clone(),
+        tags: draft.meta.tags.clone(),
+        reason: None,
+    }
+}
+
+fn skipped(source: &str, reason: String) -> ImportItem {
+    ImportItem {
+        source: source.to_string(),
+        status: ImportStatus::Skipped,
+        note_id: None,
+        title: String::new(),
+        folder: None,
+        date: String::new(),
+        participants: vec![],
+        tags: vec![],
+        reason: Some(reason),
+    }
+}
+
+/// Key a note is recognised by when imported twice: its title and day.
+fn dedupe_key(meta: &NoteMeta) -> (String, NaiveDate) {
+    (meta.title.trim().to_lowercase(), notes::meeting_day(meta))
+}
+
+/// The body a note is also recognised by, unless it is too short to tell
+/// notes apart, e.g. empty once the title heading is taken out, or a bare
+/// template.
+fn body_key(content: &str) -> Option<String> {
+    let body = content.trim();
+    (body.chars().count() >= MIN_MATCHED_BODY_CHARS).then(|| body.to_string())
+}
+
+/// The notes an import is checked against for duplicates: those in the
+/// library and those earlier in the same import, which have no id yet in a
+/// dry run.
+#[derive(Default)]
+struct Seen {
+    by_key: HashMap<(String, NaiveDate), Option<NoteId>>,
+    by_body: HashMap<String, Option<NoteId>>,
+}
+
+impl Seen {
+    fn insert(&mut self, content: &str, meta: &NoteMeta, id: Option<NoteId>) {
+        if let Some(body) = body_key(content) {
+            self.by_body.insert(body, id.clone());
+        }
+        self.by_key.insert(dedupe_key(meta), id);
+    }
+
+    /// The note one with `content` and `meta` duplicates, if any.
+    fn find(&self, content: &str, meta: &NoteMeta) -> Option<&Option<NoteId>> {
+        body_key(content)
+            .and_then(|body| self.by_body.get(&body))
+            .or_else(|| self.by_key.get(&dedupe_key(meta)))
+    }
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Import the Markdown files of the export at `path`, a directory or `.zip`,
+/// into `folder`, keeping the export's subfolders. `kind` is detected when
+/// not given. A file duplicates an existing note if it has the same body, not
+/// counting short ones, or the same title and meeting day. Without `apply` nothing is written and the
+/// report is the plan; with it, the notes marked new are created.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn import_notes(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    path: String,
+    kind: Option<ImportKind>,
+    folder: Option<FolderPath>,
+    apply: bool,
+) -> Result<ImportReport, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let source = Path::new(&path);
+    let (mut files, obsidian) = if source.is_dir() {
+        read_dir_export(source)
+    } else if path.to_lowercase().ends_with(".zip") {
+        read_zip_export(source)?
+    } else {
+        return Err(format!("Not a folder or .zip file: {path}"));
+    };
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let kind = kind.unwrap_or_else(|| detect_kind(&files, obsidian));
+
+    let mut items = Vec::new();
+    let mut drafts = Vec::new();
+    for file in &files {
+        match &file.content {
+            Ok(content) if content.trim().is_empty() => {
+                items.push(skipped(&file.path, "Empty file".into()));
+            }
+            Ok(content) => drafts.push(draft(file, content, kind, folder.as_ref())),
+            Err(e) => items.push(skipped(&file.path, format!("Unreadable: {e}"))),
+        }
+    }
+    link_drafts(&mut drafts, kind);
+
+    let directory = people::load(&dir).unwrap_or_else(|e| {
+        eprintln!("Importing participants as written: {e}");
+        Vec::new()
+    });
+    let mut seen = Seen::default();
+    for id in notes::note_ids(&dir) {
+        if let Ok(note) = notes::load_note(&dir, &id) {
+            seen.insert(&note.content, &note.meta, Some(id));
+        }
+    }
+
+    for mut draft in drafts {
+        draft.meta = tags::merge_into_meta(draft.meta, None, &draft.content, &directory);
+        if let Some(existing) = seen.find(&draft.content, &draft.meta) {
+            items.push(item(&draft, ImportStatus::Duplicate, existing.clone()));
+            continue;
+        }
+        let note_id = if apply {
+            let note = notes::add_note(
+                &lib,
+                &search,
+                &catalog,
+                &watcher,
+                draft.folder.as_ref(),
+                draft.content.clone(),
+                draft.meta.clone(),
+            )?;
+            Some(note.id)
+        } else {
+            None
+        };
+        let status = match apply {
+            true => ImportStatus::Imported,
+            false => ImportStatus::New,
+        };
+        seen.insert(&draft.content, &draft.meta, note_id.clone());
+        items.push(item(&draft, status, note_id));
+    }
+    items.sort_by(|a, b| a.source.cmp(&b.source));
+    Ok(ImportReport { kind, items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(text: &str) -> DateTime<Utc> {
+        let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn meta(title: &str, day: &str) -> NoteMeta {
+        NoteMeta {
+            title: title.to_string(),
+            participants: vec![],
+            tags: vec![],
+            created_at: local(&format!("{day} 09:00")).to_rfc3339(),
+            updated_at: String::new(),
+            series: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn reserved_fields_are_renamed() {
+        let content = "---\ntitle: Weekly\nseries: weekly\nproject: Atlas\n---\nNotes\n";
+        let file = SourceFile {
+            path: "weekly.md".to_string(),
+            content: Ok(content.to_string()),
+            modified: None,
+        };
+        let draft = draft(&file, content, ImportKind::Markdown, None);
+        assert_eq!(draft.meta.title, "Weekly");
+        assert_eq!(draft.meta.extra["imported_series"], "weekly");
+        assert_eq!(draft.meta.extra["project"], "Atlas");
+
+        let json = serde_json::to_string(&draft.meta).unwrap();
+        let meta: NoteMeta = serde_json::from_str(&json).unwrap();
+        assert!(meta.series.is_none());
+        assert_eq!(meta.extra["imported_series"], "weekly");
+    }
+
+    #[test]
+    fn notion_ids_are_stripped() {
+        assert_eq!(
+            strip_notion_id("Kickoff 0123456789abcdef0123456789abcdef"),
+            "Kickoff"
+        );
+        assert_eq!(strip_notion_id("Kickoff"), "Kickoff");
+        assert_eq!(strip_notion_id("Release 0123abcd"), "Release 0123abcd");
+        assert_eq!(
+            strip_notion_id("Kickoff 0123456789abcdef0123456789abcdeg"),
+            "Kickoff 0123456789abcdef0123456789abcdeg"
+        );
+    }
+
+    #[test]
+    fn dates_are_found_in_file_names() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            date_in_name("2026-02-18 Kickoff"),
+            Some((day(2026, 2, 18), "Kickoff".to_string()))
+        );
+        assert_eq!(
+            date_in_name("Standup_2026_02_18"),
+            Some((day(2026, 2, 18), "Standup".to_string()))
+        );
+        assert_eq!(
+            date_in_name("20260218-retro"),
+            Some((day(2026, 2, 18), "retro".to_string()))
+        );
+        assert_eq!(
+            date_in_name("Meeting 2026.02.18 notes"),
+            Some((day(2026, 2, 18), "Meeting notes".to_string()))
+        );
+        assert_eq!(
+            date_in_name("2026-02-18"),
+            Some((day(2026, 2, 18), String::new()))
+        );
+        assert_eq!(date_in_name("2026-13-01 Kickoff"), None);
+        assert_eq!(date_in_name("ticket 1202602181"), None);
+        assert_eq!(date_in_name("Q4 planning"), None);
+    }
+
+    #[test]
+    fn dates_are_parsed_in_common_formats() {
+        let utc = |text| {
+            DateTime::parse_from_rfc3339(text)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(
+            parse_date("2026-02-18T10:30:00+01:00"),
+            Some(utc("2026-02-18T09:30:00Z"))
+        );
+        assert_eq!(parse_date(" 2026-02-18 "), Some(local("2026-02-18 00:00")));
+        assert_eq!(
+            parse_date("2026-02-18 14:05"),
+            Some(local("2026-02-18 14:05"))
+        );
+        assert_eq!(
+            parse_date("February 18, 2026 3:30 PM"),
+            Some(local("2026-02-18 15:30"))
+        );
+        assert_eq!(
+            parse_date("18 February 2026"),
+            Some(local("2026-02-18 00:00"))
+        );
+        assert_eq!(parse_date("2026/02/18"), Some(local("2026-02-18 00:00")));
+        assert_eq!(parse_date("next Tuesday"), None);
+    }
+
+    #[test]
+    fn markdown_links_become_wiki_links() {
+        let titles = HashMap::from([
+            ("Kickoff 0123.md".to_string(), "Kickoff".to_string()),
+            (
+                "Projects/Roadmap.md".to_string(),
+                "Roadmap 2026".to_string(),
+            ),
+        ]);
+        let title_of = |path: &str| titles.get(path).cloned();
+
+        assert_eq!(
+            convert_markdown_links("See [the kickoff](Kickoff%200123.md).", "", &title_of),
+            "See [[Kickoff|the kickoff]]."
+        );
+        assert_eq!(
+            convert_markdown_links("[Kickoff](<Kickoff 0123.md#Goals>)", "", &title_of),
+            "[[Kickoff]]"
+        );
+        assert_eq!(
+            convert_markdown_links("[plan](../Projects/Roadmap.md)", "Meetings", &title_of),
+            "[[Roadmap 2026|plan]]"
+        );
+        for unchanged in [
+            "![diagram](Kickoff%200123.md)",
+            "[site](https://example.com/Kickoff%200123.md)",
+            "[elsewhere](Missing.md)",
+            "[pdf](Kickoff.pdf)",
+        ] {
+            assert_eq!(convert_markdown_links(unchanged, "", &title_of), unchanged);
+        }
+    }
+
+    #[test]
+    fn wiki_links_point_at_imported_titles() {
+        let titles = HashMap::from([("2026-02-18 kickoff".to_string(), "Kickoff".to_string())]);
+        let title_of = |stem: &str| titles.get(stem).cloned();
+        assert_eq!(
+            convert_wiki_links(
+                "From [[2026-02-18 Kickoff]] and [[2026-02-18 Kickoff|the kickoff]].",
+                &title_of
+            ),
+            "From [[Kickoff]] and [[Kickoff|the kickoff]]."
+        );
+        assert_eq!(
+            convert_wiki_links("[[Kickoff]] and [[Unknown]]", &title_of),
+            "[[Kickoff]] and [[Unknown]]"
+        );
+    }
+
+    #[test]
+    fn notion_property_lines_are_taken_from_the_body() {
+        let mut body = "Created: February 18, 2026 3:30 PM\nTags: planning\n\nAgenda".to_string();
+        assert_eq!(
+            take_notion_properties(&mut body),
+            [
+                (
+                    "Created".to_string(),
+                    "February 18, 2026 3:30 PM".to_string()
+                ),
+                ("Tags".to_string(), "planning".to_string()),
+            ]
+        );
+        assert_eq!(body.trim(), "Agenda");
+
+        let mut body = "Attendees: Ana, Ben\n\nAgenda".to_string();
+        assert_eq!(take_notion_properties(&mut body).len(), 1);
+
+        for text in [
+            "Note: we agreed to ship Friday\n\nMore",
+            "Status: done\nand a sentence\n\nMore",
+        ] {
+            let mut body = text.to_string();
+            assert!(take_notion_properties(&mut body).is_empty());
+            assert_eq!(body, text);
+        }
+    }
+
+    #[test]
+    fn duplicates_are_found_by_body_or_title_and_day() {
+        let agenda = "Agenda\n\n- Budget for the new office, and who signs it off\n- Hiring plan for the second half";
+        let existing = NoteId::parse("2026-02-18-kickoff").unwrap();
+        let mut seen = Seen::default();
+        seen.insert(
+            agenda,
+            &meta("Kickoff", "2026-02-18"),
+            Some(existing.clone()),
+        );
+        seen.insert("", &meta("Untitled Meeting", "2026-02-18"), None);
+
+        // Same body under another title, or same title and day.
+        assert_eq!(
+            seen.find(agenda, &meta("Office", "2026-03-01")),
+            Some(&Some(existing.clone()))
+        );
+        assert_eq!(
+            seen.find("Other notes", &meta("kickoff ", "2026-02-18")),
+            Some(&Some(existing))
+        );
+        assert_eq!(
+            seen.find("Other notes", &meta("Kickoff", "2026-02-19")),
+            None
+        );
+
+        // Empty and short bodies only match by title and day.
+        assert_eq!(seen.find("", &meta("Retro", "2026-02-18")), None);
+        assert_eq!(
+            seen.find("## Agenda\n\n## Notes", &meta("Retro", "2026-02-18")),
+            None
+        );
+
+        // A file planned earlier in a dry run is a duplicate with no id yet.
+        seen.insert("", &meta("Retro", "2026-02-18"), None);
+        assert_eq!(seen.find("", &meta("Retro", "2026-02-18")), Some(&None));
+    }
+}
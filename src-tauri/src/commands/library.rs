@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use super::atomic;
+use super::note_id::NoteId;
+use super::notes::{self, NoteStorage};
+use super::trash;
+use super::watcher::WatcherState;
+
+const DEFAULT_LIBRARY_ID: &str = "default";
+
+/// A notes library: a directory holding `.md` notes and their sidecars.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Library {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub settings: LibrarySettings,
+}
+
+/// Per-library preferences.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LibrarySettings {
+    /// Days a deleted note stays in the trash; `None` keeps it until purged.
+    pub trash_retention_days: Option<u32>,
+    /// Days note versions are kept; `None` keeps them forever.
+    pub history_retention_days: Option<u32>,
+    /// Layout new writes use. Change it with `notes::migrate_storage`, which
+    /// also converts the existing notes.
+    pub storage: NoteStorage,
+    /// Minutes without use after which unlocked encrypted notes lock again;
+    /// `None` keeps them unlocked until `lock_notes`. Applies from the next
+    /// unlock.
+    pub lock_after_minutes: Option<u32>,
+}
+
+impl Default for LibrarySettings {
+    fn default() -> Self {
+        LibrarySettings {
+            trash_retention_days: Some(30),
+            history_retention_days: Some(90),
+            storage: NoteStorage::default(),
+            lock_after_minutes: Some(15),
+        }
+    }
+}
+
+impl Library {
+    pub fn root(&self) -> PathBuf {
+        PathBuf::from(&self.path)
+    }
+
+    /// Directory holding the notes themselves, created if missing.
+    pub fn notes_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.root();
+        fs::create_dir_all(&dir).map_err(|e| format!("Cannot open library {}: {e}", self.path))?;
+        Ok(dir)
+    }
+
+    /// Where recordings go; those made for a note get a directory of their own
+    /// so they travel with it when it is trashed or renamed.
+    pub fn recordings_dir(&self, note_id: Option<&NoteId>) -> PathBuf {
+        let dir = self.root().join("recordings");
+        match note_id {
+            Some(id) => dir.join(id),
+            None => dir,
+        }
+    }
+
+    /// Per-library scratch space for derived data (search index etc.). Lives
+    /// outside the library so it is never picked up by folder sync tools.
+    pub fn cache_dir(&self) -> Result<PathBuf, String> {
+        // Tests keep it inside their temporary library, away from the user's.
+        if cfg!(test) {
+            return Ok(self.root().join(".cache"));
+        }
+        let base = dirs::cache_dir().ok_or("Cannot find cache directory")?;
+        Ok(base.join("Brief").join(&self.id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LibraryConfig {
+    pub active: String,
+    pub libraries: Vec<Library>,
+    /// Overrides where Whisper models are stored; shared by all libraries.
+    #[serde(default)]
+    pub models_dir: Option<String>,
+}
+
+// ── Paths ────────────────────────────────────────────────────────────────────
+
+fn config_path() -> Result<PathBuf, String> {
+    let base = dirs::config_dir().ok_or("Cannot find config directory")?;
+    Ok(base.join("Brief").join("libraries.json"))
+}
+
+fn default_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join("Brief"))
+}
+
+fn default_config() -> LibraryConfig {
+    let libraries = default_root()
+        .map(|root| {
+            vec![Library {
+                id: DEFAULT_LIBRARY_ID.to_string(),
+                name: "Brief".to_string(),
+                path: root.to_string_lossy().into_owned(),
+                settings: LibrarySettings::default(),
+            }]
+        })
+        .unwrap_or_default();
+    LibraryConfig {
+        active: DEFAULT_LIBRARY_ID.to_string(),
+        libraries,
+        models_dir: None,
+    }
+}
+
+fn load_config() -> LibraryConfig {
+    let Ok(path) = config_path() else {
+        return default_config();
+    };
+    match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+            eprintln!("Ignoring unreadable library config {}: {e}", path.display());
+            default_config()
+        }),
+        Err(_) => default_config(),
+    }
+}
+
+fn save_config(config: &LibraryConfig) -> Result<(), String> {
+    let path = config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    atomic::write_file(&path, json.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Turn a display name into a library id that is unique within `existing`.
+fn unique_id(name: &str, existing: &[Library]) -> String {
+    let base = notes::slugify(name);
+    let base = if base.is_empty() {
+        "library".to_string()
+    } else {
+        base
+    };
+
+    let mut id = base.clone();
+    let mut n = 2;
+    while existing.iter().any(|lib| lib.id == id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    id
+}
+
+// ── Library state ────────────────────────────────────────────────────────────
+
+/// The single place commands resolve library, recording and model paths from.
+pub struct LibraryState(Mutex<LibraryConfig>);
+
+impl Default for LibraryState {
+    fn default() -> Self {
+        LibraryState(Mutex::new(load_config()))
+    }
+}
+
+impl LibraryState {
+    /// The library all note commands currently operate on.
+    pub fn active(&self) -> Result<Library, String> {
+        let config = self.0.lock().map_err(|e| e.to_string())?;
+        config
+            .libraries
+            .iter()
+            .find(|lib| lib.id == config.active)
+            .cloned()
+            .ok_or_else(|| "No notes library configured".to_string())
+    }
+
+    /// Shorthand for `active()?.notes_dir()`.
+    pub fn notes_dir(&self) -> Result<PathBuf, String> {
+        self.active()?.notes_dir()
+    }
+
+    pub fn models_dir(&self) -> Result<PathBuf, String> {
+        let config = self.0.lock().map_err(|e| e.to_string())?;
+        match &config.models_dir {
+            Some(dir) => Ok(PathBuf::from(dir)),
+            None => default_root()
+                .map(|root| root.join("models"))
+                .ok_or_else(|| "Cannot find home directory".to_string()),
+        }
+    }
+
+    /// Change the settings of library `id` and return the updated library.
+    pub fn update_settings(
+        &self,
+        id: &str,
+        f: impl FnOnce(&mut LibrarySettings),
+    ) -> Result<Library, String> {
+        self.update(|config| {
+            let lib = config
+                .libraries
+                .iter_mut()
+                .find(|lib| lib.id == id)
+                .ok_or_else(|| format!("Unknown library: {id}"))?;
+            f(&mut lib.settings);
+            Ok(lib.clone())
+        })
+    }
+
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut LibraryConfig) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut config = self.0.lock().map_err(|e| e.to_string())?;
+        let mut updated = config.clone();
+        let result = f(&mut updated)?;
+        save_config(&updated)?;
+        *config = updated;
+        Ok(result)
+    }
+}
+
+fn ensure_absolute_dir(path: &str) -> Result<PathBuf, String> {
+    let dir = Path::new(path);
+    if !dir.is_absolute() {
+        return Err(format!("Path must be absolute: {path}"));
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Cannot create {path}: {e}"))?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {path}"));
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Prepare `library` for use: finish writes interrupted by a crash and start
+/// watching it for external changes. Problems are logged rather than fatal.
+pub fn activate(app: &tauri::AppHandle, library: &Library) {
+    let dir = match library.notes_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    if let Err(e) = atomic::recover(&dir) {
+        eprintln!("Recovery of {} failed: {e}", dir.display());
+    }
+    if let Err(e) = trash::purge_expired(&dir, library.settings.trash_retention_days) {
+        eprintln!("Emptying expired trash failed: {e}");
+    }
+    if let Err(e) = app.state::<WatcherState>().watch(app, library) {
+        eprintln!("Notes watcher unavailable: {e}");
+    }
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn list_libraries(library: tauri::State<LibraryState>) -> Result<LibraryConfig, String> {
+    let config = library.0.lock().map_err(|e| e.to_string())?;
+    Ok(config.clone())
+}
+
+#[tauri::command]
+pub fn add_library(
+    library: tauri::State<LibraryState>,
+    name: String,
+    path: String,
+) -> Result<Library, String> {
+    let dir = ensure_absolute_dir(&path)?;
+    library.update(|config| {
+        if config.libraries.iter().any(|lib| lib.root() == dir) {
+            return Err(format!("{path} is already a library"));
+        }
+        let lib = Library {
+            id: unique_id(&name, &config.libraries),
+            name,
+            path: dir.to_string_lossy().into_owned(),
+            settings: LibrarySettings::default(),
+        };
+        config.libraries.push(lib.clone());
+        if config.libraries.len() == 1 {
+            config.active = lib.id.clone();
+        }
+        Ok(lib)
+    })
+}
+
+#[tauri::command]
+pub fn switch_library(
+    app: tauri::AppHandle,
+    library: tauri::State<LibraryState>,
+    id: String,
+) -> Result<Library, String> {
+    let lib = library.update(|config| {
+        let lib = config
+            .libraries
+            .iter()
+            .find(|lib| lib.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown library: {id}"))?;
+        config.active = lib.id.clone();
+        Ok(lib)
+    })?;
+    activate(&app, &lib);
+    let _ = app.emit("library-changed", &lib);
+    Ok(lib)
+}
+
+/// Forget a library. Its files are left untouched on disk.
+#[tauri::command]
+pub fn remove_library(library: tauri::State<LibraryState>, id: String) -> Result<(), String> {
+    library.update(|config| {
+        if config.active == id {
+            return Err("Cannot remove the active library; switch to another one first".into());
+        }
+        let before = config.libraries.len();
+        config.libraries.retain(|lib| lib.id != id);
+        if config.libraries.len() == before {
+            return Err(format!("Unknown library: {id}"));
+        }
+        Ok(())
+    })
+}
+
+/// Set (or with `None`, reset) the directory Whisper models are stored in.
+#[tauri::command]
+pub fn set_models_dir(
+    library: tauri::State<LibraryState>,
+    path: Option<String>,
+) -> Result<(), String> {
+    if let Some(path) = &path {
+        ensure_absolute_dir(path)?;
+    }
+    library.update(|config| {
+        config.models_dir = path;
+        Ok(())
+    })
+}
+
+/// Replace the settings of library `id`. `storage` is left as it is; switching
+/// layouts goes through `notes::migrate_storage`.
+#[tauri::command]
+pub fn set_library_settings(
+    library: tauri::State<LibraryState>,
+    id: String,
+    settings: LibrarySettings,
+) -> Result<Library, String> {
+    library.update_settings(&id, |current| {
+        *current = LibrarySettings {
+            storage: current.storage,
+            ..settings
+        }
+    })
+}
@@ -0,0 +1,547 @@
+//! Wiki-links between notes: `[[note title]]` or `[[note-id]]` in a body,
+//! optionally with a `|label` or `#heading` after the target. Targets are
+//! cached in the catalog and resolved here, and rewritten when the note they
+//! point at is renamed, moved or retitled.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use super::catalog::{CatalogState, LinkIndex};
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes;
+use super::search::SearchState;
+use super::watcher::{NoteChange, WatcherState};
+
+/// A `[[...]]` link in a note body. `range` covers the target alone, so a
+/// rewrite keeps the label and heading.
+pub(crate) struct LinkSpan<'a> {
+    pub range: Range<usize>,
+    pub target: &'a str,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Resolution {
+    Note(NoteId),
+    Missing,
+    /// More than one note has this name or title.
+    Ambiguous,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Backlink {
+    pub note_id: NoteId,
+    pub title: String,
+    /// The first line of the linking note that holds the link.
+    pub excerpt: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokenReason {
+    Missing,
+    Ambiguous,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BrokenLink {
+    pub note_id: NoteId,
+    pub note_title: String,
+    pub target: String,
+    pub reason: BrokenReason,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GraphNode {
+    pub id: NoteId,
+    pub title: String,
+    /// Folder the note is in, empty at the top level.
+    pub folder: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GraphEdge {
+    pub source: NoteId,
+    pub target: NoteId,
+}
+
+/// Every note and the resolved links between them. Broken links are left out;
+/// see `list_broken_links`.
+#[derive(Serialize, Debug)]
+pub struct LinkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// The links in `markdown`, skipping fenced and inline code.
+pub(crate) fn link_spans(markdown: &str) -> Vec<LinkSpan<'_>> {
+    let mut spans = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut line_start = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut skip_to = 0;
+        for (i, c) in line.char_indices() {
+            if i < skip_to {
+                continue;
+            }
+            if c == '`' {
+                in_code = !in_code;
+            } else if !in_code && line[i..].starts_with("[[") {
+                let inner_start = i + 2;
+                let Some(len) = line[inner_start..].find("]]") else {
+                    break;
+                };
+                let inner = &line[inner_start..inner_start + len];
+                // `\|` separates the label inside Markdown tables.
+                let raw = &inner[..inner.find(['|', '#']).unwrap_or(inner.len())];
+                let raw = raw.strip_suffix('\\').unwrap_or(raw);
+                let target = raw.trim();
+                if !target.is_empty() && !target.contains('[') {
+                    let offset = start + inner_start + (raw.len() - raw.trim_start().len());
+                    spans.push(LinkSpan {
+                        range: offset..offset + target.len(),
+                        target,
+                    });
+                }
+                skip_to = inner_start + len + 2;
+            }
+        }
+    }
+    spans
+}
+
+/// Distinct link targets in `markdown`, as written.
+pub(crate) fn targets(markdown: &str) -> Vec<&str> {
+    let mut targets: Vec<&str> = Vec::new();
+    for span in link_spans(markdown) {
+        if !targets.contains(&span.target) {
+            targets.push(span.target);
+        }
+    }
+    targets
+}
+
+/// Resolves a link target to a note: by id, then by the id's last segment for
+/// notes in folders, then by title ignoring case.
+pub(crate) struct Resolver {
+    ids: HashSet<NoteId>,
+    names: HashMap<String, Vec<NoteId>>,
+    titles: HashMap<String, Vec<NoteId>>,
+}
+
+impl Resolver {
+    pub fn new(notes: &[(NoteId, String)]) -> Self {
+        let mut resolver = Resolver {
+            ids: HashSet::new(),
+            names: HashMap::new(),
+            titles: HashMap::new(),
+        };
+        for (id, title) in notes {
+            resolver.ids.insert(id.clone());
+            resolver
+                .names
+                .entry(id.name().to_string())
+                .or_default()
+                .push(id.clone());
+            resolver
+                .titles
+                .entry(title.trim().to_lowercase())
+                .or_default()
+                .push(id.clone());
+        }
+        resolver
+    }
+
+    pub fn resolve(&self, target: &str) -> Resolution {
+        if let Some(id) = self.ids.get(target) {
+            return Resolution::Note(id.clone());
+        }
+        let candidates = self
+            .names
+            .get(target)
+            .or_else(|| self.titles.get(&target.to_lowercase()));
+        match candidates.map(Vec::as_slice) {
+            Some([id]) => Resolution::Note(id.clone()),
+            Some(_) => Resolution::Ambiguous,
+            None => Resolution::Missing,
+        }
+    }
+}
+
+/// Replace the targets `rewrite` returns a new value for in the bodies of
+/// `sources`, saving and announcing each changed note. Call with `write_lock`
+/// held.
+fn rewrite_links(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    sources: HashSet<&NoteId>,
+    rewrite: impl Fn(&str) -> Option<String>,
+) {
+    for id in sources {
+        let mut changed = false;
+        let edited = notes::edit_note_locked(lib, search, catalog, watcher, id, |note| {
+            let mut content = note.content.clone();
+            // Back to front, so earlier ranges stay valid.
+            for span in link_spans(&note.content).iter().rev() {
+                if let Some(target) = rewrite(span.target) {
+                    content.replace_range(span.range.clone(), &target);
+                }
+            }
+            if content == note.content {
+                return false;
+            }
+            note.content = content;
+            note.meta.updated_at = chrono::Utc::now().to_rfc3339();
+            changed = true;
+            true
+        });
+        match edited {
+            Ok(()) if changed => watcher.announce(id, NoteChange::Updated),
+            Ok(()) => {}
+            Err(e) => eprintln!("Failed to update links in {id}: {e}"),
+        }
+    }
+}
+
+/// A target that resolves to `id`: `title` if given and unambiguous, else the
+/// id's last segment, else the full id.
+fn target_for(resolver: &Resolver, id: &NoteId, title: Option<&str>) -> String {
+    let usable = |target: &str| {
+        !target.contains(['|', '#', '[', ']', '\n'])
+            && resolver.resolve(target) == Resolution::Note(id.clone())
+    };
+    match title {
+        Some(title) if usable(title) => title.to_string(),
+        _ if usable(id.name()) => id.name().to_string(),
+        _ => id.to_string(),
+    }
+}
+
+fn link_index(lib: &Library, catalog: &CatalogState) -> Option<(LinkIndex, Resolver)> {
+    match catalog.link_index(lib) {
+        Ok(index) => {
+            let resolver = Resolver::new(&index.notes);
+            Some((index, resolver))
+        }
+        Err(e) => {
+            eprintln!("Failed to read note links: {e}");
+            None
+        }
+    }
+}
+
+/// Point links at note `from` to `to` after it was renamed or moved. Links
+/// that still resolve, by title or to another note, are left alone.
+pub(crate) fn retarget(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    from: &NoteId,
+    to: &NoteId,
+) {
+    let Some((index, resolver)) = link_index(lib, catalog) else {
+        return;
+    };
+    let pointed_at_from = |target: &str| {
+        (target == from.as_str() || target == from.name())
+            && resolver.resolve(target) == Resolution::Missing
+    };
+    let sources = index
+        .links
+        .iter()
+        .filter(|(_, target)| pointed_at_from(target))
+        .map(|(source, _)| source)
+        .collect();
+    let new_target = target_for(&resolver, to, None);
+    rewrite_links(lib, search, catalog, watcher, sources, |target| {
+        pointed_at_from(target).then(|| new_target.clone())
+    });
+}
+
+/// Point `[[old title]]` links at note `id` after its title changed, unless
+/// another note still has that title.
+pub(crate) fn retitle(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: &NoteId,
+    old_title: &str,
+) {
+    let Some((index, resolver)) = link_index(lib, catalog) else {
+        return;
+    };
+    let old_title = old_title.trim().to_lowercase();
+    let pointed_at_id = |target: &str| {
+        target.to_lowercase() == old_title && resolver.resolve(target) == Resolution::Missing
+    };
+    let sources = index
+        .links
+        .iter()
+        .filter(|(source, target)| source != id && pointed_at_id(target))
+        .map(|(source, _)| source)
+        .collect();
+    let title = index
+        .notes
+        .iter()
+        .find(|(note, _)| note == id)
+        .map(|(_, title)| title.trim());
+    let new_target = target_for(&resolver, id, title);
+    rewrite_links(lib, search, catalog, watcher, sources, |target| {
+        pointed_at_id(target).then(|| new_target.clone())
+    });
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Notes linking to note `id`, most recently updated first.
+#[tauri::command]
+pub fn get_backlinks(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+    id: NoteId,
+) -> Result<Vec<Backlink>, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let index = catalog.link_index(&lib)?;
+    let resolver = Resolver::new(&index.notes);
+    let links_here = |target: &str| resolver.resolve(target) == Resolution::Note(id.clone());
+
+    let sources: HashSet<&NoteId> = index
+        .links
+        .iter()
+        .filter(|(source, target)| *source != id && links_here(target))
+        .map(|(source, _)| source)
+        .collect();
+    let mut backlinks = Vec::new();
+    for (source, title) in &index.notes {
+        if !sources.contains(source) {
+            continue;
+        }
+        let excerpt = notes::load_note(&dir, source)
+            .ok()
+            .and_then(|note| {
+                note.content
+                    .lines()
+                    .find(|line| link_spans(line).iter().any(|span| links_here(span.target)))
+                    .map(|line| line.trim().to_string())
+            })
+            .unwrap_or_default();
+        backlinks.push(Backlink {
+            note_id: source.clone(),
+            title: title.clone(),
+            excerpt,
+        });
+    }
+    Ok(backlinks)
+}
+
+/// Links that point at no note, or at more than one.
+#[tauri::command]
+pub fn list_broken_links(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<Vec<BrokenLink>, String> {
+    let index = catalog.link_index(&library.active()?)?;
+    let resolver = Resolver::new(&index.notes);
+    let titles: HashMap<&NoteId, &String> = index.notes.iter().map(|(id, t)| (id, t)).collect();
+
+    let mut broken = Vec::new();
+    for (source, target) in &index.links {
+        let reason = match resolver.resolve(target) {
+            Resolution::Note(_) => continue,
+            Resolution::Missing => BrokenReason::Missing,
+            Resolution::Ambiguous => BrokenReason::Ambiguous,
+        };
+        broken.push(BrokenLink {
+            note_id: source.clone(),
+            note_title: titles
+                .get(source)
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            target: target.clone(),
+            reason,
+        });
+    }
+    broken.sort_by(|a, b| (&a.note_id, &a.target).cmp(&(&b.note_id, &b.target)));
+    Ok(broken)
+}
+
+/// The library's notes and the links between them, for graph views and export.
+#[tauri::command]
+pub fn export_link_graph(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<LinkGraph, String> {
+    let index = catalog.link_index(&library.active()?)?;
+    let resolver = Resolver::new(&index.notes);
+
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+    for (source, target) in &index.links {
+        if let Resolution::Note(target) = resolver.resolve(target) {
+            if &target != source && seen.insert((source, target.clone())) {
+                edges.push(GraphEdge {
+                    source: source.clone(),
+                    target,
+                });
+            }
+        }
+    }
+    let nodes = index
+        .notes
+        .into_iter()
+        .map(|(id, title)| GraphNode {
+            folder: id.folder().map(String::from).unwrap_or_default(),
+            id,
+            title,
+        })
+        .collect();
+    Ok(LinkGraph { nodes, edges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::atomic;
+
+    struct Fixture {
+        _tmp: tempfile::TempDir,
+        lib: Library,
+        search: SearchState,
+        catalog: CatalogState,
+        watcher: WatcherState,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let tmp = tempfile::tempdir().unwrap();
+            let lib = Library {
+                id: "test-links".to_string(),
+                name: "Test".to_string(),
+                path: tmp.path().to_string_lossy().into_owned(),
+                settings: Default::default(),
+            };
+            Fixture {
+                _tmp: tmp,
+                lib,
+                search: SearchState::default(),
+                catalog: CatalogState::default(),
+                watcher: WatcherState::default(),
+            }
+        }
+
+        fn dir(&self) -> std::path::PathBuf {
+            self.lib.notes_dir().unwrap()
+        }
+
+        fn save(&self, id: &str, title: &str, content: &str) {
+            let id = NoteId::parse(id).unwrap();
+            let (meta, base) = match notes::load_note(&self.dir(), &id) {
+                Ok(note) => (note.meta, Some(note.revision)),
+                Err(_) => (serde_json::from_str("{}").unwrap(), None),
+            };
+            let meta = notes::NoteMeta {
+                title: title.to_string(),
+                ..meta
+            };
+            notes::save_note(
+                &self.lib,
+                &self.search,
+                &self.catalog,
+                &self.watcher,
+                id,
+                content.to_string(),
+                meta,
+                base,
+            )
+            .unwrap();
+        }
+
+        fn content(&self, id: &str) -> String {
+            notes::load_note(&self.dir(), &NoteId::parse(id).unwrap())
+                .unwrap()
+                .content
+        }
+    }
+
+    #[test]
+    fn links_follow_a_retitled_note() {
+        let f = Fixture::new();
+        f.save("2026-03-01-budget", "Budget", "Numbers");
+        f.save(
+            "2026-03-02-plan",
+            "Plan",
+            "See [[Budget]] and [[budget|the numbers]].",
+        );
+        f.save("2026-03-03-other", "Other", "Unrelated [[Roadmap]].");
+
+        f.save("2026-03-01-budget", "Budget 2026", "Numbers");
+        assert_eq!(
+            f.content("2026-03-02-plan"),
+            "See [[Budget 2026]] and [[Budget 2026|the numbers]]."
+        );
+        assert_eq!(f.content("2026-03-03-other"), "Unrelated [[Roadmap]].");
+    }
+
+    #[test]
+    fn links_are_left_alone_while_another_note_has_the_old_title() {
+        let f = Fixture::new();
+        f.save("2026-03-01-budget", "Budget", "Numbers");
+        f.save("2026-02-01-budget", "Budget", "Last year's numbers");
+        f.save("2026-03-02-plan", "Plan", "See [[Budget]].");
+
+        f.save("2026-03-01-budget", "Budget 2026", "Numbers");
+        assert_eq!(f.content("2026-03-02-plan"), "See [[Budget]].");
+    }
+
+    #[test]
+    fn links_follow_a_renamed_note() {
+        let f = Fixture::new();
+        f.save("2026-03-01-budget", "Budget", "Numbers");
+        f.save(
+            "2026-03-02-plan",
+            "Plan",
+            "By id [[2026-03-01-budget]], by title [[Budget]].",
+        );
+
+        let dir = f.dir();
+        let (from, to) = (
+            NoteId::parse("2026-03-01-budget").unwrap(),
+            NoteId::parse("2026-03-01-finances").unwrap(),
+        );
+        let moves: Vec<_> = notes::note_paths(&dir, &from)
+            .into_iter()
+            .zip(notes::note_paths(&dir, &to))
+            .filter(|(from, _)| from.exists())
+            .collect();
+        atomic::move_files(&moves).unwrap();
+        let _lock = notes::write_lock();
+        notes::note_moved(&f.lib, &f.search, &f.catalog, &f.watcher, &from, &to);
+
+        assert_eq!(
+            f.content("2026-03-02-plan"),
+            "By id [[2026-03-01-finances]], by title [[Budget]]."
+        );
+    }
+}
@@ -1,3 +1,26 @@
 pub mod notes;
 pub mod ai;
+pub mod actions;
+pub mod atomic;
+pub mod attachments;
+pub mod catalog;
+pub mod decisions;
+pub mod diff;
+pub mod encryption;
+pub mod export;
+pub mod folders;
+pub mod git;
+pub mod history;
+pub mod ics;
+pub mod import;
+pub mod library;
+pub mod links;
+pub mod note_id;
+pub mod people;
+pub mod search;
+pub mod series;
+pub mod tags;
+pub mod templates;
 pub mod transcribe;
+pub mod trash;
+pub mod watcher;
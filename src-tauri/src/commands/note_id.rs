@@ -0,0 +1,352 @@
+//! Validated note ids and folder paths.
+//!
+//! Note ids are joined straight into file paths (`<id>.md`, `recordings/<id>`,
+//! ...), so every command takes a `NoteId` rather than a bare string. A note in
+//! a folder has the folder in its id, `clients/acme/2026-02-18-kickoff`, always
+//! separated by `/`. Parsing rejects anything that could name a file outside
+//! the library or that some platform cannot store, and since the check runs
+//! during deserialisation a command is never even invoked with a bad id.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Longest id accepted, leaving room for suffixes within common 255-byte
+/// file name limits.
+const MAX_LEN: usize = 200;
+
+/// Top-level directories that hold the app's own files, not notes.
+pub const RESERVED_FOLDERS: &[&str] = &["recordings", "models", "templates"];
+
+/// Names Windows reserves for devices, with or without an extension.
+const RESERVED: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NoteId(String);
+
+impl NoteId {
+    pub fn parse(id: impl Into<String>) -> Result<Self, String> {
+        let id = id.into();
+        validate_path(&id, false).map_err(|reason| format!("Invalid note id {id:?}: {reason}"))?;
+        Ok(NoteId(id))
+    }
+
+    /// The id of a note called `name` in `folder`, or at the top level.
+    pub fn in_folder(folder: Option<&FolderPath>, name: &str) -> Result<Self, String> {
+        match folder {
+            Some(folder) => NoteId::parse(format!("{folder}/{name}")),
+            None => NoteId::parse(name),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The folder the note is in, `None` at the top level.
+    pub fn folder(&self) -> Option<FolderPath> {
+        let (folder, _) = self.0.rsplit_once('/')?;
+        Some(FolderPath(folder.to_string()))
+    }
+
+    /// The id without its folder, i.e. the file name without `.md`.
+    pub fn name(&self) -> &str {
+        self.0.rsplit('/').next().unwrap_or(&self.0)
+    }
+}
+
+/// A folder of notes, relative to the library root with `/` separators.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FolderPath(String);
+
+impl FolderPath {
+    pub fn parse(path: impl Into<String>) -> Result<Self, String> {
+        let path = path.into();
+        validate_path(&path, true)
+            .map_err(|reason| format!("Invalid folder {path:?}: {reason}"))?;
+        Ok(FolderPath(path))
+    }
+
+    /// The folder called `name` in `parent`, or at the top level.
+    pub fn in_folder(parent: Option<&FolderPath>, name: &str) -> Result<Self, String> {
+        match parent {
+            Some(parent) => FolderPath::parse(format!("{parent}/{name}")),
+            None => FolderPath::parse(name),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn parent(&self) -> Option<FolderPath> {
+        let (parent, _) = self.0.rsplit_once('/')?;
+        Some(FolderPath(parent.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        self.0.rsplit('/').next().unwrap_or(&self.0)
+    }
+
+    /// Whether `path` (a note id or folder) is inside this folder, at any depth.
+    pub fn contains(&self, path: &str) -> bool {
+        path.strip_prefix(self.as_str())
+            .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// Check a `/`-separated note id or, with `is_folder`, folder path, segment
+/// by segment.
+fn validate_path(path: &str, is_folder: bool) -> Result<(), &'static str> {
+    if path.len() > MAX_LEN {
+        return Err("too long");
+    }
+    let segments: Vec<&str> = path.split('/').collect();
+    for (i, segment) in segments.iter().enumerate() {
+        validate(segment).map_err(|reason| match reason {
+            "empty" if i > 0 => "contains an empty folder name",
+            reason => reason,
+        })?;
+    }
+    if (is_folder || segments.len() > 1) && RESERVED_FOLDERS.contains(&segments[0]) {
+        return Err("reserved folder name");
+    }
+    Ok(())
+}
+
+/// Check one file or folder name.
+fn validate(id: &str) -> Result<(), &'static str> {
+    if id.is_empty() {
+        return Err("empty");
+    }
+    if id.contains("..") {
+        return Err("contains \"..\"");
+    }
+    if id.starts_with('.') {
+        return Err("starts with a dot");
+    }
+    if id.ends_with(['.', ' ']) {
+        return Err("ends with a dot or space");
+    }
+    if let Some(c) = id.chars().find(|&c| {
+        c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+    }) {
+        return Err(if c == '/' || c == '\\' {
+            "contains a path separator"
+        } else {
+            "contains a character not allowed in file names"
+        });
+    }
+    let stem = id.split('.').next().unwrap_or(id).trim_end();
+    if RESERVED.contains(&stem.to_ascii_lowercase().as_str()) {
+        return Err("reserved file name");
+    }
+    Ok(())
+}
+
+impl TryFrom<String> for NoteId {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, String> {
+        NoteId::parse(id)
+    }
+}
+
+impl From<NoteId> for String {
+    fn from(id: NoteId) -> String {
+        id.0
+    }
+}
+
+impl Deref for NoteId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for NoteId {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl Borrow<str> for NoteId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NoteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for FolderPath {
+    type Error = String;
+
+    fn try_from(path: String) -> Result<Self, String> {
+        FolderPath::parse(path)
+    }
+}
+
+impl From<FolderPath> for String {
+    fn from(path: FolderPath) -> String {
+        path.0
+    }
+}
+
+impl Deref for FolderPath {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for FolderPath {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl fmt::Display for FolderPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes;
+    use std::path::Component;
+
+    const HOSTILE: &[&str] = &[
+        "",
+        ".",
+        "..",
+        "../x",
+        "../../.ssh/id_rsa",
+        "a/../../b",
+        "..\\..\\windows",
+        "/etc/passwd",
+        "\\\\server\\share",
+        "C:\\Users",
+        "C:",
+        "notes/",
+        "notes//inner",
+        "notes/.hidden",
+        "notes/con",
+        "recordings/2026-02-18-kickoff",
+        "models/inner/x",
+        "templates/standup",
+        ".hidden",
+        ".trash",
+        ".history",
+        "a..b",
+        "trailing.",
+        "trailing ",
+        "nul\0byte",
+        "line\nbreak",
+        "CON",
+        "con.txt",
+        "Lpt1",
+        "aux .md",
+        "what?",
+        "a|b",
+    ];
+
+    #[test]
+    fn accepts_generated_and_legacy_ids() {
+        for id in [
+            "2026-02-18-product-kickoff",
+            "2026-02-18-abc12",
+            "2026-02-18-untitled-2",
+            "Meeting notes",
+            "réunion-équipe",
+            "v1.2-release",
+            "console",
+            "clients/acme/2026-02-18-kickoff",
+            "recordings",
+        ] {
+            assert!(NoteId::parse(id).is_ok(), "{id} should be accepted");
+        }
+    }
+
+    #[test]
+    fn rejects_hostile_ids() {
+        for id in HOSTILE {
+            assert!(NoteId::parse(*id).is_err(), "{id:?} should be rejected");
+        }
+        assert!(NoteId::parse("a".repeat(MAX_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn folders_split_into_parent_and_name() {
+        let id = NoteId::parse("clients/acme/2026-02-18-kickoff").unwrap();
+        let folder = id.folder().unwrap();
+        assert_eq!(folder.as_str(), "clients/acme");
+        assert_eq!(id.name(), "2026-02-18-kickoff");
+        assert_eq!(folder.parent().unwrap().as_str(), "clients");
+        assert!(folder.contains(&id));
+        assert!(folder.parent().unwrap().contains(&folder));
+        assert!(!folder.contains("clients/acme-2/x"));
+        assert!(NoteId::parse("kickoff").unwrap().folder().is_none());
+        for path in HOSTILE {
+            assert!(
+                FolderPath::parse(*path).is_err(),
+                "{path:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn commands_never_receive_hostile_ids() {
+        // Command arguments are deserialised from JSON before the command runs.
+        for id in HOSTILE {
+            let json = serde_json::to_string(id).unwrap();
+            assert!(
+                serde_json::from_str::<NoteId>(&json).is_err(),
+                "{id:?} deserialised"
+            );
+        }
+        let id: NoteId = serde_json::from_str("\"2026-02-18-kickoff\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&id).unwrap(),
+            "\"2026-02-18-kickoff\""
+        );
+    }
+
+    #[test]
+    fn note_paths_stay_inside_the_library() {
+        let root = Path::new("/library");
+        for id in [
+            "2026-02-18-kickoff",
+            "a",
+            "réunion",
+            "x.y",
+            "CONSOLE",
+            "a/b/c",
+        ] {
+            let id = NoteId::parse(id).unwrap();
+            for path in notes::note_paths(root, &id) {
+                let rel = path.strip_prefix(root).unwrap();
+                assert!(
+                    rel.components().all(|c| matches!(c, Component::Normal(_))),
+                    "{} escapes the library",
+                    path.display()
+                );
+            }
+        }
+    }
+}
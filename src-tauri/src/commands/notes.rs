@@ -1,133 +1,1122 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::actions;
+use super::atomic;
+use super::attachments::{self, ASSETS_SUFFIX};
+use super::catalog::{CatalogState, NotePage, NoteQuery};
+use super::decisions;
+use super::diff;
+use super::encryption;
+use super::git::GitState;
+use super::history::{self, SnapshotReason};
+use super::library::{Library, LibraryState};
+use super::links;
+use super::note_id::{FolderPath, NoteId, RESERVED_FOLDERS};
+use super::people;
+use super::search::SearchState;
+use super::series::SeriesLink;
+use super::tags;
+use super::trash::{self, TrashEntry};
+use super::watcher::WatcherState;
+
+/// Frontmatter written by other tools is looser than our own meta: `title:
+/// 2024`, `tags: meeting` and `tags: [a, 1]` are all accepted.
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NoteMeta {
+    #[serde(default, deserialize_with = "lenient_text")]
     pub title: String,
+    #[serde(default, deserialize_with = "lenient_list")]
     pub participants: Vec<String>,
+    #[serde(default, deserialize_with = "lenient_tags")]
     pub tags: Vec<String>,
+    #[serde(default, deserialize_with = "lenient_text")]
     pub created_at: String,
+    #[serde(default, deserialize_with = "lenient_text")]
     pub updated_at: String,
+    /// The recurring meeting this note is an occurrence of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<SeriesLink>,
+    /// Keys written by other tools (Obsidian `aliases` etc.), kept as they are.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// A scalar as text; `None` for null, lists and maps.
+fn scalar_text(value: serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(text) => Some(text),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn lenient_text<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(scalar_text(serde_json::Value::deserialize(deserializer)?).unwrap_or_default())
+}
+
+/// A list of names, or a single one.
+fn lenient_list<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    let items = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(items) => items,
+        value => vec![value],
+    };
+    Ok(items
+        .into_iter()
+        .filter_map(scalar_text)
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect())
+}
+
+/// Tags as Obsidian accepts them: a list, or one string separated by commas
+/// or spaces, with or without `#`.
+fn lenient_tags<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    let mut tags: Vec<String> = Vec::new();
+    for item in lenient_list(deserializer)? {
+        for tag in item.split([',', ' ']) {
+            let tag = tag.trim().trim_start_matches('#');
+            if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// How a library stores each note's `NoteMeta`. Notes in either layout are
+/// always readable; this only decides how they are written.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteStorage {
+    /// JSON in an `<id>.meta.json` sidecar next to `<id>.md`.
+    #[default]
+    Sidecar,
+    /// YAML frontmatter at the top of `<id>.md`, as Obsidian, Logseq and
+    /// static site generators expect.
+    Frontmatter,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Note {
-    pub id: String,
+    pub id: NoteId,
     pub content: String,
     pub meta: NoteMeta,
+    /// Hash of the note and meta files as read; pass it back to `write_note`.
+    #[serde(default)]
+    pub revision: String,
+    /// Stored encrypted; see `encryption`. Saving keeps it that way.
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NoteSummary {
-    pub id: String,
+    pub id: NoteId,
+    /// Folder the note is in, empty at the top level.
+    pub folder: String,
     pub title: String,
     pub created_at: String,
     pub updated_at: String,
     pub tags: Vec<String>,
     pub preview: String,
+    /// Listed with a placeholder title and no preview while locked.
+    pub encrypted: bool,
+}
+
+/// Error returned by `write_note`, tagged by `kind` for the frontend.
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WriteNoteError {
+    /// The note changed on disk since `base_revision` was read.
+    Conflict {
+        current: Box<Note>,
+        attempted: Box<Note>,
+    },
+    Failed {
+        message: String,
+    },
 }
 
-fn notes_dir() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join("Brief")
+impl From<String> for WriteNoteError {
+    fn from(message: String) -> Self {
+        WriteNoteError::Failed { message }
+    }
 }
 
-fn ensure_notes_dir() -> Result<PathBuf, String> {
-    let dir = notes_dir();
-    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
-    Ok(dir)
+/// Files to commit together, with their new contents.
+type FileWrites = Vec<(PathBuf, Vec<u8>)>;
+
+/// Longest slug put into a note id; keeps file names manageable.
+const MAX_SLUG_LEN: usize = 60;
+
+/// Serialises the check-then-write in `write_note` between windows.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Hold while changing which notes exist or where they live.
+pub(crate) fn write_lock() -> MutexGuard<'static, ()> {
+    WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
 }
 
+/// A page of note summaries, answered from the metadata catalog after
+/// re-reading any notes whose files changed. Without a `query`, every note,
+/// most recently updated first. Encrypted notes only show their real title
+/// while the library is unlocked, and filters see just their placeholder.
 #[tauri::command]
-pub fn list_notes() -> Result<Vec<NoteSummary>, String> {
-    let dir = ensure_notes_dir()?;
-    let mut notes = Vec::new();
-
-    let entries = fs::read_dir(&dir).map_err(|e| e.to_string())?;
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) == Some("md") {
-            let id = path.file_stem().unwrap().to_string_lossy().to_string();
-            let meta_path = dir.join(format!("{}.meta.json", id));
+pub fn list_notes(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+    query: Option<NoteQuery>,
+) -> Result<NotePage, String> {
+    let lib = library.active()?;
+    let mut page = catalog.list(&lib, &query.unwrap_or_default())?;
+    encryption::reveal(&lib.notes_dir()?, &mut page.notes);
+    Ok(page)
+}
 
-            let content = fs::read_to_string(&path).unwrap_or_default();
-            let preview: String = content.chars().take(120).collect();
+#[tauri::command]
+pub fn read_note(library: tauri::State<LibraryState>, id: NoteId) -> Result<Note, String> {
+    let dir = library.notes_dir()?;
+    load_note(&dir, &id)
+}
 
-            let meta: NoteMeta = if meta_path.exists() {
-                let raw = fs::read_to_string(&meta_path).unwrap_or_default();
-                serde_json::from_str(&raw).unwrap_or_else(|_| default_meta(&id))
-            } else {
-                default_meta(&id)
-            };
+/// Save a note. `base_revision` is the revision the edit started from, or
+/// `None` when creating a new note; if the note on disk no longer matches it
+/// nothing is written and a `Conflict` carrying both versions is returned.
+/// Returns the new revision. In a git-synced library the save is committed
+/// shortly after.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn write_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    git: tauri::State<GitState>,
+    id: NoteId,
+    content: String,
+    meta: NoteMeta,
+    base_revision: Option<String>,
+) -> Result<String, WriteNoteError> {
+    let lib = library.active()?;
+    let revision = save_note(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        id,
+        content,
+        meta,
+        base_revision,
+    )?;
+    git.schedule_commit(&lib);
+    Ok(revision)
+}
+
+/// The checked write behind `write_note`, shared with commands that replace a
+/// note wholesale. Merges the body's `#tags` and `@mentions` into `meta` and
+/// takes a throttled history snapshot of the note it replaces.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn save_note(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: NoteId,
+    content: String,
+    meta: NoteMeta,
+    base_revision: Option<String>,
+) -> Result<String, WriteNoteError> {
+    let _lock = write_lock();
+    save_note_locked(
+        lib,
+        search,
+        catalog,
+        watcher,
+        id,
+        content,
+        meta,
+        base_revision,
+    )
+}
+
+/// `save_note` for callers already holding `write_lock`. A changed title is
+/// carried over to `[[title]]` links in other notes. An encrypted note stays
+/// encrypted, without action item sidecar or history.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn save_note_locked(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: NoteId,
+    content: String,
+    meta: NoteMeta,
+    base_revision: Option<String>,
+) -> Result<String, WriteNoteError> {
+    let dir = lib.notes_dir()?;
+
+    let current = if dir.join(format!("{}.md", id)).exists() {
+        Some(load_note(&dir, &id)?)
+    } else {
+        None
+    };
+    let previous = current.as_ref().map(|note| note.content.as_str());
+    let encrypted = current.as_ref().is_some_and(|note| note.encrypted);
+    let directory = people::load(&dir).unwrap_or_else(|e| {
+        eprintln!("Saving {id} without resolving participants: {e}");
+        Vec::new()
+    });
+    let meta = tags::merge_into_meta(meta, previous, &content, &directory);
+    let storage = lib.settings.storage;
+    let (mut files, revision) = encode_note(&dir, &id, &content, &meta, storage, encrypted)?;
 
-            notes.push(NoteSummary {
+    let old_title = match current {
+        Some(current) if base_revision.as_deref() != Some(current.revision.as_str()) => {
+            let attempted = Note {
                 id,
-                title: meta.title,
-                created_at: meta.created_at,
-                updated_at: meta.updated_at,
-                tags: meta.tags,
-                preview,
+                content,
+                meta,
+                revision,
+                encrypted,
+            };
+            return Err(WriteNoteError::Conflict {
+                current: Box::new(current),
+                attempted: Box::new(attempted),
             });
         }
+        Some(current) => {
+            history::snapshot_or_log(lib, &current, SnapshotReason::Autosave);
+            Some(current.meta.title)
+        }
+        None => None,
+    };
+
+    if !encrypted {
+        files.extend(actions::sidecar_for(&dir, &id, &content)?);
+    }
+    commit_note_files(&dir, watcher, &id, &files, storage, encrypted)?;
+
+    // The note is already safely on disk; a stale index is recoverable.
+    let note = Note {
+        id,
+        content,
+        meta,
+        revision: revision.clone(),
+        encrypted,
+    };
+    if let Err(e) = search.index_note(lib, &note) {
+        eprintln!("Failed to index note {}: {e}", note.id);
+    }
+    if let Err(e) = catalog.index_note(lib, &note) {
+        eprintln!("Failed to catalog note {}: {e}", note.id);
+    }
+    if let Some(old_title) = old_title.filter(|old| *old != note.meta.title) {
+        links::retitle(lib, search, catalog, watcher, &note.id, &old_title);
+    }
+
+    Ok(revision)
+}
+
+/// Write `note` back as it is, encrypted or in the library's layout, and
+/// update the indexes. Returns the new revision. Call with `write_lock` held.
+pub(crate) fn rewrite_note_locked(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    note: &Note,
+    encrypted: bool,
+) -> Result<String, String> {
+    let dir = lib.notes_dir()?;
+    let storage = lib.settings.storage;
+    let id = &note.id;
+    let (mut files, revision) =
+        encode_note(&dir, id, &note.content, &note.meta, storage, encrypted)?;
+    if !encrypted {
+        files.extend(actions::sidecar_for(&dir, id, &note.content)?);
+    }
+    files.extend(decisions::sidecar_for(&dir, id, encrypted)?);
+    commit_note_files(&dir, watcher, id, &files, storage, encrypted)?;
+
+    let note = Note {
+        id: id.clone(),
+        content: note.content.clone(),
+        meta: note.meta.clone(),
+        revision: revision.clone(),
+        encrypted,
+    };
+    if let Err(e) = search.index_note(lib, &note) {
+        eprintln!("Failed to index note {id}: {e}");
+    }
+    if let Err(e) = catalog.index_note(lib, &note) {
+        eprintln!("Failed to catalog note {id}: {e}");
     }
+    Ok(revision)
+}
 
-    notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-    Ok(notes)
+/// Load note `id`, let `edit` change its content and meta, and save it if
+/// `edit` returns `true`. Call with `write_lock` held.
+pub(crate) fn edit_note_locked(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: &NoteId,
+    edit: impl FnOnce(&mut Note) -> bool,
+) -> Result<(), String> {
+    let mut note = load_note(&lib.notes_dir()?, id)?;
+    if !edit(&mut note) {
+        return Ok(());
+    }
+    save_note_locked(
+        lib,
+        search,
+        catalog,
+        watcher,
+        note.id,
+        note.content,
+        note.meta,
+        Some(note.revision),
+    )
+    .map(|_| ())
+    .map_err(|e| match e {
+        WriteNoteError::Conflict { .. } => format!("Note {id} changed while being updated"),
+        WriteNoteError::Failed { message } => message,
+    })
 }
 
+/// Create an empty note titled `title` under a fresh id in `folder` (the top
+/// level if `None`) and return it.
 #[tauri::command]
-pub fn read_note(id: String) -> Result<Note, String> {
-    let dir = ensure_notes_dir()?;
-    let md_path = dir.join(format!("{}.md", id));
-    let meta_path = dir.join(format!("{}.meta.json", id));
+pub fn create_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    title: String,
+    folder: Option<FolderPath>,
+) -> Result<Note, String> {
+    let lib = library.active()?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let meta = NoteMeta {
+        title,
+        participants: vec![],
+        tags: vec![],
+        created_at: now.clone(),
+        updated_at: now,
+        series: None,
+        extra: BTreeMap::new(),
+    };
+    add_note(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        folder.as_ref(),
+        String::new(),
+        meta,
+    )
+}
 
-    let content = fs::read_to_string(&md_path).map_err(|e| e.to_string())?;
-    let meta: NoteMeta = if meta_path.exists() {
-        let raw = fs::read_to_string(&meta_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&raw).map_err(|e| e.to_string())?
-    } else {
-        default_meta(&id)
+/// Save a new note with `content` and `meta` under a fresh id in `folder`
+/// derived from its meeting day and title, and return it.
+pub(crate) fn add_note(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    folder: Option<&FolderPath>,
+    content: String,
+    meta: NoteMeta,
+) -> Result<Note, String> {
+    let dir = lib.notes_dir()?;
+    let id = unique_note_id(&dir, folder, meeting_day(&meta), &meta.title, None)?;
+    let revision = save_note(
+        lib,
+        search,
+        catalog,
+        watcher,
+        id.clone(),
+        content,
+        meta,
+        None,
+    )
+    .map_err(|e| match e {
+        WriteNoteError::Conflict { .. } => format!("Note {id} already exists"),
+        WriteNoteError::Failed { message } => message,
+    })?;
+    let mut note = load_note(&dir, &id)?;
+    note.revision = revision;
+    Ok(note)
+}
+
+/// Give a note the id its `title` calls for, keeping its folder and date
+/// prefix, and move its sidecars, attachments, recordings and history along,
+/// updating links to the attachments in its body. Returns the new id, which
+/// is the old one if nothing had to change.
+#[tauri::command]
+pub fn rename_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+    title: String,
+) -> Result<NoteId, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+
+    let _lock = write_lock();
+    let note = load_note(&dir, &id)?;
+    let date = meeting_day(&note.meta);
+    let new_id = unique_note_id(&dir, id.folder().as_ref(), date, &title, Some(&id))?;
+    if new_id == id {
+        return Ok(id);
+    }
+
+    let moves: Vec<(PathBuf, PathBuf)> = note_paths(&dir, &id)
+        .into_iter()
+        .zip(note_paths(&dir, &new_id))
+        .filter(|(from, _)| from.exists())
+        .collect();
+    for (from, _) in &moves {
+        watcher.record_delete(&id, from);
+    }
+    atomic::move_files(&moves).map_err(|e| e.to_string())?;
+    for (_, to) in &moves {
+        watcher.record_write(&new_id, to);
+    }
+    note_moved(&lib, &search, &catalog, &watcher, &id, &new_id);
+    Ok(new_id)
+}
+
+/// Move a note, with its sidecars, attachments, recordings and history, into
+/// `folder` (the top level if `None`), creating the folder if needed. Returns
+/// the new id.
+#[tauri::command]
+pub fn move_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+    folder: Option<FolderPath>,
+) -> Result<NoteId, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+
+    let _lock = write_lock();
+    if !dir.join(format!("{}.md", id)).exists() {
+        return Err(format!("Note not found: {id}"));
+    }
+    let new_id = NoteId::in_folder(folder.as_ref(), id.name())?;
+    if new_id == id {
+        return Ok(id);
+    }
+    if name_taken(&dir, &new_id) {
+        return Err(format!(
+            "A note or folder named {} already exists in {}",
+            new_id.name(),
+            folder.as_deref().unwrap_or("the top level")
+        ));
+    }
+
+    let moves: Vec<(PathBuf, PathBuf)> = note_paths(&dir, &id)
+        .into_iter()
+        .zip(note_paths(&dir, &new_id))
+        .filter(|(from, _)| from.exists())
+        .collect();
+    for (from, _) in &moves {
+        watcher.record_delete(&id, from);
+    }
+    atomic::move_files(&moves).map_err(|e| e.to_string())?;
+    for (_, to) in &moves {
+        watcher.record_write(&new_id, to);
+    }
+    note_moved(&lib, &search, &catalog, &watcher, &id, &new_id);
+    Ok(new_id)
+}
+
+/// Point references, links and the indexes at a note's new id once its files
+/// have moved. Call with `write_lock` held.
+pub(crate) fn note_moved(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    from: &NoteId,
+    to: &NoteId,
+) {
+    let Ok(dir) = lib.notes_dir() else {
+        return;
     };
+    decisions::rename_references(&dir, watcher, from, to);
 
-    Ok(Note { id, content, meta })
+    if let Err(e) = search.remove_note(lib, from) {
+        eprintln!("Failed to remove note {from} from search index: {e}");
+    }
+    if let Err(e) = catalog.remove_note(lib, from) {
+        eprintln!("Failed to remove note {from} from catalog: {e}");
+    }
+    match load_note(&dir, to) {
+        Ok(note) => {
+            if let Err(e) = search.index_note(lib, &note) {
+                eprintln!("Failed to index note {to}: {e}");
+            }
+            if let Err(e) = catalog.index_note(lib, &note) {
+                eprintln!("Failed to catalog note {to}: {e}");
+            }
+        }
+        Err(e) => eprintln!("Moved note {to} is unreadable: {e}"),
+    }
+    attachments::relink(lib, search, catalog, watcher, from, to);
+    links::retarget(lib, search, catalog, watcher, from, to);
 }
 
+/// Convert every note in the active library to `storage` layout and make it
+/// the library's layout from now on. Content and metadata, including keys
+/// other tools added, carry over unchanged; encrypted notes are left as they
+/// are. Returns how many notes were
+/// converted; open notes must be re-read since their revisions change.
 #[tauri::command]
-pub fn write_note(id: String, content: String, meta: NoteMeta) -> Result<(), String> {
-    let dir = ensure_notes_dir()?;
-    let md_path = dir.join(format!("{}.md", id));
-    let meta_path = dir.join(format!("{}.meta.json", id));
+pub fn migrate_storage(
+    library: tauri::State<LibraryState>,
+    watcher: tauri::State<WatcherState>,
+    storage: NoteStorage,
+) -> Result<usize, String> {
+    let lib = library.active()?;
+    let _lock = write_lock();
+    let converted = convert_storage(&lib.notes_dir()?, &watcher, storage)?;
+    library.update_settings(&lib.id, |settings| settings.storage = storage)?;
+    Ok(converted)
+}
 
-    fs::write(&md_path, &content).map_err(|e| e.to_string())?;
-    let meta_json = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
-    fs::write(&meta_path, meta_json).map_err(|e| e.to_string())?;
+/// Rewrite every unencrypted note in `dir` not yet in `storage` layout.
+fn convert_storage(
+    dir: &Path,
+    watcher: &WatcherState,
+    storage: NoteStorage,
+) -> Result<usize, String> {
+    let mut converted = 0;
+    for id in note_ids(dir) {
+        if encryption::is_encrypted(dir, &id) || storage_of(dir, &id)? == Some(storage) {
+            continue;
+        }
+        let note = load_note(dir, &id)?;
+        let (files, _) = encode_note(dir, &id, &note.content, &note.meta, storage, false)?;
+        commit_note_files(dir, watcher, &id, &files, storage, false)
+            .map_err(|e| format!("Failed to convert note {id}: {e}"))?;
+        converted += 1;
+    }
+    Ok(converted)
+}
 
-    Ok(())
+/// Three-way merge of note bodies for resolving a `write_note` conflict:
+/// `base` is the content both sides started from. Returns `None` if the edits
+/// overlap and must be resolved by hand.
+#[tauri::command]
+pub fn merge_note(base: String, ours: String, theirs: String) -> Option<String> {
+    diff::merge3(&base, &ours, &theirs)
 }
 
+/// Move a note, its sidecars, attachments, recordings and history to the trash. See `trash::restore_note`.
 #[tauri::command]
-pub fn delete_note(id: String) -> Result<(), String> {
-    let dir = notes_dir();
+pub fn delete_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    git: tauri::State<GitState>,
+    id: NoteId,
+) -> Result<TrashEntry, String> {
+    let lib = library.active()?;
+    let _lock = write_lock();
+    let entry = trash_note(&lib, &search, &catalog, &watcher, &id)?;
+    git.schedule_commit(&lib);
+    Ok(entry)
+}
+
+/// The work behind `delete_note`, shared with deleting whole folders. Call
+/// with `write_lock` held.
+pub(crate) fn trash_note(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    id: &NoteId,
+) -> Result<TrashEntry, String> {
+    let dir = lib.notes_dir()?;
+
+    for path in note_files(&dir, id) {
+        watcher.record_delete(id, &path);
+    }
+    let entry = trash::move_to_trash(&dir, id)?;
+
+    if let Err(e) = search.remove_note(lib, id) {
+        eprintln!("Failed to remove note {id} from search index: {e}");
+    }
+    if let Err(e) = catalog.remove_note(lib, id) {
+        eprintln!("Failed to remove note {id} from catalog: {e}");
+    }
+
+    Ok(entry)
+}
+
+/// Read a note from `dir` in either layout: the meta sidecar if there is one,
+/// otherwise YAML frontmatter if the Markdown file starts with some. An
+/// encrypted note can only be read while the library is unlocked.
+pub(crate) fn load_note(dir: &Path, id: &NoteId) -> Result<Note, String> {
     let md_path = dir.join(format!("{}.md", id));
     let meta_path = dir.join(format!("{}.meta.json", id));
+    let _guard = atomic::read_guard();
 
-    if md_path.exists() {
-        fs::remove_file(&md_path).map_err(|e| e.to_string())?;
+    let content = fs::read_to_string(&md_path).map_err(|e| e.to_string())?;
+    if let Some(sealed) = encryption::sealed_body(&content) {
+        let (body, meta) = encryption::open_note(dir, id, sealed)?;
+        return Ok(Note {
+            id: id.clone(),
+            revision: revision_of(content.as_bytes(), b""),
+            content: body,
+            meta,
+            encrypted: true,
+        });
     }
-    if meta_path.exists() {
-        fs::remove_file(&meta_path).map_err(|e| e.to_string())?;
+    let raw_meta = if meta_path.exists() {
+        fs::read_to_string(&meta_path).map_err(|e| e.to_string())?
+    } else if let Some((mut meta, body)) = parse_frontmatter(&content) {
+        fill_missing_meta(&mut meta, id);
+        return Ok(Note {
+            id: id.clone(),
+            revision: revision_of(content.as_bytes(), b""),
+            content: body.to_string(),
+            meta,
+            encrypted: false,
+        });
+    } else {
+        String::new()
+    };
+    let meta: NoteMeta = if raw_meta.is_empty() {
+        default_meta(id)
+    } else {
+        serde_json::from_str(&raw_meta).map_err(|e| e.to_string())?
+    };
+
+    Ok(Note {
+        id: id.clone(),
+        revision: revision_of(content.as_bytes(), raw_meta.as_bytes()),
+        content,
+        meta,
+        encrypted: false,
+    })
+}
+
+/// Content hash identifying one on-disk version of a note.
+pub(crate) fn revision_of(content: &[u8], meta: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update((content.len() as u64).to_le_bytes());
+    hasher.update(content);
+    hasher.update(meta);
+    hasher
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Every file and directory that makes up note `id`, whether or not it
+/// exists: the Markdown file, its meta, action item and decision sidecars,
+/// its attachments, its recordings and its history.
+pub(crate) fn note_paths(dir: &Path, id: &NoteId) -> Vec<PathBuf> {
+    vec![
+        dir.join(format!("{}.md", id)),
+        dir.join(format!("{}.meta.json", id)),
+        actions::sidecar_path(dir, id),
+        decisions::sidecar_path(dir, id),
+        attachments::assets_dir(dir, id),
+        dir.join("recordings").join(id),
+        history::history_dir(dir, id),
+    ]
+}
+
+/// The directories that hold the notes of `folder`, their recordings and their
+/// history, mirroring `note_paths`.
+pub(crate) fn folder_dirs(dir: &Path, folder: &FolderPath) -> Vec<PathBuf> {
+    vec![
+        dir.join(folder),
+        dir.join("recordings").join(folder),
+        dir.join(history::HISTORY_DIR).join(folder),
+    ]
+}
+
+/// The `note_paths` of note `id` that exist.
+pub(crate) fn note_files(dir: &Path, id: &NoteId) -> Vec<PathBuf> {
+    note_paths(dir, id)
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// The day a meeting took place, from its note's creation time.
+pub(crate) fn meeting_day(meta: &NoteMeta) -> chrono::NaiveDate {
+    chrono::DateTime::parse_from_rfc3339(&meta.created_at)
+        .map(|t| t.with_timezone(&chrono::Local).date_naive())
+        .unwrap_or_else(|_| chrono::Local::now().date_naive())
+}
+
+/// Lowercase ASCII words of `text` joined by dashes, e.g. `product-kickoff`.
+pub(crate) fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A `<date>-<slug>` id in `folder` not taken by any note or folder, trashed
+/// files aside. `current` is the note being renamed, whose own id counts as
+/// free.
+fn unique_note_id(
+    dir: &Path,
+    folder: Option<&FolderPath>,
+    date: chrono::NaiveDate,
+    title: &str,
+    current: Option<&NoteId>,
+) -> Result<NoteId, String> {
+    let mut slug = slugify(title);
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+        slug.truncate(slug.trim_end_matches('-').len());
+    }
+    if slug.is_empty() {
+        slug = "untitled".to_string();
     }
+    let base = format!("{}-{slug}", date.format("%Y-%m-%d"));
 
-    Ok(())
+    let mut id = NoteId::in_folder(folder, &base)?;
+    let mut n = 2;
+    while Some(&id) != current && name_taken(dir, &id) {
+        id = NoteId::in_folder(folder, &format!("{base}-{n}"))?;
+        n += 1;
+    }
+    Ok(id)
+}
+
+/// Whether note `id` exists or a folder of the same name does. The two would
+/// share `recordings/<id>` and `.history/<id>`, so they cannot coexist.
+pub(crate) fn name_taken(dir: &Path, id: &NoteId) -> bool {
+    !note_files(dir, id).is_empty() || dir.join(id).is_dir()
+}
+
+/// Ids of every note (`*.md` file) in `dir` and its folders.
+pub(crate) fn note_ids(dir: &Path) -> Vec<NoteId> {
+    let mut ids = Vec::new();
+    walk_library(dir, &mut |path, is_dir| {
+        if !is_dir {
+            if let Some(id) = path.strip_suffix(".md") {
+                ids.extend(NoteId::parse(id).ok());
+            }
+        }
+    });
+    ids
+}
+
+/// Every folder in `dir`, parents before their children.
+pub(crate) fn folder_paths(dir: &Path) -> Vec<FolderPath> {
+    let mut folders = Vec::new();
+    walk_library(dir, &mut |path, is_dir| {
+        if is_dir {
+            folders.extend(FolderPath::parse(path).ok());
+        }
+    });
+    folders
+}
+
+/// Call `visit` with the `/`-separated relative path of every file and folder
+/// below `root`, skipping hidden entries, the app's reserved folders and
+/// notes' attachment directories.
+fn walk_library(root: &Path, visit: &mut dyn FnMut(&str, bool)) {
+    fn walk(dir: &Path, prefix: &str, visit: &mut dyn FnMut(&str, bool)) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') || (prefix.is_empty() && RESERVED_FOLDERS.contains(&&*name)) {
+                continue;
+            }
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir && path.ends_with(ASSETS_SUFFIX) {
+                continue;
+            }
+            visit(&path, is_dir);
+            if is_dir {
+                walk(&entry.path(), &path, visit);
+            }
+        }
+    }
+    walk(root, "", visit);
 }
 
-fn default_meta(id: &str) -> NoteMeta {
+fn default_meta(id: &NoteId) -> NoteMeta {
     NoteMeta {
-        title: id.to_string(),
+        title: id.name().to_string(),
         participants: vec![],
         tags: vec![],
         created_at: chrono::Utc::now().to_rfc3339(),
         updated_at: chrono::Utc::now().to_rfc3339(),
+        series: None,
+        extra: BTreeMap::new(),
+    }
+}
+
+// ── Storage layouts ──────────────────────────────────────────────────────────
+
+/// Split `---\n<yaml>---\n<body>` into its YAML and body.
+pub(crate) fn split_frontmatter(raw: &str) -> Option<(&str, &str)> {
+    let rest = raw
+        .strip_prefix("---\n")
+        .or_else(|| raw.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The meta and body of a note written with frontmatter. A leading block that
+/// is not a YAML mapping (a pair of horizontal rules, say) is part of the body.
+fn parse_frontmatter(raw: &str) -> Option<(NoteMeta, &str)> {
+    let (yaml, body) = split_frontmatter(raw)?;
+    let value: serde_yaml::Value = serde_yaml::from_str(yaml).ok()?;
+    if !value.is_mapping() {
+        return None;
+    }
+    let meta = serde_yaml::from_value(value).ok()?;
+    Some((meta, body))
+}
+
+/// Frontmatter written by other tools may lack our fields.
+fn fill_missing_meta(meta: &mut NoteMeta, id: &NoteId) {
+    let defaults = default_meta(id);
+    if meta.title.is_empty() {
+        meta.title = defaults.title;
+    }
+    if meta.created_at.is_empty() {
+        meta.created_at = defaults.created_at;
+    }
+    if meta.updated_at.is_empty() {
+        meta.updated_at = meta.created_at.clone();
+    }
+}
+
+/// The layout note `id` is stored in, or `None` if it has no metadata yet.
+fn storage_of(dir: &Path, id: &NoteId) -> Result<Option<NoteStorage>, String> {
+    let _guard = atomic::read_guard();
+    if dir.join(format!("{}.meta.json", id)).exists() {
+        return Ok(Some(NoteStorage::Sidecar));
+    }
+    let raw = fs::read_to_string(dir.join(format!("{}.md", id))).map_err(|e| e.to_string())?;
+    Ok(parse_frontmatter(&raw).map(|_| NoteStorage::Frontmatter))
+}
+
+/// The files that store a note in `storage` layout, or encrypted in a single
+/// `<id>.md`, and its resulting revision.
+fn encode_note(
+    dir: &Path,
+    id: &NoteId,
+    content: &str,
+    meta: &NoteMeta,
+    storage: NoteStorage,
+    encrypted: bool,
+) -> Result<(FileWrites, String), String> {
+    let md_path = dir.join(format!("{}.md", id));
+    if encrypted {
+        let sealed = encryption::seal_note(dir, id, content, meta)?;
+        let revision = revision_of(sealed.as_bytes(), b"");
+        return Ok((vec![(md_path, sealed.into_bytes())], revision));
+    }
+    match storage {
+        NoteStorage::Sidecar => {
+            let meta_json = serde_json::to_string_pretty(meta).map_err(|e| e.to_string())?;
+            let revision = revision_of(content.as_bytes(), meta_json.as_bytes());
+            let meta_path = dir.join(format!("{}.meta.json", id));
+            Ok((
+                vec![
+                    (md_path, content.as_bytes().to_vec()),
+                    (meta_path, meta_json.into_bytes()),
+                ],
+                revision,
+            ))
+        }
+        NoteStorage::Frontmatter => {
+            let yaml = serde_yaml::to_string(meta).map_err(|e| e.to_string())?;
+            let markdown = format!("---\n{yaml}---\n{content}");
+            let revision = revision_of(markdown.as_bytes(), b"");
+            Ok((vec![(md_path, markdown.into_bytes())], revision))
+        }
+    }
+}
+
+/// Commit `files` for note `id`. In frontmatter layout the meta sidecar is
+/// removed in the same commit, since it would otherwise take precedence. An
+/// encrypted note loses its plaintext meta and action item sidecars.
+fn commit_note_files(
+    dir: &Path,
+    watcher: &WatcherState,
+    id: &NoteId,
+    files: &[(PathBuf, Vec<u8>)],
+    storage: NoteStorage,
+    encrypted: bool,
+) -> Result<(), String> {
+    let meta_path = dir.join(format!("{}.meta.json", id));
+    let mut remove = if storage == NoteStorage::Frontmatter || encrypted {
+        vec![meta_path]
+    } else {
+        vec![]
+    };
+    if encrypted {
+        remove.push(actions::sidecar_path(dir, id));
+    }
+    remove.retain(|path| path.exists());
+    for path in &remove {
+        watcher.record_delete(id, path);
+    }
+    if let Some(folder) = id.folder() {
+        fs::create_dir_all(dir.join(folder)).map_err(|e| e.to_string())?;
+    }
+    atomic::write_files(dir, files, &remove).map_err(|e| e.to_string())?;
+    for (path, _) in files {
+        watcher.record_write(id, path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_migration_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let watcher = WatcherState::default();
+        let id = NoteId::parse("2026-03-01-kickoff").unwrap();
+        // Edge cases for frontmatter: a leading rule, CRLF, no final newline.
+        let content = "---\n\n# Kickoff  \r\n- [ ] send notes\n\n---\ntrailing";
+        let meta: NoteMeta = serde_json::from_value(serde_json::json!({
+            "title": "Kickoff: \"Q3\" #1",
+            "participants": ["Ana", "Sam"],
+            "tags": ["launch"],
+            "created_at": "2026-03-01T09:00:00Z",
+            "updated_at": "2026-03-01T10:30:00Z",
+            "series": { "series_id": "kickoff", "occurrence": "2026-03-01" },
+            "aliases": ["kick-off", "2026-03-01"],
+            "rating": 4.5,
+            "draft": false,
+            "review": { "by": "Ana", "notes": null },
+        }))
+        .unwrap();
+        let (files, _) =
+            encode_note(dir, &id, content, &meta, NoteStorage::Sidecar, false).unwrap();
+        commit_note_files(dir, &watcher, &id, &files, NoteStorage::Sidecar, false).unwrap();
+        let md_path = dir.join(format!("{id}.md"));
+        let meta_path = dir.join(format!("{id}.meta.json"));
+        let original_meta = fs::read(&meta_path).unwrap();
+
+        assert_eq!(
+            convert_storage(dir, &watcher, NoteStorage::Frontmatter),
+            Ok(1)
+        );
+        assert!(!meta_path.exists());
+        let note = load_note(dir, &id).unwrap();
+        assert_eq!(note.content, content);
+        assert_eq!(
+            serde_json::to_value(&note.meta).unwrap(),
+            serde_json::to_value(&meta).unwrap()
+        );
+        assert_eq!(
+            convert_storage(dir, &watcher, NoteStorage::Frontmatter),
+            Ok(0)
+        );
+
+        assert_eq!(convert_storage(dir, &watcher, NoteStorage::Sidecar), Ok(1));
+        assert_eq!(fs::read(&md_path).unwrap(), content.as_bytes());
+        assert_eq!(fs::read(&meta_path).unwrap(), original_meta);
+    }
+
+    #[test]
+    fn obsidian_frontmatter_is_read() {
+        let raw = "---
+title: 2024
+tags: meeting, weekly
+participants: Ana
+aliases:
+  - Kickoff
+created: 2024-01-05
+cssclasses: wide
+---
+# Notes
+";
+        let (meta, body) = parse_frontmatter(raw).unwrap();
+        assert_eq!(meta.title, "2024");
+        assert_eq!(meta.tags, ["meeting", "weekly"]);
+        assert_eq!(meta.participants, ["Ana"]);
+        assert_eq!(meta.extra["aliases"], serde_json::json!(["Kickoff"]));
+        assert_eq!(meta.extra["created"], "2024-01-05");
+        assert_eq!(body, "# Notes\n");
+
+        let raw =
+            "---\ntags:\n  - '#project/alpha'\n  - 2024\nparticipants: [Ana, Sam, ~]\n---\nBody";
+        let (meta, body) = parse_frontmatter(raw).unwrap();
+        assert_eq!(meta.tags, ["project/alpha", "2024"]);
+        assert_eq!(meta.participants, ["Ana", "Sam"]);
+        assert_eq!(meta.title, "");
+        assert_eq!(body, "Body");
+    }
+
+    #[test]
+    fn obsidian_notes_keep_one_frontmatter_block() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let id = NoteId::parse("2024-01-05-standup").unwrap();
+        let raw = "---\ntitle: 2024\ntags: meeting\n---\nBody\n";
+        fs::write(dir.join(format!("{id}.md")), raw).unwrap();
+
+        let note = load_note(dir, &id).unwrap();
+        assert_eq!(note.content, "Body\n");
+        let (files, _) = encode_note(
+            dir,
+            &id,
+            &note.content,
+            &note.meta,
+            NoteStorage::Frontmatter,
+            false,
+        )
+        .unwrap();
+        let written = String::from_utf8(files[0].1.clone()).unwrap();
+        assert_eq!(written.matches("---\n").count(), 2, "{written}");
+        assert!(written.ends_with("---\nBody\n"));
     }
 }
@@ -0,0 +1,500 @@
+//! People directory: who the names in `NoteMeta.participants` refer to. Each
+//! person has aliases and an e-mail address that are resolved to their name
+//! whenever a note is saved, so "Ana", "Ana S." and "ana@corp.com" end up as
+//! one participant. Stored in `<library>/.people.json`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::actions::{self, ActionStatus, NoteActionItem};
+use super::atomic;
+use super::catalog::{CatalogState, UsageCount};
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes;
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+const PEOPLE_FILE: &str = ".people.json";
+
+/// Serialises read-modify-write cycles on the people file.
+static PEOPLE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Person {
+    /// Derived from the name when the person is added; never changes.
+    pub person_id: String,
+    pub name: String,
+    /// Other names the person appears under in notes.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub email: Option<String>,
+    pub team: Option<String>,
+    /// Free-form notes about the person.
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Fields a caller supplies when adding or editing a person.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct PersonInput {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub email: Option<String>,
+    pub team: Option<String>,
+    pub notes: String,
+}
+
+/// A meeting a person attended.
+#[derive(Serialize, Debug)]
+pub struct Meeting {
+    pub note_id: NoteId,
+    pub title: String,
+    /// Day of the meeting, as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+/// Everything the library knows about a person, for 1:1 prep.
+#[derive(Serialize, Debug)]
+pub struct PersonView {
+    pub person: Person,
+    /// Newest first.
+    pub meetings: Vec<Meeting>,
+    /// Open items owned by the person, by due date.
+    pub open_actions: Vec<NoteActionItem>,
+}
+
+impl Person {
+    /// Whether `name` (a participant, `@mention` or action item owner) refers
+    /// to this person.
+    fn answers_to(&self, name: &str) -> bool {
+        let name = name.trim().trim_start_matches('@');
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .chain(&self.email)
+            .any(|known| known.trim().eq_ignore_ascii_case(name))
+    }
+}
+
+fn people_path(dir: &Path) -> PathBuf {
+    dir.join(PEOPLE_FILE)
+}
+
+/// The people of the library in `dir`; empty if there is no directory yet.
+/// Fails if the file is unreadable, so it is not mistaken for an empty
+/// directory and overwritten.
+pub(crate) fn load(dir: &Path) -> Result<Vec<Person>, String> {
+    match fs::read(people_path(dir)) {
+        Ok(raw) => serde_json::from_slice(&raw).map_err(|e| format!("Invalid {PEOPLE_FILE}: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn save(dir: &Path, people: &[Person]) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(people).map_err(|e| e.to_string())?;
+    atomic::write_file(&people_path(dir), &json).map_err(|e| e.to_string())
+}
+
+/// Apply `f` to the people of the library in `dir` and save them.
+fn update_people<T>(
+    dir: &Path,
+    f: impl FnOnce(&mut Vec<Person>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = PEOPLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut people = load(dir)?;
+    let result = f(&mut people)?;
+    save(dir, &people)?;
+    Ok(result)
+}
+
+/// The name of the person `name` refers to, or `name` itself if nobody in
+/// `people` answers to it.
+pub(crate) fn canonical(people: &[Person], name: &str) -> String {
+    people
+        .iter()
+        .find(|person| person.answers_to(name))
+        .map(|person| person.name.clone())
+        .unwrap_or_else(|| name.trim().to_string())
+}
+
+fn find<'a>(people: &'a mut [Person], person_id: &str) -> Result<&'a mut Person, String> {
+    people
+        .iter_mut()
+        .find(|p| p.person_id == person_id)
+        .ok_or_else(|| format!("No person {person_id}"))
+}
+
+/// Trim `input` and check none of its names is taken by someone other than
+/// `person_id`.
+fn validate(
+    people: &[Person],
+    person_id: Option<&str>,
+    input: PersonInput,
+) -> Result<PersonInput, String> {
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in input.aliases {
+        let alias = alias.trim().trim_start_matches('@').to_string();
+        if !alias.is_empty() && !aliases.iter().any(|a| a.eq_ignore_ascii_case(&alias)) {
+            aliases.push(alias);
+        }
+    }
+    let input = PersonInput {
+        name: input.name.trim().to_string(),
+        aliases,
+        email: non_empty(input.email),
+        team: non_empty(input.team),
+        notes: input.notes.trim().to_string(),
+    };
+    if input.name.is_empty() {
+        return Err("A person needs a name".into());
+    }
+
+    let names = std::iter::once(&input.name)
+        .chain(&input.aliases)
+        .chain(&input.email);
+    for name in names {
+        if let Some(other) = people
+            .iter()
+            .find(|p| Some(p.person_id.as_str()) != person_id && p.answers_to(name))
+        {
+            return Err(format!("{name} already refers to {}", other.name));
+        }
+    }
+    Ok(input)
+}
+
+/// The work behind `merge_people`: fold `duplicates` into `person_id` within
+/// `people`.
+fn merge(
+    people: &mut Vec<Person>,
+    person_id: &str,
+    duplicates: &[String],
+) -> Result<Person, String> {
+    if duplicates.iter().any(|d| d == person_id) {
+        return Err("Cannot merge a person into themselves".into());
+    }
+    let mut removed = Vec::new();
+    for duplicate in duplicates {
+        let at = people
+            .iter()
+            .position(|p| &p.person_id == duplicate)
+            .ok_or_else(|| format!("No person {duplicate}"))?;
+        removed.push(people.remove(at));
+    }
+    let person = find(people, person_id)?;
+    for other in removed {
+        for name in std::iter::once(other.name).chain(other.aliases) {
+            if !person.answers_to(&name) {
+                person.aliases.push(name);
+            }
+        }
+        match (&person.email, other.email) {
+            (None, email) => person.email = email,
+            (Some(_), Some(email)) if !person.answers_to(&email) => person.aliases.push(email),
+            _ => {}
+        }
+        person.team = person.team.take().or(other.team);
+        if !other.notes.is_empty() {
+            if !person.notes.is_empty() {
+                person.notes.push_str("\n\n");
+            }
+            person.notes.push_str(&other.notes);
+        }
+    }
+    Ok(person.clone())
+}
+
+fn unique_person_id(people: &[Person], name: &str) -> String {
+    let mut base = notes::slugify(name);
+    if base.is_empty() {
+        base = "person".to_string();
+    }
+    let mut id = base.clone();
+    let mut n = 2;
+    while people.iter().any(|p| p.person_id == id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    id
+}
+
+/// Resolve the participants of every note against the directory, saving the
+/// notes where a name changes.
+fn apply_to_notes(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+) -> Result<(), String> {
+    let dir = lib.notes_dir()?;
+    let _lock = notes::write_lock();
+    let people = load(&dir)?;
+
+    for id in notes::note_ids(&dir) {
+        let edited = notes::edit_note_locked(lib, search, catalog, watcher, &id, |note| {
+            let mut participants: Vec<String> = Vec::new();
+            for name in &note.meta.participants {
+                let name = canonical(&people, name);
+                if !participants.iter().any(|p| p.eq_ignore_ascii_case(&name)) {
+                    participants.push(name);
+                }
+            }
+            if participants == note.meta.participants {
+                return false;
+            }
+            note.meta.participants = participants;
+            true
+        });
+        if let Err(e) = edited {
+            eprintln!("Failed to update participants of {id}: {e}");
+        }
+    }
+    Ok(())
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Everyone in the directory, by name.
+#[tauri::command]
+pub fn list_people(library: tauri::State<LibraryState>) -> Result<Vec<Person>, String> {
+    let mut people = load(&library.notes_dir()?)?;
+    people.sort_by_key(|p| p.name.to_lowercase());
+    Ok(people)
+}
+
+/// Participants of notes that nobody in the directory answers to, most
+/// frequent first. Candidates for `add_person` or an alias.
+#[tauri::command]
+pub fn list_unknown_participants(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<Vec<UsageCount>, String> {
+    let lib = library.active()?;
+    let people = load(&lib.notes_dir()?)?;
+    let mut counts = catalog.participant_counts(&lib)?;
+    counts.retain(|count| !people.iter().any(|p| p.answers_to(&count.name)));
+    Ok(counts)
+}
+
+/// Add a person and resolve their names in existing notes.
+#[tauri::command]
+pub fn add_person(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    person: PersonInput,
+) -> Result<Person, String> {
+    let lib = library.active()?;
+    let person = update_people(&lib.notes_dir()?, |people| {
+        let input = validate(people, None, person)?;
+        let person = Person {
+            person_id: unique_person_id(people, &input.name),
+            name: input.name,
+            aliases: input.aliases,
+            email: input.email,
+            team: input.team,
+            notes: input.notes,
+        };
+        people.push(person.clone());
+        Ok(person)
+    })?;
+    apply_to_notes(&lib, &search, &catalog, &watcher)?;
+    Ok(person)
+}
+
+/// Replace a person's details. Notes are updated to their new name.
+#[tauri::command]
+pub fn update_person(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    person_id: String,
+    person: PersonInput,
+) -> Result<Person, String> {
+    let lib = library.active()?;
+    let updated = update_people(&lib.notes_dir()?, |people| {
+        let input = validate(people, Some(&person_id), person)?;
+        let existing = find(people, &person_id)?;
+        // Notes still say the old name until they are rewritten below.
+        let mut aliases = input.aliases;
+        if !existing.name.eq_ignore_ascii_case(&input.name)
+            && !aliases
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(&existing.name))
+        {
+            aliases.push(existing.name.clone());
+        }
+        existing.name = input.name;
+        existing.aliases = aliases;
+        existing.email = input.email;
+        existing.team = input.team;
+        existing.notes = input.notes;
+        Ok(existing.clone())
+    })?;
+    apply_to_notes(&lib, &search, &catalog, &watcher)?;
+    Ok(updated)
+}
+
+/// Remove a person from the directory. Notes keep the name as it is.
+#[tauri::command]
+pub fn remove_person(library: tauri::State<LibraryState>, person_id: String) -> Result<(), String> {
+    update_people(&library.notes_dir()?, |people| {
+        let before = people.len();
+        people.retain(|p| p.person_id != person_id);
+        if people.len() == before {
+            return Err(format!("No person {person_id}"));
+        }
+        Ok(())
+    })
+}
+
+/// Fold `duplicates` into `person_id`: their names become aliases, missing
+/// details are filled in from them, and notes are updated to the one name.
+#[tauri::command]
+pub fn merge_people(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    person_id: String,
+    duplicates: Vec<String>,
+) -> Result<Person, String> {
+    let lib = library.active()?;
+    let merged = update_people(&lib.notes_dir()?, |people| {
+        merge(people, &person_id, &duplicates)
+    })?;
+    apply_to_notes(&lib, &search, &catalog, &watcher)?;
+    Ok(merged)
+}
+
+/// A person with the meetings they attended and their open action items.
+#[tauri::command]
+pub fn get_person(
+    library: tauri::State<LibraryState>,
+    person_id: String,
+) -> Result<PersonView, String> {
+    let dir = library.notes_dir()?;
+    let mut people = load(&dir)?;
+    let person = find(&mut people, &person_id)?.clone();
+    let mut meetings = Vec::new();
+    let mut open_actions = Vec::new();
+
+    for id in notes::note_ids(&dir) {
+        let Ok(note) = notes::load_note(&dir, &id) else {
+            continue;
+        };
+        if note.meta.participants.iter().any(|p| person.answers_to(p)) {
+            meetings.push(Meeting {
+                note_id: id.clone(),
+                title: note.meta.title.clone(),
+                date: notes::meeting_day(&note.meta)
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            });
+        }
+        for item in actions::note_items(&dir, &note) {
+            let theirs = item.owner.as_deref().is_some_and(|o| person.answers_to(o));
+            if theirs && item.status == ActionStatus::Open {
+                open_actions.push(NoteActionItem {
+                    item,
+                    note_id: id.clone(),
+                    note_title: note.meta.title.clone(),
+                });
+            }
+        }
+    }
+
+    meetings.sort_by(|a, b| (&b.date, &b.note_id).cmp(&(&a.date, &a.note_id)));
+    open_actions.sort_by(|a, b| {
+        let due = |i: &NoteActionItem| (i.item.due.is_none(), i.item.due.clone());
+        due(a).cmp(&due(b))
+    });
+    Ok(PersonView {
+        person,
+        meetings,
+        open_actions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(person_id: &str, name: &str, aliases: &[&str], email: Option<&str>) -> Person {
+        Person {
+            person_id: person_id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            email: email.map(str::to_string),
+            team: None,
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn names_resolve_to_the_person() {
+        let people = [person(
+            "ana",
+            "Ana Silva",
+            &["Ana S."],
+            Some("ana@corp.com"),
+        )];
+        assert_eq!(canonical(&people, "ana s."), "Ana Silva");
+        assert_eq!(canonical(&people, "@Ana@corp.com"), "Ana Silva");
+        assert_eq!(canonical(&people, " ANA SILVA "), "Ana Silva");
+        assert_eq!(canonical(&people, " Sam "), "Sam");
+    }
+
+    #[test]
+    fn duplicates_fold_into_one_person() {
+        let mut people = vec![
+            person("ana", "Ana Silva", &[], Some("ana@corp.com")),
+            person("ana-2", "Ana S.", &["Ana"], Some("ana@home.org")),
+            person("sam", "Sam", &[], None),
+        ];
+        people[1].team = Some("Design".to_string());
+        people[1].notes = "Prefers mornings".to_string();
+
+        let merged = merge(&mut people, "ana", &["ana-2".to_string()]).unwrap();
+        assert_eq!(merged.aliases, ["Ana S.", "Ana", "ana@home.org"]);
+        assert_eq!(merged.email.as_deref(), Some("ana@corp.com"));
+        assert_eq!(merged.team.as_deref(), Some("Design"));
+        assert_eq!(merged.notes, "Prefers mornings");
+        assert_eq!(people.len(), 2);
+        assert_eq!(canonical(&people, "ana@home.org"), "Ana Silva");
+
+        assert!(merge(&mut people, "ana", &["ana".to_string()]).is_err());
+        assert!(merge(&mut people, "ana", &["nobody".to_string()]).is_err());
+        assert_eq!(people.len(), 2);
+    }
+
+    #[test]
+    fn corrupt_directories_are_not_overwritten() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(load(tmp.path()).unwrap().is_empty());
+        let conflicted = "[{\"person_id\": \"ana\", <<<<<<< ours";
+        fs::write(people_path(tmp.path()), conflicted).unwrap();
+
+        assert!(load(tmp.path()).is_err());
+        let added = update_people(tmp.path(), |people| {
+            people.push(person("sam", "Sam", &[], None));
+            Ok(())
+        });
+        assert!(added.is_err());
+        assert_eq!(
+            fs::read_to_string(people_path(tmp.path())).unwrap(),
+            conflicted
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{AllQuery, QueryParser};
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+use super::encryption;
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes::{self, Note};
+
+const WRITER_HEAP_BYTES: usize = 20_000_000;
+const SNIPPET_CHARS: usize = 160;
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Serialize, Debug)]
+pub struct SearchHit {
+    pub id: String,
+    pub title: String,
+    pub score: f32,
+    /// HTML fragment of the body with matched terms wrapped in `<b>`.
+    pub snippet: String,
+    pub tags: Vec<String>,
+    pub updated_at: String,
+}
+
+// ── Index ────────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+struct Fields {
+    id: Field,
+    title: Field,
+    body: Field,
+    tags: Field,
+    participants: Field,
+    updated_at: Field,
+    mtime: Field,
+}
+
+fn build_schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        id: builder.add_text_field("id", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        body: builder.add_text_field("body", TEXT | STORED),
+        tags: builder.add_text_field("tags", TEXT | STORED),
+        participants: builder.add_text_field("participants", TEXT | STORED),
+        updated_at: builder.add_text_field("updated_at", STORED),
+        mtime: builder.add_u64_field("mtime", STORED),
+    };
+    (builder.build(), fields)
+}
+
+struct SearchIndex {
+    notes_dir: PathBuf,
+    index: Index,
+    reader: IndexReader,
+    writer: IndexWriter,
+    fields: Fields,
+}
+
+impl SearchIndex {
+    /// Open the on-disk index for `library`, recreating it if it is missing or
+    /// was written with an incompatible schema, then bring it up to date.
+    fn open(library: &Library) -> Result<Self, String> {
+        let notes_dir = library.notes_dir()?;
+        let path = library.cache_dir()?.join("search-index");
+        let (schema, fields) = build_schema();
+
+        let index = match open_index(&path, schema.clone()) {
+            Ok(index) => index,
+            Err(e) => {
+                eprintln!("Recreating search index: {e}");
+                let _ = fs::remove_dir_all(&path);
+                open_index(&path, schema)?
+            }
+        };
+
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e| e.to_string())?;
+        let writer = index
+            .writer_with_num_threads(1, WRITER_HEAP_BYTES)
+            .map_err(|e| e.to_string())?;
+
+        let mut search = SearchIndex {
+            notes_dir,
+            index,
+            reader,
+            writer,
+            fields,
+        };
+        search.reconcile()?;
+        Ok(search)
+    }
+
+    /// Reindex notes changed on disk since they were last indexed and drop
+    /// entries for notes that no longer exist.
+    fn reconcile(&mut self) -> Result<(), String> {
+        let searcher = self.reader.searcher();
+        let addresses = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .map_err(|e| e.to_string())?;
+
+        let mut indexed: HashMap<String, u64> = HashMap::new();
+        for address in addresses {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            let id = text_value(&doc, self.fields.id);
+            let mtime = doc
+                .get_first(self.fields.mtime)
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            indexed.insert(id, mtime);
+        }
+
+        let on_disk: HashSet<NoteId> = notes::note_ids(&self.notes_dir).into_iter().collect();
+        let mut changed = false;
+
+        for id in indexed.keys().filter(|id| !on_disk.contains(id.as_str())) {
+            self.writer
+                .delete_term(Term::from_field_text(self.fields.id, id));
+            changed = true;
+        }
+
+        for id in &on_disk {
+            let mtime = note_mtime(&self.notes_dir, id);
+            if indexed.get(id.as_str()) == Some(&mtime) {
+                continue;
+            }
+            if encryption::is_encrypted(&self.notes_dir, id) {
+                if indexed.contains_key(id.as_str()) {
+                    self.writer
+                        .delete_term(Term::from_field_text(self.fields.id, id));
+                    changed = true;
+                }
+                continue;
+            }
+            match notes::load_note(&self.notes_dir, id) {
+                Ok(note) => {
+                    self.stage(&note, mtime)?;
+                    changed = true;
+                }
+                Err(e) => eprintln!("Skipping unreadable note {id}: {e}"),
+            }
+        }
+
+        if changed {
+            self.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the entry of `note`. Encrypted notes are only removed, never
+    /// indexed, as the index is stored in plaintext.
+    fn stage(&mut self, note: &Note, mtime: u64) -> Result<(), String> {
+        let f = self.fields;
+        self.writer
+            .delete_term(Term::from_field_text(f.id, &note.id));
+        if note.encrypted {
+            return Ok(());
+        }
+        self.writer
+            .add_document(doc!(
+                f.id => note.id.as_str(),
+                f.title => note.meta.title.as_str(),
+                f.body => note.content.as_str(),
+                f.tags => note.meta.tags.join(" "),
+                f.participants => note.meta.participants.join(" "),
+                f.updated_at => note.meta.updated_at.as_str(),
+                f.mtime => mtime,
+            ))
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), String> {
+        self.writer.commit().map_err(|e| e.to_string())?;
+        self.reader.reload().map_err(|e| e.to_string())
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let f = self.fields;
+        let mut parser =
+            QueryParser::for_index(&self.index, vec![f.title, f.body, f.tags, f.participants]);
+        parser.set_field_boost(f.title, 3.0);
+        parser.set_field_boost(f.tags, 2.0);
+        parser.set_field_boost(f.participants, 2.0);
+        // User input is free-form; ignore syntax errors rather than failing the search.
+        let (query, _errors) = parser.parse_query_lenient(query);
+
+        let searcher = self.reader.searcher();
+        let top = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(|e| e.to_string())?;
+
+        let mut snippets =
+            SnippetGenerator::create(&searcher, &*query, f.body).map_err(|e| e.to_string())?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            let snippet = snippets.snippet_from_doc(&doc);
+            let snippet = if snippet.fragment().is_empty() {
+                // The match was in the title/tags only; show the start of the body.
+                let body = text_value(&doc, f.body);
+                escape_html(&body.chars().take(SNIPPET_CHARS).collect::<String>())
+            } else {
+                snippet.to_html()
+            };
+
+            hits.push(SearchHit {
+                id: text_value(&doc, f.id),
+                title: text_value(&doc, f.title),
+                score,
+                snippet,
+                tags: text_value(&doc, f.tags)
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+                updated_at: text_value(&doc, f.updated_at),
+            });
+        }
+        Ok(hits)
+    }
+}
+
+fn open_index(path: &Path, schema: Schema) -> Result<Index, String> {
+    fs::create_dir_all(path).map_err(|e| e.to_string())?;
+    let dir = MmapDirectory::open(path).map_err(|e| e.to_string())?;
+    Index::open_or_create(dir, schema).map_err(|e| e.to_string())
+}
+
+/// Modification time of a note's Markdown file, in milliseconds since the epoch.
+fn note_mtime(dir: &Path, id: &str) -> u64 {
+    fs::metadata(dir.join(format!("{}.md", id)))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn text_value(doc: &TantivyDocument, field: Field) -> String {
+    doc.get_first(field)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// ── Search state ─────────────────────────────────────────────────────────────
+
+/// Lazily opened search index shared by the note commands.
+pub struct SearchState(Mutex<Option<SearchIndex>>);
+
+impl Default for SearchState {
+    fn default() -> Self {
+        SearchState(Mutex::new(None))
+    }
+}
+
+impl SearchState {
+    /// Run `f` against the index of `library`, (re)opening it if the active
+    /// library changed since the last call.
+    fn with_index<T>(
+        &self,
+        library: &Library,
+        f: impl FnOnce(&mut SearchIndex) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut guard = self.0.lock().map_err(|e| e.to_string())?;
+        if guard.as_ref().map(|idx| idx.notes_dir.as_path()) != Some(library.root().as_path()) {
+            // Drop the old writer first so its directory lock is released.
+            *guard = None;
+            *guard = Some(SearchIndex::open(library)?);
+        }
+        f(guard.as_mut().unwrap())
+    }
+
+    /// Add or replace a single note in the index.
+    pub fn index_note(&self, library: &Library, note: &Note) -> Result<(), String> {
+        self.with_index(library, |idx| {
+            let mtime = note_mtime(&idx.notes_dir, &note.id);
+            idx.stage(note, mtime)?;
+            idx.commit()
+        })
+    }
+
+    /// Remove a single note from the index.
+    pub fn remove_note(&self, library: &Library, id: &str) -> Result<(), String> {
+        self.with_index(library, |idx| {
+            idx.writer
+                .delete_term(Term::from_field_text(idx.fields.id, id));
+            idx.commit()
+        })
+    }
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn search_notes(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    search.with_index(&library.active()?, |idx| {
+        idx.search(&query, limit.unwrap_or(DEFAULT_LIMIT))
+    })
+}
+
+/// Throw away the index and rebuild it from the notes on disk.
+#[tauri::command]
+pub fn rebuild_search_index(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+) -> Result<(), String> {
+    search.with_index(&library.active()?, |idx| {
+        idx.writer
+            .delete_all_documents()
+            .map_err(|e| e.to_string())?;
+        idx.commit()?;
+        idx.reconcile()
+    })
+}
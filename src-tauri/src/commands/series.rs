@@ -0,0 +1,457 @@
+//! Meeting series: notes of a recurring meeting linked through
+//! `NoteMeta.series`, so each occurrence knows the one before and after it
+//! and unresolved action items can be carried forward.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::actions::{self, ActionStatus};
+use super::catalog::{CatalogState, Occurrence};
+use super::library::{Library, LibraryState};
+use super::note_id::NoteId;
+use super::notes::{self, Note, NoteMeta};
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+/// Month names and abbreviations, which vary between occurrences' titles.
+const MONTHS: &str = "jan feb mar apr may jun jul aug sep sept oct nov dec january february \
+                      march april june july august september october november december";
+
+/// Where a note sits in a series.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SeriesLink {
+    pub series_id: String,
+    /// Day of the occurrence, as `YYYY-MM-DD`.
+    pub occurrence: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Previous,
+    Next,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SeriesSummary {
+    pub series_id: String,
+    /// Title of the latest occurrence.
+    pub title: String,
+    pub count: usize,
+    pub first: String,
+    pub last: String,
+}
+
+/// Notes `infer_series` found to belong together.
+#[derive(Serialize, Debug)]
+pub struct InferredSeries {
+    pub series_id: String,
+    pub title: String,
+    /// Oldest first.
+    pub note_ids: Vec<NoteId>,
+}
+
+fn validate_series_id(series_id: &str) -> Result<(), String> {
+    let valid = !series_id.is_empty()
+        && series_id
+            .chars()
+            .all(|c| (c.is_alphanumeric() && !c.is_uppercase()) || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid series id: {series_id}"))
+    }
+}
+
+fn meeting_date(meta: &NoteMeta) -> String {
+    notes::meeting_day(meta).format("%Y-%m-%d").to_string()
+}
+
+/// Default titles of new and imported notes, which say nothing about a series.
+const PLACEHOLDER_TITLES: [&str; 4] = ["untitled", "untitled meeting", "untitled note", "new note"];
+
+/// What the titles of a series have in common: `Weekly sync 2026-03-02` and
+/// `Weekly sync – Mar 9th` both give `weekly-sync`. Empty for placeholder
+/// titles such as `Untitled Meeting`.
+fn series_key(title: &str) -> String {
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let lower = title.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| {
+            let number = ["st", "nd", "rd", "th"]
+                .iter()
+                .find_map(|suffix| word.strip_suffix(suffix))
+                .unwrap_or(word);
+            // `w12` and `q3` count weeks and quarters.
+            let period = word.starts_with(['w', 'q']) && digits(&word[1..]);
+            !word.is_empty()
+                && !digits(number)
+                && !period
+                && !MONTHS.split_whitespace().any(|m| m == *word)
+        })
+        .collect();
+    if PLACEHOLDER_TITLES.contains(&words.join(" ").as_str()) {
+        return String::new();
+    }
+    // Lowercase words rather than `notes::slugify`, which drops non-ASCII titles.
+    words.join("-")
+}
+
+/// The occurrence of `id`'s series before or after it.
+fn adjacent(
+    lib: &Library,
+    catalog: &CatalogState,
+    id: &NoteId,
+    direction: Direction,
+) -> Result<Option<Occurrence>, String> {
+    let note = notes::load_note(&lib.notes_dir()?, id)?;
+    let Some(link) = note.meta.series else {
+        return Ok(None);
+    };
+    let occurrences = catalog.occurrences(lib, Some(&link.series_id))?;
+    let Some(at) = occurrences.iter().position(|o| &o.note_id == id) else {
+        return Ok(None);
+    };
+    let neighbour = match direction {
+        Direction::Previous => at.checked_sub(1),
+        Direction::Next => Some(at + 1),
+    };
+    Ok(neighbour.and_then(|i| occurrences.get(i).cloned()))
+}
+
+/// Checklist entries for the open action items of `previous` that `content`
+/// does not have yet.
+fn carried_items(lib: &Library, previous: &Note, content: &str) -> Result<Vec<String>, String> {
+    let dir = lib.notes_dir()?;
+    let existing = actions::items_in(content);
+    Ok(actions::note_items(&dir, previous)
+        .into_iter()
+        .filter(|item| item.status == ActionStatus::Open)
+        .filter(|item| {
+            !existing
+                .iter()
+                .any(|e| e.text.eq_ignore_ascii_case(&item.text))
+        })
+        .map(|item| actions::checklist_line(&item))
+        .collect())
+}
+
+/// `content` with `items` appended under a heading.
+fn append_carried(content: &str, items: &[String]) -> String {
+    let mut content = content.to_string();
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str("## Carried over\n\n");
+    content.push_str(&items.join("\n"));
+    content.push('\n');
+    content
+}
+
+/// Set or clear the series of each note in `links`. Call with `write_lock`
+/// held.
+fn link_notes(
+    lib: &Library,
+    search: &SearchState,
+    catalog: &CatalogState,
+    watcher: &WatcherState,
+    links: Vec<(NoteId, Option<SeriesLink>)>,
+) -> Result<(), String> {
+    for (id, link) in links {
+        notes::edit_note_locked(lib, search, catalog, watcher, &id, |note| {
+            if note.meta.series == link {
+                return false;
+            }
+            note.meta.series = link;
+            true
+        })?;
+    }
+    Ok(())
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Every series in the library, by id.
+#[tauri::command]
+pub fn list_series(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<Vec<SeriesSummary>, String> {
+    let mut series: Vec<SeriesSummary> = Vec::new();
+    for occurrence in catalog.occurrences(&library.active()?, None)? {
+        match series.last_mut() {
+            Some(last) if last.series_id == occurrence.series_id => {
+                last.count += 1;
+                last.title = occurrence.title;
+                last.last = occurrence.occurrence;
+            }
+            _ => series.push(SeriesSummary {
+                series_id: occurrence.series_id,
+                title: occurrence.title,
+                count: 1,
+                first: occurrence.occurrence.clone(),
+                last: occurrence.occurrence,
+            }),
+        }
+    }
+    Ok(series)
+}
+
+/// The occurrences of a series, oldest first.
+#[tauri::command]
+pub fn get_series(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+    series_id: String,
+) -> Result<Vec<Occurrence>, String> {
+    catalog.occurrences(&library.active()?, Some(&series_id))
+}
+
+/// The occurrence before or after note `id` in its series, or `None` at either
+/// end or if the note is not in a series.
+#[tauri::command]
+pub fn get_adjacent_occurrence(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+    id: NoteId,
+    direction: Direction,
+) -> Result<Option<Occurrence>, String> {
+    adjacent(&library.active()?, &catalog, &id, direction)
+}
+
+/// Make note `id` an occurrence of `series_id` on `occurrence` (its meeting
+/// day if `None`), or with `series_id` `None` take it out of its series.
+#[tauri::command]
+pub fn set_note_series(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+    series_id: Option<String>,
+    occurrence: Option<String>,
+) -> Result<Option<SeriesLink>, String> {
+    let lib = library.active()?;
+    let note = notes::load_note(&lib.notes_dir()?, &id)?;
+    let link = match series_id {
+        Some(series_id) => {
+            validate_series_id(&series_id)?;
+            let occurrence = match occurrence {
+                Some(day) => chrono::NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .map_err(|_| format!("Invalid date {day:?}, expected YYYY-MM-DD"))?,
+                None => meeting_date(&note.meta),
+            };
+            Some(SeriesLink {
+                series_id,
+                occurrence,
+            })
+        }
+        None => None,
+    };
+
+    let _lock = notes::write_lock();
+    link_notes(&lib, &search, &catalog, &watcher, vec![(id, link.clone())])?;
+    Ok(link)
+}
+
+/// Append the open action items of the previous occurrence that note `id`
+/// does not have yet. Returns how many were added.
+#[tauri::command]
+pub fn carry_forward_action_items(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    id: NoteId,
+) -> Result<usize, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let previous = adjacent(&lib, &catalog, &id, Direction::Previous)?
+        .ok_or_else(|| format!("Note {id} has no previous occurrence"))?;
+    let previous = notes::load_note(&dir, &previous.note_id)?;
+
+    let _lock = notes::write_lock();
+    let mut added = Ok(0);
+    notes::edit_note_locked(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        &id,
+        |note| match carried_items(&lib, &previous, &note.content) {
+            Ok(items) if !items.is_empty() => {
+                note.content = append_carried(&note.content, &items);
+                note.meta.updated_at = chrono::Utc::now().to_rfc3339();
+                added = Ok(items.len());
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                added = Err(e);
+                false
+            }
+        },
+    )?;
+    added
+}
+
+/// Create today's occurrence of a series after its latest one: same folder,
+/// participants and tags, with the latest one's open action items carried
+/// forward. `title` defaults to the latest title with its dates updated.
+#[tauri::command]
+pub fn create_next_occurrence(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    series_id: String,
+    title: Option<String>,
+) -> Result<Note, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let latest = catalog
+        .occurrences(&lib, Some(&series_id))?
+        .pop()
+        .ok_or_else(|| format!("No series {series_id}"))?;
+    let latest = notes::load_note(&dir, &latest.note_id)?;
+
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| {
+            let previous_day = latest
+                .meta
+                .series
+                .as_ref()
+                .map_or_else(|| meeting_date(&latest.meta), |s| s.occurrence.clone());
+            latest.meta.title.replace(&previous_day, &today)
+        });
+    let items = carried_items(&lib, &latest, "")?;
+    let content = if items.is_empty() {
+        String::new()
+    } else {
+        append_carried("", &items)
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+    let meta = NoteMeta {
+        title,
+        participants: latest.meta.participants.clone(),
+        tags: latest.meta.tags.clone(),
+        created_at: now.clone(),
+        updated_at: now,
+        series: Some(SeriesLink {
+            series_id,
+            occurrence: today,
+        }),
+        extra: BTreeMap::new(),
+    };
+    notes::add_note(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        latest.id.folder().as_ref(),
+        content,
+        meta,
+    )
+}
+
+/// Group notes outside any series whose titles differ only in dates and
+/// numbers, e.g. `Weekly sync 2026-03-02` and `Weekly sync 2026-03-09`. With
+/// `apply`, link each group as a series, joining an existing series of the
+/// same name.
+#[tauri::command]
+pub fn infer_series(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    apply: bool,
+) -> Result<Vec<InferredSeries>, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let existing: Vec<String> = catalog
+        .occurrences(&lib, None)?
+        .into_iter()
+        .map(|o| o.series_id)
+        .collect();
+
+    let _lock = notes::write_lock();
+    let mut groups: BTreeMap<String, Vec<(String, NoteId, String)>> = BTreeMap::new();
+    for id in notes::note_ids(&dir) {
+        let Ok(note) = notes::load_note(&dir, &id) else {
+            continue;
+        };
+        let key = series_key(&note.meta.title);
+        if note.meta.series.is_none() && !key.is_empty() {
+            groups
+                .entry(key)
+                .or_default()
+                .push((meeting_date(&note.meta), id, note.meta.title));
+        }
+    }
+
+    let mut inferred = Vec::new();
+    for (series_id, mut members) in groups {
+        if members.len() < 2 && !existing.contains(&series_id) {
+            continue;
+        }
+        members.sort();
+        if apply {
+            let links = members
+                .iter()
+                .map(|(day, id, _)| {
+                    let link = SeriesLink {
+                        series_id: series_id.clone(),
+                        occurrence: day.clone(),
+                    };
+                    (id.clone(), Some(link))
+                })
+                .collect();
+            link_notes(&lib, &search, &catalog, &watcher, links)?;
+        }
+        inferred.push(InferredSeries {
+            title: members
+                .last()
+                .map(|(_, _, t)| t.clone())
+                .unwrap_or_default(),
+            note_ids: members.into_iter().map(|(_, id, _)| id).collect(),
+            series_id,
+        });
+    }
+    Ok(inferred)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_and_numbers_are_dropped_from_the_key() {
+        assert_eq!(series_key("Weekly sync 2026-03-02"), "weekly-sync");
+        assert_eq!(series_key("Weekly sync – Mar 9th"), "weekly-sync");
+        assert_eq!(series_key("Planning W12 / Q3"), "planning");
+    }
+
+    #[test]
+    fn placeholder_titles_have_no_key() {
+        assert_eq!(series_key("Untitled Meeting"), "");
+        assert_eq!(series_key("Untitled meeting 2026-03-02"), "");
+        assert_eq!(series_key("2026-03-02"), "");
+    }
+
+    #[test]
+    fn non_ascii_titles_keep_their_words() {
+        let key = series_key("Réunion hebdo 2026-03-02");
+        assert_eq!(key, "réunion-hebdo");
+        assert!(validate_series_id(&key).is_ok());
+        assert_eq!(series_key("週次 会議 2026-03-09"), "週次-会議");
+        assert!(validate_series_id("Weekly").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! `#tags` and `@mentions` written in note bodies. Every save merges them into
+//! `NoteMeta.tags` and `NoteMeta.participants`, so notes are organised without
+//! a separate metadata editor.
+
+use super::catalog::{CatalogState, UsageCount};
+use super::library::LibraryState;
+use super::notes::NoteMeta;
+use super::people::{self, Person};
+
+/// Tags and mentioned people found in a note body, in order of appearance.
+#[derive(Default, Debug)]
+pub(crate) struct Extracted {
+    pub tags: Vec<String>,
+    pub people: Vec<String>,
+}
+
+/// Collect `#tags` and `@people` from `markdown`, skipping fenced and inline
+/// code, URLs and e-mail addresses. Headings (`# Title`) and issue numbers
+/// (`#123`) are not tags.
+pub(crate) fn extract(markdown: &str) -> Extracted {
+    let mut found = Extracted::default();
+    let mut fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+            continue;
+        }
+        // Text between backticks is inline code.
+        for (i, segment) in line.split('`').enumerate() {
+            if i % 2 == 0 {
+                scan(segment, &mut found);
+            }
+        }
+    }
+    found
+}
+
+fn scan(text: &str, found: &mut Extracted) {
+    for word in text.split_whitespace() {
+        if word.contains("://") || word.starts_with("www.") || word.starts_with("mailto:") {
+            continue;
+        }
+        let chars: Vec<char> = word.chars().collect();
+        for (i, &marker) in chars.iter().enumerate() {
+            if marker != '#' && marker != '@' {
+                continue;
+            }
+            // `a#b`, `sam@example.com` and `&#39;` are not markers.
+            if i > 0 && (chars[i - 1].is_alphanumeric() || "&#@/\\".contains(chars[i - 1])) {
+                continue;
+            }
+            let allowed = |c: char| {
+                c.is_alphanumeric()
+                    || c == '-'
+                    || c == '_'
+                    || (if marker == '#' { c == '/' } else { c == '.' })
+            };
+            let name: String = chars[i + 1..].iter().take_while(|&&c| allowed(c)).collect();
+            let name = name.trim_end_matches(['-', '_', '/', '.']);
+            let valid = if marker == '#' {
+                name.chars().any(char::is_alphabetic)
+            } else {
+                name.starts_with(char::is_alphabetic)
+            };
+            if valid {
+                let list = if marker == '#' {
+                    &mut found.tags
+                } else {
+                    &mut found.people
+                };
+                push_unique(list, name);
+            }
+        }
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, name: &str) {
+    if !list
+        .iter()
+        .any(|existing| existing.eq_ignore_ascii_case(name))
+    {
+        list.push(name.to_string());
+    }
+}
+
+/// Update `list` for a body edit: drop names `before` had and `after` no
+/// longer has, then add the ones `after` has. Names set by other means stay.
+fn merge(list: &mut Vec<String>, before: &[String], after: &[String]) {
+    let contains =
+        |names: &[String], name: &str| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+    list.retain(|name| !contains(before, name) || contains(after, name));
+    for name in after {
+        push_unique(list, name);
+    }
+}
+
+/// `meta` with the tags and mentions of `content` merged in, given the body
+/// `previous` it replaces (if any). Participants are resolved against the
+/// people directory.
+pub(crate) fn merge_into_meta(
+    mut meta: NoteMeta,
+    previous: Option<&str>,
+    content: &str,
+    people: &[Person],
+) -> NoteMeta {
+    let before = previous.map(extract).unwrap_or_default();
+    let after = extract(content);
+    merge(&mut meta.tags, &before.tags, &after.tags);
+
+    let canonical = |names: &[String]| {
+        let mut resolved = Vec::new();
+        for name in names {
+            push_unique(&mut resolved, &people::canonical(people, name));
+        }
+        resolved
+    };
+    meta.participants = canonical(&meta.participants);
+    merge(
+        &mut meta.participants,
+        &canonical(&before.people),
+        &canonical(&after.people),
+    );
+    meta
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Every tag in the library with the number of notes using it, most used first.
+#[tauri::command]
+pub fn list_tags(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<Vec<UsageCount>, String> {
+    catalog.tag_counts(&library.active()?)
+}
+
+/// Every participant in the library with the number of notes they appear in,
+/// most frequent first.
+#[tauri::command]
+pub fn list_participants(
+    library: tauri::State<LibraryState>,
+    catalog: tauri::State<CatalogState>,
+) -> Result<Vec<UsageCount>, String> {
+    catalog.participant_counts(&library.active()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_and_mentions_are_collected_once() {
+        let found = extract("Sync with @sam about #launch and #Q3/plan.\n@Sam owns #LAUNCH.");
+        assert_eq!(found.tags, ["launch", "Q3/plan"]);
+        assert_eq!(found.people, ["sam"]);
+    }
+
+    #[test]
+    fn code_is_skipped() {
+        let markdown = "\
+#real
+```
+#fenced @fenced
+```
+~~~rust
+let x = \"#tilde\";
+~~~
+Run `git tag #inline @inline` then #after.
+";
+        let found = extract(markdown);
+        assert_eq!(found.tags, ["real", "after"]);
+        assert!(found.people.is_empty());
+    }
+
+    #[test]
+    fn urls_and_addresses_are_skipped() {
+        let found = extract(
+            "See https://example.com/page#section and www.example.com/#top, \
+             mail sam@example.com or mailto:ana@example.com, a#b, &#39;",
+        );
+        assert!(found.tags.is_empty(), "{:?}", found.tags);
+        assert!(found.people.is_empty(), "{:?}", found.people);
+    }
+
+    #[test]
+    fn headings_and_issue_numbers_are_not_tags() {
+        let found = extract("# Weekly sync\n## Notes\nFixes #123 and #42, see #followup");
+        assert_eq!(found.tags, ["followup"]);
+    }
+}
@@ -0,0 +1,296 @@
+//! Note templates: Markdown skeletons for recurring meetings, kept in
+//! `<library>/templates/<template_id>.md`. The YAML frontmatter of a template
+//! holds its name and the metadata notes made from it start with; the body is
+//! the note content. Both may use placeholders such as `{{date}}`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::actions::{self, ActionStatus};
+use super::atomic;
+use super::catalog::CatalogState;
+use super::library::LibraryState;
+use super::note_id::FolderPath;
+use super::notes::{self, Note, NoteMeta};
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+const TEMPLATES_DIR: &str = "templates";
+/// `NoteMeta` key recording the template a note was made from.
+const TEMPLATE_KEY: &str = "template";
+
+/// Serialises creating, editing and deleting template files.
+static TEMPLATES_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Template {
+    /// File name without `.md`, derived from the name when it is created.
+    pub template_id: String,
+    #[serde(flatten)]
+    pub header: TemplateHeader,
+    pub body: String,
+}
+
+/// The frontmatter of a template file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TemplateHeader {
+    pub name: String,
+    /// Title of notes made from the template, e.g. `Standup {{date}}`.
+    pub title: String,
+    pub participants: Vec<String>,
+    pub tags: Vec<String>,
+    /// Folder new notes go into unless the caller picks one.
+    pub folder: Option<FolderPath>,
+}
+
+/// Fields a caller supplies when creating or editing a template.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct TemplateInput {
+    #[serde(flatten)]
+    pub header: TemplateHeader,
+    pub body: String,
+}
+
+fn templates_dir(dir: &Path) -> PathBuf {
+    dir.join(TEMPLATES_DIR)
+}
+
+fn template_path(dir: &Path, template_id: &str) -> Result<PathBuf, String> {
+    let valid = !template_id.is_empty()
+        && template_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(format!("Invalid template id: {template_id}"));
+    }
+    Ok(templates_dir(dir).join(format!("{template_id}.md")))
+}
+
+fn parse_template(template_id: &str, raw: &str) -> Template {
+    let (header, body) = match notes::split_frontmatter(raw) {
+        Some((yaml, body)) => match serde_yaml::from_str::<TemplateHeader>(yaml) {
+            Ok(header) => (header, body),
+            Err(_) => (TemplateHeader::default(), raw),
+        },
+        None => (TemplateHeader::default(), raw),
+    };
+    let mut header = header;
+    if header.name.trim().is_empty() {
+        header.name = template_id.to_string();
+    }
+    Template {
+        template_id: template_id.to_string(),
+        header,
+        body: body.to_string(),
+    }
+}
+
+fn load_template(dir: &Path, template_id: &str) -> Result<Template, String> {
+    let raw = fs::read_to_string(template_path(dir, template_id)?)
+        .map_err(|_| format!("Template not found: {template_id}"))?;
+    Ok(parse_template(template_id, &raw))
+}
+
+fn write_template(dir: &Path, template_id: &str, input: TemplateInput) -> Result<Template, String> {
+    let mut header = input.header;
+    header.name = header.name.trim().to_string();
+    if header.name.is_empty() {
+        return Err("A template needs a name".into());
+    }
+    let yaml = serde_yaml::to_string(&header).map_err(|e| e.to_string())?;
+    let path = template_path(dir, template_id)?;
+    fs::create_dir_all(templates_dir(dir)).map_err(|e| e.to_string())?;
+    atomic::write_file(&path, format!("---\n{yaml}---\n{}", input.body).as_bytes())
+        .map_err(|e| e.to_string())?;
+    Ok(Template {
+        template_id: template_id.to_string(),
+        header,
+        body: input.body,
+    })
+}
+
+/// Open action items of the newest note made from `template_id`, as a
+/// Markdown checklist.
+fn last_note_action_items(dir: &Path, template_id: &str) -> String {
+    let last = notes::note_ids(dir)
+        .into_iter()
+        .filter_map(|id| notes::load_note(dir, &id).ok())
+        .filter(|note| {
+            note.meta.extra.get(TEMPLATE_KEY).and_then(|v| v.as_str()) == Some(template_id)
+        })
+        .max_by(|a, b| a.meta.created_at.cmp(&b.meta.created_at));
+    let Some(note) = last else {
+        return String::new();
+    };
+    actions::note_items(dir, &note)
+        .into_iter()
+        .filter(|item| item.status == ActionStatus::Open)
+        .map(|item| actions::checklist_line(&item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `text` with each `{{key}}` that `value` knows replaced. Unknown
+/// placeholders are left as they are.
+fn render(text: &str, value: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + 2 + len].trim();
+        out.push_str(&rest[..start]);
+        match value(key) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + len + 4]),
+        }
+        rest = &rest[start + len + 4..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Every template in the library, by name.
+#[tauri::command]
+pub fn list_templates(library: tauri::State<LibraryState>) -> Result<Vec<Template>, String> {
+    let dir = library.notes_dir()?;
+    let mut templates = Vec::new();
+    let Ok(entries) = fs::read_dir(templates_dir(&dir)) else {
+        return Ok(templates);
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let Some(template_id) = file_name.strip_suffix(".md") else {
+            continue;
+        };
+        if template_path(&dir, template_id).is_err() {
+            continue;
+        }
+        match fs::read_to_string(entry.path()) {
+            Ok(raw) => templates.push(parse_template(template_id, &raw)),
+            Err(e) => eprintln!("Skipping unreadable template {file_name}: {e}"),
+        }
+    }
+    templates.sort_by_key(|t| t.header.name.to_lowercase());
+    Ok(templates)
+}
+
+#[tauri::command]
+pub fn create_template(
+    library: tauri::State<LibraryState>,
+    template: TemplateInput,
+) -> Result<Template, String> {
+    let dir = library.notes_dir()?;
+    let _lock = TEMPLATES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut base = notes::slugify(&template.header.name);
+    if base.is_empty() {
+        base = "template".to_string();
+    }
+    let mut template_id = base.clone();
+    let mut n = 2;
+    while template_path(&dir, &template_id)?.exists() {
+        template_id = format!("{base}-{n}");
+        n += 1;
+    }
+    write_template(&dir, &template_id, template)
+}
+
+/// Replace a template's header and body. Its id stays the same, so notes made
+/// from it keep counting as earlier instances.
+#[tauri::command]
+pub fn update_template(
+    library: tauri::State<LibraryState>,
+    template_id: String,
+    template: TemplateInput,
+) -> Result<Template, String> {
+    let dir = library.notes_dir()?;
+    let _lock = TEMPLATES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    load_template(&dir, &template_id)?;
+    write_template(&dir, &template_id, template)
+}
+
+#[tauri::command]
+pub fn delete_template(
+    library: tauri::State<LibraryState>,
+    template_id: String,
+) -> Result<(), String> {
+    let dir = library.notes_dir()?;
+    let _lock = TEMPLATES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    fs::remove_file(template_path(&dir, &template_id)?)
+        .map_err(|_| format!("Template not found: {template_id}"))
+}
+
+/// Create a note from a template, filling in its placeholders: `{{date}}`,
+/// `{{time}}`, `{{title}}`, `{{participants}}` and
+/// `{{last_note_action_items}}`, the open action items of the previous note
+/// made from the same template. `title`, `folder` and `participants` override
+/// the template's defaults.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn create_note_from_template(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    template_id: String,
+    title: Option<String>,
+    folder: Option<FolderPath>,
+    participants: Option<Vec<String>>,
+) -> Result<Note, String> {
+    let lib = library.active()?;
+    let dir = lib.notes_dir()?;
+    let template = load_template(&dir, &template_id)?;
+    let header = template.header;
+    let now = chrono::Local::now();
+    let participants = participants.unwrap_or(header.participants);
+
+    let date = now.format("%Y-%m-%d").to_string();
+    let time = now.format("%H:%M").to_string();
+    let attendees = participants.join(", ");
+    let common = |key: &str| match key {
+        "date" => Some(date.clone()),
+        "time" => Some(time.clone()),
+        "participants" => Some(attendees.clone()),
+        _ => None,
+    };
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| match header.title.trim() {
+            "" => format!("{} {date}", header.name),
+            pattern => render(pattern, &common),
+        });
+    let content = render(&template.body, &|key| match key {
+        "title" => Some(title.clone()),
+        "last_note_action_items" => Some(last_note_action_items(&dir, &template_id)),
+        _ => common(key),
+    });
+
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let meta = NoteMeta {
+        title,
+        participants,
+        tags: header.tags,
+        created_at: created_at.clone(),
+        updated_at: created_at,
+        series: None,
+        extra: BTreeMap::from([(TEMPLATE_KEY.to_string(), template_id.into())]),
+    };
+    notes::add_note(
+        &lib,
+        &search,
+        &catalog,
+        &watcher,
+        folder.or(header.folder).as_ref(),
+        content,
+        meta,
+    )
+}
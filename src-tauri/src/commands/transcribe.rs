@@ -1,6 +1,7 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use ruhear::{RUBuffers, RUHear};
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -8,6 +9,10 @@ use tauri::Emitter;
 use tokio::io::AsyncWriteExt;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+use super::encryption::RecordingSeal;
+use super::library::LibraryState;
+use super::note_id::NoteId;
+
 // ── Recording state ─────────────────────────────────────────────────────────
 
 struct SysAudioHandle {
@@ -44,22 +49,8 @@ impl Default for WhisperState {
 
 // ── Paths ────────────────────────────────────────────────────────────────────
 
-fn brief_dir() -> PathBuf {
-    dirs::home_dir()
-        .expect("Cannot find home dir")
-        .join("Brief")
-}
-
-fn recordings_dir() -> PathBuf {
-    brief_dir().join("recordings")
-}
-
-fn models_dir() -> PathBuf {
-    brief_dir().join("models")
-}
-
-fn model_path() -> PathBuf {
-    models_dir().join("ggml-base.en.bin")
+fn model_path(models_dir: &Path) -> PathBuf {
+    models_dir.join("ggml-base.en.bin")
 }
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
@@ -189,10 +180,15 @@ fn collect_samples(state: &RecordingState) -> Result<(Vec<i16>, u32, Vec<f32>),
     Ok((mic_samples, sample_rate, sys_samples))
 }
 
-/// Write a mono i16 PCM buffer to a WAV file and return the path.
-fn write_wav(samples: &[i16], sample_rate: u32) -> Result<PathBuf, String> {
-    let dir = recordings_dir();
-    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+/// Write a mono i16 PCM buffer to a WAV file in `dir`, sealed by `seal`, and
+/// return the path it was meant for.
+fn write_wav(
+    samples: &[i16],
+    sample_rate: u32,
+    dir: &Path,
+    seal: &RecordingSeal,
+) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
     let path = dir.join(format!(
         "{}.wav",
         chrono::Utc::now().format("%Y%m%d-%H%M%S")
@@ -203,14 +199,64 @@ fn write_wav(samples: &[i16], sample_rate: u32) -> Result<PathBuf, String> {
         bits_per_sample: 16,
         sample_format: hound::SampleFormat::Int,
     };
-    let mut writer = hound::WavWriter::create(&path, spec).map_err(|e| e.to_string())?;
+    // Encoded in memory so that an encrypted note's audio is only written sealed
+    let mut wav = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut wav, spec).map_err(|e| e.to_string())?;
     for &s in samples {
         writer.write_sample(s).map_err(|e| e.to_string())?;
     }
     writer.finalize().map_err(|e| e.to_string())?;
+    seal.write(&path, wav.get_ref())?;
     Ok(path)
 }
 
+/// Run Whisper over 16 kHz mono `audio`, loading the model into `whisper_ctx`
+/// on first use.
+fn transcribe(
+    whisper_ctx: &Mutex<Option<WhisperContext>>,
+    model: &Path,
+    audio: &[f32],
+) -> Result<String, String> {
+    // Load the model if not already cached
+    if !model.exists() {
+        return Err("Whisper model not found. Please download it first.".to_string());
+    }
+
+    let mut ctx_guard = whisper_ctx.lock().map_err(|e| e.to_string())?;
+    if ctx_guard.is_none() {
+        let ctx = WhisperContext::new_with_params(
+            model.to_str().ok_or("Invalid model path")?,
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| e.to_string())?;
+        *ctx_guard = Some(ctx);
+    }
+    let ctx = ctx_guard.as_ref().unwrap();
+
+    // Run transcription
+    let mut wstate = ctx.create_state().map_err(|e| e.to_string())?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 0 });
+    params.set_language(Some("en"));
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    wstate.full(params, audio).map_err(|e| e.to_string())?;
+
+    let n = wstate.full_n_segments().map_err(|e| e.to_string())?;
+    let mut transcript = String::new();
+    for i in 0..n {
+        let text = wstate
+            .full_get_segment_text(i)
+            .map_err(|e| e.to_string())?;
+        transcript.push_str(text.trim());
+        transcript.push(' ');
+    }
+
+    Ok(transcript.trim().to_string())
+}
+
 // ── Commands ─────────────────────────────────────────────────────────────────
 
 #[tauri::command]
@@ -326,18 +372,43 @@ pub fn start_recording(state: tauri::State<RecordingState>) -> Result<(), String
     Ok(())
 }
 
+/// Stop recording and keep the audio. A recording for an encrypted note is
+/// sealed; the note must be unlocked, or recording carries on.
 #[tauri::command]
-pub fn stop_recording(state: tauri::State<RecordingState>) -> Result<String, String> {
+pub fn stop_recording(
+    state: tauri::State<RecordingState>,
+    library: tauri::State<LibraryState>,
+    note_id: Option<NoteId>,
+) -> Result<String, String> {
+    let lib = library.active()?;
+    let recordings_dir = lib.recordings_dir(note_id.as_ref());
+    let seal = match &note_id {
+        Some(id) => RecordingSeal::for_note(&lib.notes_dir()?, id)?,
+        None => RecordingSeal::none(),
+    };
     let (samples, sample_rate, _sys_samples) = collect_samples(&state)?;
-    let path = write_wav(&samples, sample_rate)?;
-    Ok(path.to_string_lossy().into_owned())
+    let path = write_wav(&samples, sample_rate, &recordings_dir, &seal)?;
+    Ok(seal.stored_path(&path).to_string_lossy().into_owned())
 }
 
 #[tauri::command]
 pub async fn stop_and_transcribe(
     recording: tauri::State<'_, RecordingState>,
     whisper: tauri::State<'_, WhisperState>,
+    library: tauri::State<'_, LibraryState>,
+    note_id: Option<NoteId>,
 ) -> Result<String, String> {
+    // Recordings made for a note are kept next to their transcript; others are
+    // only written for debugging and removed again.
+    let keep_recording = note_id.is_some();
+    let lib = library.active()?;
+    let recordings_dir = lib.recordings_dir(note_id.as_ref());
+    let model = model_path(&library.models_dir()?);
+    let seal = match &note_id {
+        Some(id) => RecordingSeal::for_note(&lib.notes_dir()?, id)?,
+        None => RecordingSeal::none(),
+    };
+
     // Collect samples synchronously (fast, needs tauri::State access)
     let (mic_i16, mic_rate, sys_mono) = collect_samples(&recording)?;
 
@@ -352,7 +423,7 @@ pub async fn stop_and_transcribe(
     let transcript = tokio::task::spawn_blocking(move || {
         // Write temp WAV of mic audio (useful for debugging; deleted after transcription)
         let wav_path = if !mic_i16.is_empty() {
-            Some(write_wav(&mic_i16, mic_rate)?)
+            Some(write_wav(&mic_i16, mic_rate, &recordings_dir, &seal)?)
         } else {
             None
         };
@@ -379,50 +450,19 @@ pub async fn stop_and_transcribe(
                 .collect()
         };
 
-        // Load the model if not already cached
-        let model = model_path();
-        if !model.exists() {
-            return Err("Whisper model not found. Please download it first.".to_string());
-        }
-
-        let mut ctx_guard = whisper_ctx.lock().map_err(|e| e.to_string())?;
-        if ctx_guard.is_none() {
-            let ctx = WhisperContext::new_with_params(
-                model.to_str().ok_or("Invalid model path")?,
-                WhisperContextParameters::default(),
-            )
-            .map_err(|e| e.to_string())?;
-            *ctx_guard = Some(ctx);
-        }
-        let ctx = ctx_guard.as_ref().unwrap();
-
-        // Run transcription
-        let mut wstate = ctx.create_state().map_err(|e| e.to_string())?;
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 0 });
-        params.set_language(Some("en"));
-        params.set_print_special(false);
-        params.set_print_progress(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(false);
-
-        wstate.full(params, &audio_f32).map_err(|e| e.to_string())?;
-
-        let n = wstate.full_n_segments().map_err(|e| e.to_string())?;
-        let mut transcript = String::new();
-        for i in 0..n {
-            let text = wstate
-                .full_get_segment_text(i)
-                .map_err(|e| e.to_string())?;
-            transcript.push_str(text.trim());
-            transcript.push(' ');
-        }
-
-        // Clean up temp WAV
+        let transcript = transcribe(&whisper_ctx, &model, &audio_f32);
         if let Some(path) = wav_path {
-            let _ = std::fs::remove_file(&path);
+            if keep_recording {
+                // The audio stays with the note even if transcription failed
+                if let Ok(transcript) = &transcript {
+                    seal.write(&path.with_extension("txt"), transcript.as_bytes())?;
+                }
+            } else {
+                // Clean up temp WAV, whether or not transcription worked
+                let _ = std::fs::remove_file(seal.stored_path(&path));
+            }
         }
-
-        Ok(transcript.trim().to_string())
+        transcript
     })
     .await
     .map_err(|e| e.to_string())??;
@@ -431,8 +471,10 @@ pub async fn stop_and_transcribe(
 }
 
 #[tauri::command]
-pub fn check_whisper_model() -> Result<serde_json::Value, String> {
-    let path = model_path();
+pub fn check_whisper_model(
+    library: tauri::State<LibraryState>,
+) -> Result<serde_json::Value, String> {
+    let path = model_path(&library.models_dir()?);
     Ok(serde_json::json!({
         "exists": path.exists(),
         "path": path.to_string_lossy()
@@ -440,14 +482,17 @@ pub fn check_whisper_model() -> Result<serde_json::Value, String> {
 }
 
 #[tauri::command]
-pub async fn download_whisper_model(app: tauri::AppHandle) -> Result<(), String> {
+pub async fn download_whisper_model(
+    app: tauri::AppHandle,
+    library: tauri::State<'_, LibraryState>,
+) -> Result<(), String> {
     const URL: &str =
         "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin";
 
-    let dir = models_dir();
+    let dir = library.models_dir()?;
     std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
 
-    let final_path = model_path();
+    let final_path = model_path(&dir);
     let tmp_path = dir.join("ggml-base.en.bin.tmp");
 
     let client = reqwest::Client::new();
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::catalog::CatalogState;
+use super::library::LibraryState;
+use super::note_id::NoteId;
+use super::notes;
+use super::search::SearchState;
+use super::watcher::WatcherState;
+
+const TRASH_DIR: &str = ".trash";
+const MANIFEST: &str = "trash.json";
+
+/// A deleted note waiting in `<library>/.trash/<trash_id>/`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashEntry {
+    /// Directory name inside `.trash`, unique per deletion.
+    pub trash_id: String,
+    /// Id the note had before it was deleted.
+    pub id: NoteId,
+    pub title: String,
+    pub deleted_at: String,
+    /// The trashed files and directories, relative to the library root.
+    pub files: Vec<PathBuf>,
+}
+
+fn trash_dir(root: &Path) -> PathBuf {
+    root.join(TRASH_DIR)
+}
+
+fn entry_dir(root: &Path, trash_id: &str) -> Result<PathBuf, String> {
+    if trash_id.is_empty() || trash_id.contains(['/', '\\']) || trash_id.starts_with('.') {
+        return Err(format!("Invalid trash id: {trash_id}"));
+    }
+    Ok(trash_dir(root).join(trash_id))
+}
+
+/// Whether `path` is relative and stays below the directory it is joined to.
+fn is_plain_relative(path: &Path) -> bool {
+    path.components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+/// Move a note and everything that belongs to it into the trash. Call with
+/// `notes::write_lock` held, so a listing never sees the entry half moved.
+pub(crate) fn move_to_trash(root: &Path, id: &NoteId) -> Result<TrashEntry, String> {
+    let title = notes::load_note(root, id)
+        .map(|note| note.meta.title)
+        .unwrap_or_else(|_| id.to_string());
+    let now = chrono::Utc::now();
+    let entry = TrashEntry {
+        trash_id: format!("{}--{}", id.name(), now.format("%Y%m%dT%H%M%S%3fZ")),
+        id: id.clone(),
+        title,
+        deleted_at: now.to_rfc3339(),
+        files: notes::note_files(root, id)
+            .into_iter()
+            .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+            .collect(),
+    };
+    if entry.files.is_empty() {
+        return Err(format!("Note not found: {id}"));
+    }
+
+    // Manifest first: an entry whose files never arrived is purged once expired.
+    let dir = entry_dir(root, &entry.trash_id)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let manifest = serde_json::to_string_pretty(&entry).map_err(|e| e.to_string())?;
+    atomic::write_file(&dir.join(MANIFEST), manifest.as_bytes()).map_err(|e| e.to_string())?;
+
+    let moves: Vec<(PathBuf, PathBuf)> = entry
+        .files
+        .iter()
+        .map(|rel| (root.join(rel), dir.join(rel)))
+        .collect();
+    atomic::move_files(&moves).map_err(|e| e.to_string())?;
+    Ok(entry)
+}
+
+/// The entry in trash directory `path`, if its manifest is readable and any
+/// of its files are there.
+fn read_entry(path: &Path) -> Result<TrashEntry, String> {
+    let raw = fs::read_to_string(path.join(MANIFEST)).map_err(|e| e.to_string())?;
+    let entry: TrashEntry = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    if !entry.files.iter().any(|rel| path.join(rel).exists()) {
+        return Err("none of its files are there".to_string());
+    }
+    Ok(entry)
+}
+
+/// Readable trash entries, most recently deleted first. Others are skipped;
+/// `purge_expired` clears them away once they are old enough.
+fn read_entries(root: &Path) -> Vec<TrashEntry> {
+    let Ok(dirs) = fs::read_dir(trash_dir(root)) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for dir in dirs.flatten() {
+        match read_entry(&dir.path()) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => eprintln!("Skipping trash entry {}: {e}", dir.path().display()),
+        }
+    }
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    entries
+}
+
+/// Permanently delete trash entries older than `retention_days`. Entries that
+/// cannot be read (an interrupted deletion, or a manifest from an older
+/// version) count from the time their directory was last changed.
+pub(crate) fn purge_expired(root: &Path, retention_days: Option<u32>) -> Result<usize, String> {
+    let Some(days) = retention_days else {
+        return Ok(0);
+    };
+    let _lock = notes::write_lock();
+    let Ok(dirs) = fs::read_dir(trash_dir(root)) else {
+        return Ok(0);
+    };
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days.into());
+    let mut purged = 0;
+    for dir in dirs.flatten() {
+        let deleted = match read_entry(&dir.path()) {
+            Ok(entry) => chrono::DateTime::parse_from_rfc3339(&entry.deleted_at)
+                .ok()
+                .map(|deleted| deleted.with_timezone(&chrono::Utc)),
+            Err(_) => dir
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .map(chrono::DateTime::<chrono::Utc>::from),
+        };
+        if deleted.is_some_and(|deleted| deleted < cutoff) {
+            fs::remove_dir_all(dir.path()).map_err(|e| e.to_string())?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Trashed notes, most recently deleted first. Expired entries are purged.
+#[tauri::command]
+pub fn list_trash(library: tauri::State<LibraryState>) -> Result<Vec<TrashEntry>, String> {
+    let lib = library.active()?;
+    let root = lib.notes_dir()?;
+    purge_expired(&root, lib.settings.trash_retention_days)?;
+    Ok(read_entries(&root))
+}
+
+/// Move a trashed note back into the library under its original id.
+#[tauri::command]
+pub fn restore_note(
+    library: tauri::State<LibraryState>,
+    search: tauri::State<SearchState>,
+    catalog: tauri::State<CatalogState>,
+    watcher: tauri::State<WatcherState>,
+    trash_id: String,
+) -> Result<NoteId, String> {
+    let lib = library.active()?;
+    let root = lib.notes_dir()?;
+    let dir = entry_dir(&root, &trash_id)?;
+    let _lock = notes::write_lock();
+    let raw =
+        fs::read_to_string(dir.join(MANIFEST)).map_err(|_| format!("Not in trash: {trash_id}"))?;
+    let entry: TrashEntry = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    // The manifest is a plain file in the library; never follow it elsewhere.
+    if !entry.files.iter().all(|rel| is_plain_relative(rel)) {
+        return Err(format!("Corrupt trash entry: {trash_id}"));
+    }
+
+    let moves: Vec<(PathBuf, PathBuf)> = entry
+        .files
+        .iter()
+        .filter(|rel| dir.join(rel).exists())
+        .map(|rel| (dir.join(rel), root.join(rel)))
+        .collect();
+    if moves.iter().any(|(_, to)| to.exists()) {
+        return Err(format!(
+            "Cannot restore \"{}\": a note with id {} already exists",
+            entry.title, entry.id
+        ));
+    }
+    atomic::move_files(&moves).map_err(|e| e.to_string())?;
+    for (_, to) in &moves {
+        watcher.record_write(&entry.id, to);
+    }
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    match notes::load_note(&root, &entry.id) {
+        Ok(note) => {
+            if let Err(e) = search.index_note(&lib, &note) {
+                eprintln!("Failed to index restored note {}: {e}", entry.id);
+            }
+            if let Err(e) = catalog.index_note(&lib, &note) {
+                eprintln!("Failed to catalog restored note {}: {e}", entry.id);
+            }
+        }
+        Err(e) => eprintln!("Restored note {} is unreadable: {e}", entry.id),
+    }
+    Ok(entry.id)
+}
+
+/// Permanently delete one trashed note, or with `None` empty the trash.
+#[tauri::command]
+pub fn purge_trash(
+    library: tauri::State<LibraryState>,
+    trash_id: Option<String>,
+) -> Result<(), String> {
+    let root = library.notes_dir()?;
+    let target = match trash_id {
+        Some(trash_id) => entry_dir(&root, &trash_id)?,
+        None => trash_dir(&root),
+    };
+    match fs::remove_dir_all(&target) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_paths_must_stay_inside_the_library() {
+        assert!(is_plain_relative(Path::new("2026-02-18-kickoff.md")));
+        assert!(is_plain_relative(Path::new(
+            "recordings/2026-02-18-kickoff"
+        )));
+        assert!(!is_plain_relative(Path::new("../outside.md")));
+        assert!(!is_plain_relative(Path::new("recordings/../../outside")));
+        assert!(!is_plain_relative(Path::new("/etc/passwd")));
+    }
+
+    /// Make `path` look untouched for `days`.
+    fn age(path: &Path, days: u64) {
+        let then = std::time::SystemTime::now() - std::time::Duration::from_secs(days * 86_400);
+        fs::File::open(path).unwrap().set_modified(then).unwrap();
+    }
+
+    #[test]
+    fn unreadable_entries_survive_listing_until_they_expire() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let id = NoteId::parse("2026-03-01-kickoff").unwrap();
+        fs::write(root.join(format!("{id}.md")), "Body").unwrap();
+        let entry = move_to_trash(root, &id).unwrap();
+        assert!(!root.join(format!("{id}.md")).exists());
+
+        // A manifest whose files have not arrived yet, and one naming an id
+        // this version no longer accepts.
+        let pending = trash_dir(root).join("pending--20260301T000000000Z");
+        fs::create_dir_all(&pending).unwrap();
+        let mut manifest = serde_json::to_value(&entry).unwrap();
+        manifest["files"] = serde_json::json!(["2026-03-02-later.md"]);
+        fs::write(pending.join(MANIFEST), manifest.to_string()).unwrap();
+        let legacy = trash_dir(root).join("legacy--20200101T000000000Z");
+        fs::create_dir_all(&legacy).unwrap();
+        manifest["id"] = serde_json::json!("../legacy");
+        manifest["files"] = serde_json::json!(["legacy.md"]);
+        fs::write(legacy.join(MANIFEST), manifest.to_string()).unwrap();
+        fs::write(legacy.join("legacy.md"), "Old body").unwrap();
+
+        let listed = read_entries(root);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].trash_id, entry.trash_id);
+        assert!(pending.exists() && legacy.exists());
+
+        age(&legacy, 40);
+        assert_eq!(purge_expired(root, Some(30)), Ok(1));
+        assert!(!legacy.exists());
+        assert!(pending.exists());
+        assert!(entry_dir(root, &entry.trash_id).unwrap().exists());
+    }
+}
@@ -0,0 +1,235 @@
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager};
+
+use super::attachments::ASSETS_SUFFIX;
+use super::catalog::CatalogState;
+use super::library::Library;
+use super::note_id::{NoteId, RESERVED_FOLDERS};
+use super::notes;
+use super::search::SearchState;
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A change to a note the editor did not make: outside the app (another
+/// editor, a sync tool, ...) or by the app on its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoteChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl NoteChange {
+    pub fn event_name(self) -> &'static str {
+        match self {
+            NoteChange::Created => "note-created",
+            NoteChange::Updated => "note-updated",
+            NoteChange::Deleted => "note-deleted",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct NoteChangeEvent {
+    pub id: NoteId,
+}
+
+// ── Watcher state ────────────────────────────────────────────────────────────
+
+struct ActiveWatcher {
+    root: PathBuf,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// State shared between the note commands and the debouncer thread.
+#[derive(Default)]
+struct Shared {
+    /// Files the app itself just wrote (`Some(mtime)`) or removed (`None`).
+    own_writes: HashMap<PathBuf, Option<SystemTime>>,
+    /// Ids of notes known to exist, used to tell creations from updates.
+    known: HashSet<NoteId>,
+}
+
+pub struct WatcherState {
+    active: Mutex<Option<ActiveWatcher>>,
+    shared: Arc<Mutex<Shared>>,
+    /// Set once watching starts; used to `announce` the app's own changes.
+    app: Mutex<Option<tauri::AppHandle>>,
+}
+
+impl Default for WatcherState {
+    fn default() -> Self {
+        WatcherState {
+            active: Mutex::new(None),
+            shared: Arc::new(Mutex::new(Shared::default())),
+            app: Mutex::new(None),
+        }
+    }
+}
+
+impl WatcherState {
+    /// Start watching `library`, replacing any watcher on a previous library.
+    pub fn watch(&self, app: &tauri::AppHandle, library: &Library) -> Result<(), String> {
+        let root = library.notes_dir()?;
+        let mut active = self.active.lock().map_err(|e| e.to_string())?;
+        if active.as_ref().map(|w| w.root == root).unwrap_or(false) {
+            return Ok(());
+        }
+        // Stop the old watcher before touching shared state it may be using.
+        *active = None;
+        *self.app.lock().map_err(|e| e.to_string())? = Some(app.clone());
+
+        {
+            let mut shared = self.shared.lock().map_err(|e| e.to_string())?;
+            shared.own_writes.clear();
+            shared.known = notes::note_ids(&root).into_iter().collect();
+        }
+
+        let app = app.clone();
+        let shared = Arc::clone(&self.shared);
+        let lib = library.clone();
+        let handler_root = root.clone();
+        let mut debouncer = new_debouncer(DEBOUNCE, move |res: DebounceEventResult| match res {
+            Ok(events) => {
+                let paths: Vec<PathBuf> = events.into_iter().map(|e| e.path).collect();
+                handle_changes(&app, &shared, &lib, &handler_root, &paths);
+            }
+            Err(e) => eprintln!("Notes watcher error: {e}"),
+        })
+        .map_err(|e| e.to_string())?;
+
+        debouncer
+            .watcher()
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Cannot watch {}: {e}", root.display()))?;
+
+        *active = Some(ActiveWatcher {
+            root,
+            _debouncer: debouncer,
+        });
+        Ok(())
+    }
+
+    /// Record that the app wrote `path` for note `id`, so the resulting
+    /// filesystem event is not reported back as an external change.
+    pub fn record_write(&self, id: &NoteId, path: &Path) {
+        let mtime = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.own_writes.insert(path.to_path_buf(), mtime);
+            shared.known.insert(id.clone());
+        }
+    }
+
+    /// Report a change the app made to note `id` on its own, such as links
+    /// rewritten after another note was renamed, so an open editor reloads
+    /// the note instead of saving over it.
+    pub fn announce(&self, id: &NoteId, change: NoteChange) {
+        if let Some(app) = self.app.lock().ok().and_then(|app| app.clone()) {
+            let _ = app.emit(change.event_name(), NoteChangeEvent { id: id.clone() });
+        }
+    }
+
+    /// Record that the app removed `path` belonging to note `id`.
+    pub fn record_delete(&self, id: &NoteId, path: &Path) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.own_writes.insert(path.to_path_buf(), None);
+            shared.known.remove(id);
+        }
+    }
+}
+
+/// Map a file in the library at `root` to the id of the note it belongs to.
+/// Files under hidden or reserved folders, and attachments, belong to no note.
+fn note_id_for(root: &Path, path: &Path) -> Option<NoteId> {
+    // Events may report the canonical path of a library reached via a symlink.
+    let rel = match path.strip_prefix(root) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => path
+            .strip_prefix(fs::canonicalize(root).ok()?)
+            .ok()?
+            .to_path_buf(),
+    };
+    let segments = rel.iter().map(|s| s.to_str()).collect::<Option<Vec<_>>>()?;
+    if segments.iter().any(|s| s.starts_with('.'))
+        || (segments.len() > 1 && RESERVED_FOLDERS.contains(segments.first()?))
+        || segments[..segments.len() - 1]
+            .iter()
+            .any(|s| s.ends_with(ASSETS_SUFFIX))
+    {
+        return None;
+    }
+    let name = segments.join("/");
+    name.strip_suffix(".meta.json")
+        .or_else(|| name.strip_suffix(".actions.json"))
+        .or_else(|| name.strip_suffix(".decisions.json"))
+        .or_else(|| name.strip_suffix(".md"))
+        .and_then(|id| NoteId::parse(id).ok())
+}
+
+fn handle_changes(
+    app: &tauri::AppHandle,
+    shared: &Mutex<Shared>,
+    library: &Library,
+    root: &Path,
+    paths: &[PathBuf],
+) {
+    let Ok(mut shared) = shared.lock() else {
+        return;
+    };
+
+    let mut changed_ids = BTreeSet::new();
+    for path in paths {
+        let Some(id) = note_id_for(root, path) else {
+            continue;
+        };
+        let current = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if shared.own_writes.get(path) == Some(&current) {
+            shared.own_writes.remove(path);
+            continue;
+        }
+        changed_ids.insert(id);
+    }
+
+    let mut changes = Vec::new();
+    for id in changed_ids {
+        let change = if root.join(format!("{}.md", id)).exists() {
+            if shared.known.insert(id.clone()) {
+                NoteChange::Created
+            } else {
+                NoteChange::Updated
+            }
+        } else if shared.known.remove(&id) {
+            NoteChange::Deleted
+        } else {
+            // A stray sidecar without a note; nothing to report.
+            continue;
+        };
+        changes.push((id, change));
+    }
+    drop(shared);
+
+    let search = app.state::<SearchState>();
+    let catalog = app.state::<CatalogState>();
+    for (id, change) in changes {
+        let indexed = match change {
+            NoteChange::Deleted => search
+                .remove_note(library, &id)
+                .and_then(|()| catalog.remove_note(library, &id)),
+            _ => notes::load_note(root, &id).and_then(|note| {
+                search.index_note(library, &note)?;
+                catalog.index_note(library, &note)
+            }),
+        };
+        if let Err(e) = indexed {
+            eprintln!("Failed to reindex externally changed note {id}: {e}");
+        }
+        let _ = app.emit(change.event_name(), NoteChangeEvent { id });
+    }
+}
@@ -1,5 +1,7 @@
 mod commands;
 
+use tauri::Manager;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -8,13 +10,104 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(commands::library::LibraryState::default())
         .manage(commands::transcribe::RecordingState::default())
         .manage(commands::transcribe::WhisperState::default())
+        .manage(commands::search::SearchState::default())
+        .manage(commands::catalog::CatalogState::default())
+        .manage(commands::watcher::WatcherState::default())
+        .manage(commands::git::GitState::default())
+        .setup(|app| {
+            let library = app.state::<commands::library::LibraryState>().active();
+            match library {
+                Ok(library) => commands::library::activate(app.handle(), &library),
+                Err(e) => eprintln!("{e}"),
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            commands::library::list_libraries,
+            commands::library::add_library,
+            commands::library::switch_library,
+            commands::library::remove_library,
+            commands::library::set_models_dir,
+            commands::library::set_library_settings,
             commands::notes::list_notes,
             commands::notes::read_note,
             commands::notes::write_note,
+            commands::notes::create_note,
+            commands::notes::rename_note,
+            commands::notes::migrate_storage,
             commands::notes::delete_note,
+            commands::notes::merge_note,
+            commands::notes::move_note,
+            commands::folders::list_folders,
+            commands::folders::create_folder,
+            commands::folders::rename_folder,
+            commands::folders::move_folder,
+            commands::folders::delete_folder,
+            commands::series::list_series,
+            commands::series::get_series,
+            commands::series::get_adjacent_occurrence,
+            commands::series::set_note_series,
+            commands::series::carry_forward_action_items,
+            commands::series::create_next_occurrence,
+            commands::series::infer_series,
+            commands::templates::list_templates,
+            commands::templates::create_template,
+            commands::templates::update_template,
+            commands::templates::delete_template,
+            commands::templates::create_note_from_template,
+            commands::trash::list_trash,
+            commands::trash::restore_note,
+            commands::trash::purge_trash,
+            commands::actions::list_action_items,
+            commands::actions::set_action_item_done,
+            commands::decisions::list_decisions,
+            commands::decisions::add_decision,
+            commands::decisions::update_decision,
+            commands::decisions::remove_decision,
+            commands::decisions::supersede_decision,
+            commands::attachments::list_attachments,
+            commands::attachments::add_attachment,
+            commands::attachments::add_attachment_from_path,
+            commands::attachments::delete_attachment,
+            commands::encryption::encryption_status,
+            commands::encryption::set_encryption_passphrase,
+            commands::encryption::unlock_notes,
+            commands::encryption::lock_notes,
+            commands::encryption::set_note_encrypted,
+            commands::git::git_status,
+            commands::git::enable_git_sync,
+            commands::git::set_git_remote,
+            commands::git::git_commit,
+            commands::git::git_pull,
+            commands::git::resolve_git_conflicts,
+            commands::git::git_push,
+            commands::git::note_git_log,
+            commands::history::list_versions,
+            commands::history::read_version,
+            commands::history::diff_versions,
+            commands::history::restore_version,
+            commands::tags::list_tags,
+            commands::tags::list_participants,
+            commands::people::list_people,
+            commands::people::list_unknown_participants,
+            commands::people::add_person,
+            commands::people::update_person,
+            commands::people::remove_person,
+            commands::people::merge_people,
+            commands::people::get_person,
+            commands::links::get_backlinks,
+            commands::links::list_broken_links,
+            commands::links::export_link_graph,
+            commands::ics::list_ics_events,
+            commands::ics::import_ics,
+            commands::import::import_notes,
+            commands::export::export_note,
+            commands::export::export_notes,
+            commands::search::search_notes,
+            commands::search::rebuild_search_index,
             commands::ai::enhance_note,
             commands::transcribe::start_recording,
             commands::transcribe::stop_recording,
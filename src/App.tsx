@@ -4,8 +4,7 @@ import { Sidebar } from "@/components/sidebar/Sidebar";
 import { Editor } from "@/components/editor/Editor";
 import { WelcomeScreen } from "@/components/editor/WelcomeScreen";
 import { EditorToolbar } from "@/components/editor/EditorToolbar";
-import { listNotes, writeNote, deleteNote } from "@/lib/notes";
-import { generateNoteId, nowISO } from "@/lib/utils";
+import { listNotes, createNote, deleteNote, onNoteChanged } from "@/lib/notes";
 import type { NoteSummary } from "@/types";
 
 export default function App() {
@@ -17,27 +16,32 @@ export default function App() {
     refreshNotes();
   }, []);
 
+  // Notes edited outside the app (other editors, sync tools)
+  useEffect(() => {
+    const unlisten = onNoteChanged((change, id) => {
+      if (change === "note-deleted") {
+        setActiveId((current) => (current === id ? null : current));
+      }
+      refreshNotes();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   async function refreshNotes() {
     try {
-      const list = await listNotes();
-      setNotes(list);
+      const page = await listNotes();
+      setNotes(page.notes);
     } finally {
       setLoading(false);
     }
   }
 
   async function handleNewNote() {
-    const id = generateNoteId();
-    const now = nowISO();
-    await writeNote(id, "", {
-      title: "Untitled Meeting",
-      participants: [],
-      tags: [],
-      created_at: now,
-      updated_at: now,
-    });
+    const note = await createNote("Untitled Meeting");
     await refreshNotes();
-    setActiveId(id);
+    setActiveId(note.id);
   }
 
   async function handleDeleteNote(id: string) {
@@ -87,7 +91,15 @@ export default function App() {
         />
         <main className="flex-1 overflow-hidden">
           {activeId ? (
-            <Editor key={activeId} noteId={activeId} onSave={refreshNotes} />
+            <Editor
+              key={activeId}
+              noteId={activeId}
+              onSave={refreshNotes}
+              onRename={(id) => {
+                setActiveId(id);
+                refreshNotes();
+              }}
+            />
           ) : (
             <WelcomeScreen onNew={handleNewNote} />
           )}
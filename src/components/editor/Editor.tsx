@@ -1,18 +1,20 @@
 import { useState, useEffect, useRef, useCallback } from "react";
 import { Loader2 } from "lucide-react";
-import { readNote, writeNote } from "@/lib/notes";
+import { readNote, writeNote, mergeNote, renameNote, onNoteChanged } from "@/lib/notes";
 import { enhanceNote } from "@/lib/ai";
 import { startRecording, stopAndTranscribe } from "@/lib/audio";
+import { addAttachment } from "@/lib/attachments";
 import { nowISO } from "@/lib/utils";
 import { registerEnhanceListener, registerRecordListener } from "@/components/editor/EditorToolbar";
-import type { Note, EnhanceMode } from "@/types";
+import type { Note, EnhanceMode, WriteNoteError } from "@/types";
 
 interface EditorProps {
   noteId: string;
   onSave: () => void;
+  onRename: (newId: string) => void;
 }
 
-export function Editor({ noteId, onSave }: EditorProps) {
+export function Editor({ noteId, onSave, onRename }: EditorProps) {
   const [note, setNote] = useState<Note | null>(null);
   const [content, setContent] = useState("");
   const [title, setTitle] = useState("");
@@ -35,15 +37,62 @@ export function Editor({ noteId, onSave }: EditorProps) {
     });
   }, [noteId]);
 
+  // Reload when the note is edited outside the app, unless a local save is pending
+  useEffect(() => {
+    const unlisten = onNoteChanged((change, id) => {
+      if (change !== "note-updated" || id !== noteId) return;
+      if (saveTimer.current) {
+        setError("This note was changed on disk while you were editing it.");
+        return;
+      }
+      readNote(noteId).then((n: Note) => {
+        setNote(n);
+        setContent(n.content);
+        setTitle(n.meta.title);
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [noteId]);
+
   const save = useCallback(
     async (newContent: string, newTitle: string) => {
       const currentNote = noteRef.current;
       if (!currentNote) return;
-      await writeNote(noteId, newContent, {
-        ...currentNote.meta,
-        title: newTitle,
-        updated_at: nowISO(),
-      });
+      const meta = { ...currentNote.meta, title: newTitle, updated_at: nowISO() };
+      try {
+        const revision = await writeNote(noteId, newContent, meta, currentNote.revision);
+        noteRef.current = { ...currentNote, content: newContent, meta, revision };
+      } catch (e) {
+        const err = e as WriteNoteError;
+        if (err?.kind !== "conflict") {
+          setError(err?.kind === "failed" ? err.message : String(e));
+          return;
+        }
+        // Changed elsewhere since we loaded it: merge if the edits don't overlap
+        const merged = await mergeNote(currentNote.content, newContent, err.current.content);
+        if (merged === null) {
+          setError("This note was changed elsewhere and your edits overlap. Copy your changes and reopen the note.");
+          return;
+        }
+        // Keep their metadata (participants, tags, series…); this editor only changes the title
+        const titleChanged = newTitle !== currentNote.meta.title;
+        const mergedMeta = {
+          ...err.current.meta,
+          title: titleChanged ? newTitle : err.current.meta.title,
+          updated_at: meta.updated_at,
+        };
+        const revision = await writeNote(noteId, merged, mergedMeta, err.current.revision);
+        noteRef.current = { ...err.current, content: merged, meta: mergedMeta, revision };
+        setContent(merged);
+        contentRef.current = merged;
+        if (!titleChanged) {
+          setTitle(mergedMeta.title);
+          titleRef.current = mergedMeta.title;
+        }
+      }
+      setNote(noteRef.current);
       onSave();
     },
     [noteId, onSave],
@@ -51,7 +100,10 @@ export function Editor({ noteId, onSave }: EditorProps) {
 
   function scheduleSave(newContent: string, newTitle: string) {
     if (saveTimer.current) clearTimeout(saveTimer.current);
-    saveTimer.current = setTimeout(() => save(newContent, newTitle), 800);
+    saveTimer.current = setTimeout(() => {
+      saveTimer.current = null;
+      save(newContent, newTitle);
+    }, 800);
   }
 
   // Register with EditorToolbar's event bus
@@ -60,7 +112,7 @@ export function Editor({ noteId, onSave }: EditorProps) {
       if (!contentRef.current.trim()) return;
       setError(null);
       try {
-        const result = await enhanceNote(contentRef.current, mode, "local");
+        const result = await enhanceNote(contentRef.current, mode, "local", undefined, undefined, noteId);
         setContent(result);
         contentRef.current = result;
         save(result, titleRef.current);
@@ -79,7 +131,7 @@ export function Editor({ noteId, onSave }: EditorProps) {
         await startRecording();
       } else {
         try {
-          const transcript = await stopAndTranscribe();
+          const transcript = await stopAndTranscribe(noteId);
           if (transcript) {
             const newContent = contentRef.current
               ? `${contentRef.current}\n\n${transcript}`
@@ -101,11 +153,55 @@ export function Editor({ noteId, onSave }: EditorProps) {
     scheduleSave(e.target.value, titleRef.current);
   }
 
+  // Images pasted from the clipboard are attached to the note and linked at the cursor
+  async function handlePaste(e: React.ClipboardEvent<HTMLTextAreaElement>) {
+    const images = Array.from(e.clipboardData.files).filter((file) => file.type.startsWith("image/"));
+    if (images.length === 0) return;
+    e.preventDefault();
+    const textarea = e.currentTarget;
+    const { selectionStart, selectionEnd } = textarea;
+    try {
+      const links: string[] = [];
+      for (const image of images) {
+        const bytes = new Uint8Array(await image.arrayBuffer());
+        // Screenshots all arrive as "image.png"; let the backend name those
+        const name = image.name && image.name !== "image.png" ? image.name : undefined;
+        links.push((await addAttachment(noteId, bytes, name)).markdown);
+      }
+      const inserted = links.join("\n");
+      const current = contentRef.current;
+      const newContent = current.slice(0, selectionStart) + inserted + current.slice(selectionEnd);
+      setContent(newContent);
+      contentRef.current = newContent;
+      scheduleSave(newContent, titleRef.current);
+      requestAnimationFrame(() => {
+        textarea.selectionStart = textarea.selectionEnd = selectionStart + inserted.length;
+      });
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  }
+
   function handleTitleChange(e: React.ChangeEvent<HTMLInputElement>) {
     setTitle(e.target.value);
     scheduleSave(contentRef.current, e.target.value);
   }
 
+  // The id follows the title; settle it once the user is done editing the title
+  async function handleTitleBlur() {
+    if (saveTimer.current) {
+      clearTimeout(saveTimer.current);
+      saveTimer.current = null;
+      await save(contentRef.current, titleRef.current);
+    }
+    try {
+      const newId = await renameNote(noteId, titleRef.current);
+      if (newId !== noteId) onRename(newId);
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    }
+  }
+
   if (!note) {
     return (
       <div className="h-full flex items-center justify-center">
@@ -121,6 +217,7 @@ export function Editor({ noteId, onSave }: EditorProps) {
         <input
           value={title}
           onChange={handleTitleChange}
+          onBlur={handleTitleBlur}
           placeholder="Meeting title…"
           className="selectable w-full text-xl font-semibold bg-transparent outline-none text-[hsl(var(--foreground))] placeholder:text-[hsl(var(--muted-foreground))]"
         />
@@ -137,6 +234,7 @@ export function Editor({ noteId, onSave }: EditorProps) {
         <textarea
           value={content}
           onChange={handleContentChange}
+          onPaste={handlePaste}
           placeholder="Start typing your meeting notes…"
           className="selectable w-full h-full min-h-full resize-none bg-transparent outline-none text-sm text-[hsl(var(--foreground))] placeholder:text-[hsl(var(--muted-foreground))] leading-relaxed"
         />
@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ActionFilter, ActionItem, NoteActionItem } from "@/types";
+
+/** Action items across the library; open ones only unless `include_done` is set. */
+export async function listActionItems(filter: ActionFilter = {}): Promise<NoteActionItem[]> {
+  return invoke("list_action_items", { filter });
+}
+
+/** Tick (or untick) an action item's checkbox in its note. */
+export async function setActionItemDone(
+  noteId: string,
+  itemId: string,
+  done: boolean,
+): Promise<ActionItem> {
+  return invoke("set_action_item_done", { noteId, itemId, done });
+}
@@ -7,18 +7,21 @@ interface EnhanceRequest {
   provider: AIProvider;
   api_key?: string;
   model?: string;
+  note_id?: string;
 }
 
 interface EnhanceResponse {
   result: string;
 }
 
+/** Pass `noteId` to snapshot the note into its history before the rewrite. */
 export async function enhanceNote(
   content: string,
   mode: EnhanceMode,
   provider: AIProvider,
   apiKey?: string,
   model?: string,
+  noteId?: string,
 ): Promise<string> {
   const req: EnhanceRequest = {
     content,
@@ -26,6 +29,7 @@ export async function enhanceNote(
     provider,
     api_key: apiKey,
     model,
+    note_id: noteId,
   };
   const resp: EnhanceResponse = await invoke("enhance_note", { request: req });
   return resp.result;
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Attachment } from "@/types";
+
+export async function listAttachments(id: string): Promise<Attachment[]> {
+  return invoke("list_attachments", { id });
+}
+
+/** Attach raw data, e.g. an image pasted from the clipboard; a name is made up if none is given. */
+export async function addAttachment(id: string, bytes: Uint8Array, name?: string): Promise<Attachment> {
+  return invoke("add_attachment", { id, name: name ?? null, bytes: Array.from(bytes) });
+}
+
+/** Copy the file at `path` into the note's attachments. */
+export async function addAttachmentFromPath(id: string, path: string): Promise<Attachment> {
+  return invoke("add_attachment_from_path", { id, path });
+}
+
+export async function deleteAttachment(id: string, name: string): Promise<void> {
+  return invoke("delete_attachment", { id, name });
+}
@@ -34,6 +34,7 @@ export async function startRecording(): Promise<void> {
   return invoke("start_recording");
 }
 
-export async function stopAndTranscribe(): Promise<string> {
-  return invoke("stop_and_transcribe");
+/** Stop recording and transcribe it. With `noteId`, the audio and transcript are kept with that note. */
+export async function stopAndTranscribe(noteId?: string): Promise<string> {
+  return invoke("stop_and_transcribe", { noteId: noteId ?? null });
 }
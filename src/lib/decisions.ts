@@ -0,0 +1,35 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Decision, DecisionInput, DecisionRef, NoteDecision } from "@/types";
+
+/** Decision register across all notes, oldest first. */
+export async function listDecisions(
+  query?: string,
+  includeSuperseded = true,
+): Promise<NoteDecision[]> {
+  return invoke("list_decisions", { query: query ?? null, includeSuperseded });
+}
+
+export async function addDecision(noteId: string, decision: DecisionInput): Promise<Decision> {
+  return invoke("add_decision", { noteId, decision });
+}
+
+export async function updateDecision(
+  noteId: string,
+  decisionId: string,
+  decision: DecisionInput,
+): Promise<Decision> {
+  return invoke("update_decision", { noteId, decisionId, decision });
+}
+
+export async function removeDecision(noteId: string, decisionId: string): Promise<void> {
+  return invoke("remove_decision", { noteId, decisionId });
+}
+
+/** Mark a decision as replaced by a later one; `null` clears the mark. */
+export async function supersedeDecision(
+  noteId: string,
+  decisionId: string,
+  supersededBy: DecisionRef | null,
+): Promise<Decision> {
+  return invoke("supersede_decision", { noteId, decisionId, supersededBy });
+}
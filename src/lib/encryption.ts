@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { EncryptionStatus, Note } from "@/types";
+
+export async function encryptionStatus(): Promise<EncryptionStatus> {
+  return invoke("encryption_status");
+}
+
+/** Set the library's passphrase, or change it given the `current` one. Leaves the library unlocked. */
+export async function setEncryptionPassphrase(passphrase: string, current?: string): Promise<void> {
+  return invoke("set_encryption_passphrase", { passphrase, current: current ?? null });
+}
+
+export async function unlockNotes(passphrase: string): Promise<void> {
+  return invoke("unlock_notes", { passphrase });
+}
+
+export async function lockNotes(): Promise<void> {
+  return invoke("lock_notes");
+}
+
+/**
+ * Encrypt a note, or decrypt it; needs the library unlocked. Returns the rewritten note.
+ * Rejects if git history already holds the note unencrypted, unless `despiteGitHistory`.
+ */
+export async function setNoteEncrypted(
+  id: string,
+  encrypted: boolean,
+  despiteGitHistory = false,
+): Promise<Note> {
+  return invoke("set_note_encrypted", { id, encrypted, despiteGitHistory });
+}
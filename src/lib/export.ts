@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ExportFormat, NoteQuery } from "@/types";
+
+/** Write a note, with its date, participants and tags, to `path`. */
+export async function exportNote(id: string, format: ExportFormat, path: string): Promise<void> {
+  return invoke("export_note", { id, format, path });
+}
+
+/** Export the notes `query` matches into `dir`, mirroring their folders; returns the files written. */
+export async function exportNotes(query: NoteQuery, format: ExportFormat, dir: string): Promise<string[]> {
+  return invoke("export_notes", { query, format, dir });
+}
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { TrashEntry } from "@/types";
+
+/** Every folder path in the library, parents first. */
+export async function listFolders(): Promise<string[]> {
+  return invoke("list_folders");
+}
+
+export async function createFolder(path: string): Promise<string> {
+  return invoke("create_folder", { path });
+}
+
+/** Rename a folder in place; resolves to its new path. Note ids inside change too. */
+export async function renameFolder(path: string, name: string): Promise<string> {
+  return invoke("rename_folder", { path, name });
+}
+
+/** Move a folder into `parent` (`null` for the top level); resolves to its new path. */
+export async function moveFolder(path: string, parent: string | null): Promise<string> {
+  return invoke("move_folder", { path, parent });
+}
+
+/** Trash every note in a folder and remove it. */
+export async function deleteFolder(path: string): Promise<TrashEntry[]> {
+  return invoke("delete_folder", { path });
+}
@@ -0,0 +1,37 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ConflictSide, GitCommit, GitStatus, PullResult } from "@/types";
+
+export async function gitStatus(): Promise<GitStatus> {
+  return invoke("git_status");
+}
+
+/** Make the library a git repository, with `remote` (URL or path of a bare repository) to sync with. */
+export async function enableGitSync(remote?: string): Promise<GitStatus> {
+  return invoke("enable_git_sync", { remote: remote ?? null });
+}
+
+export async function setGitRemote(url: string | null): Promise<void> {
+  return invoke("set_git_remote", { url });
+}
+
+/** Commit pending changes now; null if nothing changed. */
+export async function gitCommit(message?: string): Promise<GitCommit | null> {
+  return invoke("git_commit", { message: message ?? null });
+}
+
+export async function gitPull(): Promise<PullResult> {
+  return invoke("git_pull");
+}
+
+/** Finish a pull that returned conflicts, with a side picked for every conflict key. */
+export async function resolveGitConflicts(choices: Record<string, ConflictSide>): Promise<PullResult> {
+  return invoke("resolve_git_conflicts", { choices });
+}
+
+export async function gitPush(): Promise<void> {
+  return invoke("git_push");
+}
+
+export async function noteGitLog(id: string, limit?: number): Promise<GitCommit[]> {
+  return invoke("note_git_log", { id, limit: limit ?? null });
+}
@@ -0,0 +1,28 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { DiffLine, Note, Version } from "@/types";
+
+/** Past versions of a note, newest first. */
+export async function listVersions(id: string): Promise<Version[]> {
+  return invoke("list_versions", { id });
+}
+
+export async function readVersion(id: string, versionId: string): Promise<Note> {
+  return invoke("read_version", { id, versionId });
+}
+
+/** Line diff from version `from` to version `to`, or to the current note. */
+export async function diffVersions(id: string, from: string, to?: string): Promise<DiffLine[]> {
+  return invoke("diff_versions", { id, from, to: to ?? null });
+}
+
+/**
+ * Replace a note with one of its versions and return the new revision. Rejects
+ * with a `WriteNoteError` like `writeNote`.
+ */
+export async function restoreVersion(
+  id: string,
+  versionId: string,
+  baseRevision: string | null,
+): Promise<string> {
+  return invoke("restore_version", { id, versionId, baseRevision });
+}
@@ -0,0 +1,29 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { CalendarEvent, Note } from "@/types";
+
+/** Days to list, `YYYY-MM-DD` inclusive; today and the following 30 days by default. */
+export interface IcsWindow {
+  from?: string;
+  to?: string;
+}
+
+/** Events in a local `.ics` file, recurring events expanded into occurrences. */
+export async function listIcsEvents(path: string, window: IcsWindow = {}): Promise<CalendarEvent[]> {
+  return invoke("list_ics_events", { path, from: window.from ?? null, to: window.to ?? null });
+}
+
+/** Create notes for the picked events; events imported before are skipped. */
+export async function importIcs(
+  path: string,
+  eventIds: string[],
+  window: IcsWindow = {},
+  folder?: string,
+): Promise<Note[]> {
+  return invoke("import_ics", {
+    path,
+    eventIds,
+    from: window.from ?? null,
+    to: window.to ?? null,
+    folder: folder ?? null,
+  });
+}
@@ -0,0 +1,19 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ImportKind, ImportReport } from "@/types";
+
+export interface ImportOptions {
+  /** Detected from the export when not given. */
+  kind?: ImportKind;
+  /** Folder to import into; the export's subfolders are kept below it. */
+  folder?: string;
+}
+
+/** What importing the folder or `.zip` at `path` would do; nothing is written. */
+export async function planImport(path: string, options: ImportOptions = {}): Promise<ImportReport> {
+  return invoke("import_notes", { path, kind: options.kind ?? null, folder: options.folder ?? null, apply: false });
+}
+
+/** Import the files `planImport` reports as new; duplicates are left out. */
+export async function importNotes(path: string, options: ImportOptions = {}): Promise<ImportReport> {
+  return invoke("import_notes", { path, kind: options.kind ?? null, folder: options.folder ?? null, apply: true });
+}
@@ -0,0 +1,36 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { Library, LibraryConfig, LibrarySettings, NoteStorage } from "@/types";
+
+export async function listLibraries(): Promise<LibraryConfig> {
+  return invoke("list_libraries");
+}
+
+export async function addLibrary(name: string, path: string): Promise<Library> {
+  return invoke("add_library", { name, path });
+}
+
+export async function switchLibrary(id: string): Promise<Library> {
+  return invoke("switch_library", { id });
+}
+
+export async function removeLibrary(id: string): Promise<void> {
+  return invoke("remove_library", { id });
+}
+
+export async function setModelsDir(path: string | null): Promise<void> {
+  return invoke("set_models_dir", { path });
+}
+
+export async function setLibrarySettings(id: string, settings: LibrarySettings): Promise<Library> {
+  return invoke("set_library_settings", { id, settings });
+}
+
+/** Convert the active library's notes to `storage`; resolves to how many changed. */
+export async function migrateStorage(storage: NoteStorage): Promise<number> {
+  return invoke("migrate_storage", { storage });
+}
+
+export function onLibraryChanged(fn: (library: Library) => void): Promise<UnlistenFn> {
+  return listen<Library>("library-changed", (event) => fn(event.payload));
+}
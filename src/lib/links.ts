@@ -0,0 +1,17 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Backlink, BrokenLink, LinkGraph } from "@/types";
+
+/** Notes with a `[[...]]` link to `id`, most recently updated first. */
+export async function getBacklinks(id: string): Promise<Backlink[]> {
+  return invoke("get_backlinks", { id });
+}
+
+/** Links that point at no note, or at more than one. */
+export async function listBrokenLinks(): Promise<BrokenLink[]> {
+  return invoke("list_broken_links");
+}
+
+/** Every note and the resolved links between them. */
+export async function exportLinkGraph(): Promise<LinkGraph> {
+  return invoke("export_link_graph");
+}
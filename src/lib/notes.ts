@@ -1,18 +1,63 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Note, NoteMeta, NoteSummary } from "@/types";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { Note, NoteMeta, NotePage, NoteQuery, TrashEntry } from "@/types";
 
-export async function listNotes(): Promise<NoteSummary[]> {
-  return invoke("list_notes");
+/** A page of notes; without a query, all notes, most recently updated first. */
+export async function listNotes(query?: NoteQuery): Promise<NotePage> {
+  return invoke("list_notes", { query: query ?? null });
 }
 
 export async function readNote(id: string): Promise<Note> {
   return invoke("read_note", { id });
 }
 
-export async function writeNote(id: string, content: string, meta: NoteMeta): Promise<void> {
-  return invoke("write_note", { id, content, meta });
+/** Create an empty note in `folder` (default: top level); its id is derived from the date and `title`. */
+export async function createNote(title: string, folder?: string): Promise<Note> {
+  return invoke("create_note", { title, folder: folder ?? null });
 }
 
-export async function deleteNote(id: string): Promise<void> {
+/** Move a note to the id matching `title`; resolves to the (possibly unchanged) id. */
+export async function renameNote(id: string, title: string): Promise<string> {
+  return invoke("rename_note", { id, title });
+}
+
+/**
+ * Save a note and return its new revision. `baseRevision` is the revision the
+ * edit started from (`null` for a new note); rejects with a `WriteNoteError`.
+ */
+export async function writeNote(
+  id: string,
+  content: string,
+  meta: NoteMeta,
+  baseRevision: string | null,
+): Promise<string> {
+  return invoke("write_note", { id, content, meta, baseRevision });
+}
+
+/** Move a note into `folder` (`null` for the top level); resolves to its new id. */
+export async function moveNote(id: string, folder: string | null): Promise<string> {
+  return invoke("move_note", { id, folder });
+}
+
+/** Three-way merge of note bodies; `null` if the edits overlap. */
+export async function mergeNote(base: string, ours: string, theirs: string): Promise<string | null> {
+  return invoke("merge_note", { base, ours, theirs });
+}
+
+/** Move a note to the trash; see `@/lib/trash` to restore it. */
+export async function deleteNote(id: string): Promise<TrashEntry> {
   return invoke("delete_note", { id });
 }
+
+export type NoteChange = "note-created" | "note-updated" | "note-deleted";
+
+/** Subscribe to notes changed on disk by something other than this app. */
+export async function onNoteChanged(
+  fn: (change: NoteChange, id: string) => void,
+): Promise<UnlistenFn> {
+  const events: NoteChange[] = ["note-created", "note-updated", "note-deleted"];
+  const unlisteners = await Promise.all(
+    events.map((name) => listen<{ id: string }>(name, (event) => fn(name, event.payload.id))),
+  );
+  return () => unlisteners.forEach((unlisten) => unlisten());
+}
@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Person, PersonInput, PersonView, UsageCount } from "@/types";
+
+export async function listPeople(): Promise<Person[]> {
+  return invoke("list_people");
+}
+
+/** Participants nobody in the directory answers to, most frequent first. */
+export async function listUnknownParticipants(): Promise<UsageCount[]> {
+  return invoke("list_unknown_participants");
+}
+
+export async function addPerson(person: PersonInput): Promise<Person> {
+  return invoke("add_person", { person });
+}
+
+export async function updatePerson(personId: string, person: PersonInput): Promise<Person> {
+  return invoke("update_person", { personId, person });
+}
+
+export async function removePerson(personId: string): Promise<void> {
+  return invoke("remove_person", { personId });
+}
+
+/** Fold `duplicates` into `personId`; their names become aliases. */
+export async function mergePeople(personId: string, duplicates: string[]): Promise<Person> {
+  return invoke("merge_people", { personId, duplicates });
+}
+
+/** A person with the meetings they attended and their open action items. */
+export async function getPerson(personId: string): Promise<PersonView> {
+  return invoke("get_person", { personId });
+}
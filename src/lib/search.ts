@@ -0,0 +1,10 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { SearchHit } from "@/types";
+
+export async function searchNotes(query: string, limit?: number): Promise<SearchHit[]> {
+  return invoke("search_notes", { query, limit });
+}
+
+export async function rebuildSearchIndex(): Promise<void> {
+  return invoke("rebuild_search_index");
+}
@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { InferredSeries, Note, Occurrence, SeriesLink, SeriesSummary } from "@/types";
+
+export async function listSeries(): Promise<SeriesSummary[]> {
+  return invoke("list_series");
+}
+
+/** The occurrences of a series, oldest first. */
+export async function getSeries(seriesId: string): Promise<Occurrence[]> {
+  return invoke("get_series", { seriesId });
+}
+
+/** The occurrence before or after a note in its series, if any. */
+export async function getAdjacentOccurrence(
+  id: string,
+  direction: "previous" | "next",
+): Promise<Occurrence | null> {
+  return invoke("get_adjacent_occurrence", { id, direction });
+}
+
+/** Put a note in a series (on its meeting day unless `occurrence` is given), or take it out with `null`. */
+export async function setNoteSeries(
+  id: string,
+  seriesId: string | null,
+  occurrence?: string,
+): Promise<SeriesLink | null> {
+  return invoke("set_note_series", { id, seriesId, occurrence: occurrence ?? null });
+}
+
+/** Append the previous occurrence's open action items; resolves to how many were added. */
+export async function carryForwardActionItems(id: string): Promise<number> {
+  return invoke("carry_forward_action_items", { id });
+}
+
+/** Create today's occurrence of a series with open action items carried forward. */
+export async function createNextOccurrence(seriesId: string, title?: string): Promise<Note> {
+  return invoke("create_next_occurrence", { seriesId, title: title ?? null });
+}
+
+/** Group notes whose titles differ only in dates; with `apply`, link them as series. */
+export async function inferSeries(apply: boolean): Promise<InferredSeries[]> {
+  return invoke("infer_series", { apply });
+}
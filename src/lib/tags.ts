@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { UsageCount } from "@/types";
+
+/** Tags used across the library, most used first. */
+export async function listTags(): Promise<UsageCount[]> {
+  return invoke("list_tags");
+}
+
+/** People in the library's notes, most frequent first. */
+export async function listParticipants(): Promise<UsageCount[]> {
+  return invoke("list_participants");
+}
@@ -0,0 +1,34 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Note, Template, TemplateInput } from "@/types";
+
+export async function listTemplates(): Promise<Template[]> {
+  return invoke("list_templates");
+}
+
+export async function createTemplate(template: TemplateInput): Promise<Template> {
+  return invoke("create_template", { template });
+}
+
+export async function updateTemplate(templateId: string, template: TemplateInput): Promise<Template> {
+  return invoke("update_template", { templateId, template });
+}
+
+export async function deleteTemplate(templateId: string): Promise<void> {
+  return invoke("delete_template", { templateId });
+}
+
+/**
+ * Create a note from a template with its placeholders filled in. `title`,
+ * `folder` and `participants` override the template's defaults.
+ */
+export async function createNoteFromTemplate(
+  templateId: string,
+  options: { title?: string; folder?: string; participants?: string[] } = {},
+): Promise<Note> {
+  return invoke("create_note_from_template", {
+    templateId,
+    title: options.title ?? null,
+    folder: options.folder ?? null,
+    participants: options.participants ?? null,
+  });
+}
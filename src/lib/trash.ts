@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { TrashEntry } from "@/types";
+
+export async function listTrash(): Promise<TrashEntry[]> {
+  return invoke("list_trash");
+}
+
+/** Restore a trashed note and return its id. */
+export async function restoreNote(trashId: string): Promise<string> {
+  return invoke("restore_note", { trashId });
+}
+
+/** Permanently delete one entry, or the whole trash when `trashId` is omitted. */
+export async function purgeTrash(trashId?: string): Promise<void> {
+  return invoke("purge_trash", { trashId: trashId ?? null });
+}
@@ -10,13 +10,6 @@ export function formatDate(iso: string): string {
   return d.toLocaleDateString("en-US", { month: "short", day: "numeric", year: "numeric" });
 }
 
-export function generateNoteId(): string {
-  const now = new Date();
-  const date = now.toISOString().slice(0, 10);
-  const rand = Math.random().toString(36).slice(2, 7);
-  return `${date}-${rand}`;
-}
-
 export function nowISO(): string {
   return new Date().toISOString();
 }
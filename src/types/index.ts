@@ -4,21 +4,94 @@ export interface NoteMeta {
   tags: string[];
   created_at: string;
   updated_at: string;
+  /** The recurring meeting this note is an occurrence of. */
+  series?: SeriesLink;
+  /** Frontmatter keys from other tools, preserved on save. */
+  [key: string]: unknown;
+}
+
+export interface SeriesLink {
+  series_id: string;
+  /** Day of the occurrence, `YYYY-MM-DD`. */
+  occurrence: string;
 }
 
 export interface Note {
   id: string;
   content: string;
   meta: NoteMeta;
+  /** Revision of the files as read; pass back to `writeNote`. */
+  revision: string;
+  /** Stored encrypted; saving keeps it that way. */
+  encrypted: boolean;
 }
 
+export type WriteNoteError =
+  | { kind: "conflict"; current: Note; attempted: Note }
+  | { kind: "failed"; message: string };
+
 export interface NoteSummary {
+  /** Includes the folder, e.g. `clients/acme/2026-02-18-kickoff`. */
   id: string;
+  /** Folder path with `/` separators; empty at the top level. */
+  folder: string;
   title: string;
   created_at: string;
   updated_at: string;
   tags: string[];
   preview: string;
+  /** Listed with a placeholder title and no preview while locked. */
+  encrypted: boolean;
+}
+
+export type NoteSort = "updated" | "created" | "title";
+
+/** Filters, order and page for `listNotes`; every field is optional. */
+export interface NoteQuery {
+  /** Notes in this folder or its subfolders. */
+  folder?: string;
+  tag?: string;
+  participant?: string;
+  /** Occurrences of this meeting series. */
+  series?: string;
+  /** Meeting day range, inclusive, as `YYYY-MM-DD`. */
+  date_from?: string;
+  date_to?: string;
+  sort?: NoteSort;
+  reverse?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+export interface NotePage {
+  notes: NoteSummary[];
+  /** Number of notes matching the query, across all pages. */
+  total: number;
+}
+
+/** A tag or participant and how many notes it appears in. */
+export interface UsageCount {
+  name: string;
+  count: number;
+}
+
+export interface Backlink {
+  note_id: string;
+  title: string;
+  /** First line of the linking note that holds the link. */
+  excerpt: string;
+}
+
+export interface BrokenLink {
+  note_id: string;
+  note_title: string;
+  target: string;
+  reason: "missing" | "ambiguous";
+}
+
+export interface LinkGraph {
+  nodes: { id: string; title: string; folder: string }[];
+  edges: { source: string; target: string }[];
 }
 
 export type AIProvider = "local" | "openai" | "anthropic";
@@ -30,3 +103,269 @@ export interface AISettings {
   anthropicKey?: string;
   localModel?: string;
 }
+
+export interface SearchHit {
+  id: string;
+  title: string;
+  score: number;
+  /** HTML fragment with matched terms wrapped in `<b>`. */
+  snippet: string;
+  tags: string[];
+  updated_at: string;
+}
+
+/** Where a note's metadata lives: a `.meta.json` sidecar or YAML frontmatter. */
+export type NoteStorage = "sidecar" | "frontmatter";
+
+export interface LibrarySettings {
+  trash_retention_days: number | null;
+  history_retention_days: number | null;
+  storage: NoteStorage;
+  /** Minutes unused after which encrypted notes lock again; null never. */
+  lock_after_minutes: number | null;
+}
+
+export interface Library {
+  id: string;
+  name: string;
+  path: string;
+  settings: LibrarySettings;
+}
+
+export type SnapshotReason = "autosave" | "before_ai" | "before_restore";
+
+export interface Version {
+  version_id: string;
+  created_at: string;
+  reason: SnapshotReason;
+  revision: string;
+  title: string;
+  size: number;
+  blob: string;
+}
+
+export interface DiffLine {
+  change: "equal" | "insert" | "delete";
+  text: string;
+}
+
+export type ActionStatus = "open" | "done";
+
+export interface ActionItem {
+  item_id: string;
+  text: string;
+  owner: string | null;
+  due: string | null;
+  status: ActionStatus;
+  created_at: string;
+  completed_at: string | null;
+  line: number;
+}
+
+export interface NoteActionItem extends ActionItem {
+  note_id: string;
+  note_title: string;
+}
+
+export interface Occurrence extends SeriesLink {
+  note_id: string;
+  title: string;
+}
+
+export interface SeriesSummary {
+  series_id: string;
+  /** Title of the latest occurrence. */
+  title: string;
+  count: number;
+  first: string;
+  last: string;
+}
+
+export interface InferredSeries {
+  series_id: string;
+  title: string;
+  /** Oldest first. */
+  note_ids: string[];
+}
+
+export interface CalendarEvent {
+  /** UID of the event, plus the start for occurrences of recurring events. */
+  event_id: string;
+  title: string;
+  start: string;
+  end: string | null;
+  all_day: boolean;
+  location: string | null;
+  attendees: string[];
+  description: string;
+  /** Series the occurrences of a recurring event are imported into. */
+  series_id: string | null;
+  /** Whether a note was already imported for this event. */
+  imported: boolean;
+}
+
+/** `print_html` is laid out for printing or saving as PDF. */
+export interface Attachment {
+  name: string;
+  size: number;
+  /** Path relative to the note, for links in its body. */
+  link: string;
+  /** An image or link to the attachment, ready to insert into the body. */
+  markdown: string;
+}
+
+export interface EncryptionStatus {
+  /** Whether the library has a passphrase. */
+  enabled: boolean;
+  unlocked: boolean;
+}
+
+export interface GitCommit {
+  id: string;
+  message: string;
+  author: string;
+  time: string;
+}
+
+export interface GitStatus {
+  /** Whether the library is a git repository. */
+  enabled: boolean;
+  branch: string | null;
+  remote: string | null;
+  /** Commits not yet pushed, and fetched commits not yet pulled. */
+  ahead: number;
+  behind: number;
+  /** Files changed since the last commit. */
+  uncommitted: number;
+  last_commit: GitCommit | null;
+}
+
+/** A note, or a file belonging to no note, changed on both sides of a pull. */
+export interface GitConflict {
+  /** Note id or file path; pick a side by it in `resolveGitConflicts`. */
+  key: string;
+  note_id: string | null;
+  /** Files changed on both sides, e.g. only the `.meta.json`. */
+  paths: string[];
+  /**
+   * Each side's version of the first text file in `paths`, the Markdown if it is one;
+   * null if deleted there, an attachment, or encrypted and locked.
+   */
+  ours: string | null;
+  theirs: string | null;
+}
+
+export type ConflictSide = "ours" | "theirs";
+
+export type PullResult =
+  | { status: "up_to_date" }
+  | { status: "fast_forward" }
+  | { status: "merged" }
+  | { status: "conflicts"; conflicts: GitConflict[] };
+
+export type ExportFormat = "html" | "print_html" | "docx";
+
+export type ImportKind = "markdown" | "obsidian" | "notion";
+
+/** `new` is a note a dry run would create. */
+export type ImportStatus = "new" | "imported" | "duplicate" | "skipped";
+
+export interface ImportItem {
+  /** Path of the file within the export. */
+  source: string;
+  status: ImportStatus;
+  /** The note created, or the one it duplicates. */
+  note_id: string | null;
+  title: string;
+  folder: string | null;
+  /** Meeting day, as `YYYY-MM-DD`. */
+  date: string;
+  participants: string[];
+  tags: string[];
+  reason: string | null;
+}
+
+export interface ImportReport {
+  kind: ImportKind;
+  items: ImportItem[];
+}
+
+export interface TemplateInput {
+  name: string;
+  /** Title of notes made from the template; may use placeholders. */
+  title: string;
+  participants: string[];
+  tags: string[];
+  folder: string | null;
+  body: string;
+}
+
+export interface Template extends TemplateInput {
+  template_id: string;
+}
+
+export interface Person {
+  person_id: string;
+  name: string;
+  aliases: string[];
+  email: string | null;
+  team: string | null;
+  notes: string;
+}
+
+export type PersonInput = Omit<Person, "person_id">;
+
+export interface PersonView {
+  person: Person;
+  /** Newest first. */
+  meetings: { note_id: string; title: string; date: string }[];
+  open_actions: NoteActionItem[];
+}
+
+export interface ActionFilter {
+  owner?: string;
+  due_from?: string;
+  due_to?: string;
+  include_done?: boolean;
+}
+
+export interface DecisionRef {
+  note_id: string;
+  decision_id: string;
+}
+
+export interface Decision {
+  decision_id: string;
+  statement: string;
+  rationale: string | null;
+  date: string;
+  participants: string[];
+  created_at: string;
+  superseded_by: DecisionRef | null;
+}
+
+export interface NoteDecision extends Decision {
+  note_id: string;
+  note_title: string;
+}
+
+export interface DecisionInput {
+  statement: string;
+  rationale?: string;
+  date?: string;
+  participants?: string[];
+}
+
+export interface TrashEntry {
+  trash_id: string;
+  id: string;
+  title: string;
+  deleted_at: string;
+  files: string[];
+}
+
+export interface LibraryConfig {
+  active: string;
+  libraries: Library[];
+  models_dir?: string | null;
+}